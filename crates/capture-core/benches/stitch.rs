@@ -0,0 +1,86 @@
+//! Overlap search benchmarks on 2x-scale (Retina) scroll frames.
+//!
+//! Run with `cargo bench -p capture-core`. The `exhaustive` entries are the
+//! pre-pyramid search, kept for comparison; `exact` includes hashing both
+//! frames.
+
+use capture_core::stitch::{
+    find_best_overlap, find_best_overlap_exhaustive, find_exact_overlap, row_hashes, ScrollBand,
+    Stitcher,
+};
+use capture_core::{MatchRegion, ScrollConfig};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use image::{Rgba, RgbaImage};
+
+/// Logical 1200x900 capture at 2x scale.
+const WIDTH: u32 = 2400;
+const HEIGHT: u32 = 1800;
+
+/// Text-like content: short dark runs on a light background, varying per row.
+fn page_pixel(x: u32, y: u32) -> Rgba<u8> {
+    let line = y / 36;
+    let in_text_row = y % 36 >= 8 && y % 36 < 28;
+    let word = (x / 24).wrapping_mul(2_654_435_761) ^ line.wrapping_mul(40_503);
+    let ink = in_text_row && !word.is_multiple_of(5) && (x + y * 3) % 7 < 4;
+    if ink {
+        Rgba([30, 30, 40, 255])
+    } else {
+        Rgba([250, 250, 248, 255])
+    }
+}
+
+fn frame_at(offset: u32) -> RgbaImage {
+    RgbaImage::from_fn(WIDTH, HEIGHT, |x, y| page_pixel(x, y + offset))
+}
+
+fn bench_overlap_search(c: &mut Criterion) {
+    let prev = frame_at(0);
+    let band = ScrollBand::full(HEIGHT);
+    let region = MatchRegion::default();
+    let mut group = c.benchmark_group("find_best_overlap_2x");
+    group.sample_size(10);
+
+    for delta in [240u32, 900] {
+        let current = frame_at(delta);
+        group.bench_with_input(
+            BenchmarkId::new("pyramid", delta),
+            &current,
+            |b, current| b.iter(|| find_best_overlap(&prev, current, band, region)),
+        );
+        group.bench_with_input(BenchmarkId::new("exact", delta), &current, |b, current| {
+            b.iter(|| {
+                let prev_hashes = row_hashes(&prev, region);
+                let current_hashes = row_hashes(current, region);
+                find_exact_overlap(&prev, &prev_hashes, current, &current_hashes, band, region)
+            })
+        });
+        group.bench_with_input(
+            BenchmarkId::new("exhaustive", delta),
+            &current,
+            |b, current| b.iter(|| find_best_overlap_exhaustive(&prev, current, band, region)),
+        );
+    }
+    group.finish();
+}
+
+fn bench_stitcher(c: &mut Criterion) {
+    let frames: Vec<RgbaImage> = (0..6).map(|index| frame_at(index * 600)).collect();
+    let mut group = c.benchmark_group("stitcher_2x");
+    group.sample_size(10);
+    group.bench_function("six_frames", |b| {
+        b.iter(|| {
+            let mut stitcher = Stitcher::new(ScrollConfig {
+                throttle_ms: 0,
+                ..ScrollConfig::default()
+            });
+            for frame in &frames {
+                let _ = stitcher.push(frame.clone());
+            }
+            stitcher.finish()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_overlap_search, bench_stitcher);
+criterion_main!(benches);
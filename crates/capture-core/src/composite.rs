@@ -0,0 +1,322 @@
+//! Compositing captures from several monitors into one image.
+//!
+//! A region may straddle displays with different scale factors. Each
+//! monitor's share of the region is cropped from its capture, resampled to
+//! a common output scale and placed by its desktop position; parts of the
+//! region between displays that do not touch stay transparent.
+//!
+//! [`composite_desktop`] does the same for the whole virtual desktop and
+//! returns a [`DesktopLayout`] saying where each monitor landed.
+
+use image::imageops::{crop_imm, replace, resize, FilterType};
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+
+use crate::geometry::{
+    desktop_bounds, monitors_intersecting, LogicalRect, MonitorInfo, PhysicalRect,
+};
+use crate::provider::MIN_CAPTURE_SIZE_PX;
+use crate::{CaptureError, CaptureErrorKind};
+
+/// The output scale that keeps every pixel of the sharpest monitor
+/// overlapping `rect`.
+pub fn region_output_scale(monitors: &[MonitorInfo], rect: LogicalRect) -> Option<f32> {
+    monitors_intersecting(monitors, rect)
+        .into_iter()
+        .map(|monitor| monitor.scale_factor)
+        .reduce(f32::max)
+}
+
+/// Where a monitor sits in a virtual desktop composite.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LayoutMonitor {
+    pub id: u32,
+    pub name: String,
+    pub is_primary: bool,
+    pub scale_factor: f32,
+    /// Desktop position in logical points.
+    pub logical: LogicalRect,
+    /// Position in the composite image, in its pixels.
+    pub pixels: PhysicalRect,
+}
+
+/// Layout map of a virtual desktop composite, serialized in camelCase like
+/// the `VirtualDesktopShot` it is sent in.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DesktopLayout {
+    /// Composite pixels per logical point.
+    pub output_scale: f32,
+    /// The desktop area the composite covers, in logical points.
+    pub bounds: LogicalRect,
+    pub width: u32,
+    pub height: u32,
+    pub monitors: Vec<LayoutMonitor>,
+}
+
+/// Composite `rect` from every monitor it overlaps at `output_scale`
+/// pixels per point. `capture` is called once per overlapping monitor.
+///
+/// The result covers `rect` clipped to the bounding box of those monitors.
+pub fn composite_region<F>(
+    monitors: &[MonitorInfo],
+    rect: LogicalRect,
+    output_scale: f32,
+    mut capture: F,
+) -> Result<RgbaImage, CaptureError>
+where
+    F: FnMut(&MonitorInfo) -> Result<RgbaImage, CaptureError>,
+{
+    let parts = monitors_intersecting(monitors, rect);
+    let covered = parts
+        .iter()
+        .map(|monitor| monitor.bounds())
+        .reduce(|bounds, monitor| bounds.union(&monitor))
+        .and_then(|bounds| bounds.intersection(&rect))
+        .ok_or_else(|| {
+            CaptureError::new(
+                CaptureErrorKind::CaptureFailed,
+                "Selected area is outside available monitors",
+            )
+        })?;
+
+    let output = covered.to_physical(output_scale);
+    if output.width < MIN_CAPTURE_SIZE_PX || output.height < MIN_CAPTURE_SIZE_PX {
+        return Err(CaptureError::new(
+            CaptureErrorKind::CaptureFailed,
+            "Selected area is too small",
+        ));
+    }
+
+    let mut canvas = RgbaImage::new(output.width, output.height);
+    for monitor in parts {
+        let Some(overlap) = monitor.bounds().intersection(&covered) else {
+            continue;
+        };
+        let image = capture(monitor)?;
+        let bounds = PhysicalRect::from_size(image.width(), image.height());
+        let Some(source) = monitor.to_local_physical(overlap).intersection(&bounds) else {
+            continue;
+        };
+        let target = output_rect(overlap, output, output_scale);
+
+        let mut part = crop_imm(
+            &image,
+            source.x as u32,
+            source.y as u32,
+            source.width,
+            source.height,
+        )
+        .to_image();
+        if (part.width(), part.height()) != (target.width, target.height) {
+            part = resize(&part, target.width, target.height, FilterType::Triangle);
+        }
+        replace(&mut canvas, &part, i64::from(target.x), i64::from(target.y));
+    }
+
+    Ok(canvas)
+}
+
+/// Capture the whole virtual desktop into one image at `output_scale`, with
+/// the position of every monitor in it.
+pub fn composite_desktop<F>(
+    monitors: &[MonitorInfo],
+    output_scale: f32,
+    capture: F,
+) -> Result<(RgbaImage, DesktopLayout), CaptureError>
+where
+    F: FnMut(&MonitorInfo) -> Result<RgbaImage, CaptureError>,
+{
+    let bounds = desktop_bounds(monitors).ok_or_else(|| {
+        CaptureError::new(CaptureErrorKind::CaptureFailed, "No monitors available")
+    })?;
+    let image = composite_region(monitors, bounds, output_scale, capture)?;
+    let output = bounds.to_physical(output_scale);
+    let layout = DesktopLayout {
+        output_scale,
+        bounds,
+        width: image.width(),
+        height: image.height(),
+        monitors: monitors
+            .iter()
+            .map(|monitor| LayoutMonitor {
+                id: monitor.id,
+                name: monitor.name.clone(),
+                is_primary: monitor.is_primary,
+                scale_factor: monitor.scale_factor,
+                logical: monitor.bounds(),
+                pixels: output_rect(monitor.bounds(), output, output_scale),
+            })
+            .collect(),
+    };
+    Ok((image, layout))
+}
+
+/// `rect` in the pixels of a composite whose top-left is `output`.
+fn output_rect(rect: LogicalRect, output: PhysicalRect, output_scale: f32) -> PhysicalRect {
+    rect.to_physical(output_scale)
+        .translate(-output.x, -output.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn monitor(id: u32, x: i32, y: i32, width: u32, height: u32, scale: f32) -> MonitorInfo {
+        MonitorInfo {
+            id,
+            name: format!("Display {}", id),
+            x,
+            y,
+            width,
+            height,
+            scale_factor: scale,
+            is_primary: id == 1,
+        }
+    }
+
+    /// A capture filled with the monitor id in the red channel.
+    fn solid_capture(monitor: &MonitorInfo) -> Result<RgbaImage, CaptureError> {
+        let (width, height) = monitor.physical_size();
+        Ok(RgbaImage::from_pixel(
+            width,
+            height,
+            Rgba([monitor.id as u8, 0, 0, 255]),
+        ))
+    }
+
+    #[test]
+    fn straddling_region_is_resampled_to_the_sharper_monitor() {
+        let monitors = vec![
+            monitor(1, 0, 0, 200, 100, 1.0),
+            monitor(2, 200, 0, 100, 100, 2.0),
+        ];
+        let rect = LogicalRect::new(180, 10, 40, 20);
+        let scale = region_output_scale(&monitors, rect).unwrap();
+        assert_eq!(scale, 2.0);
+
+        let mut captured = Vec::new();
+        let image = composite_region(&monitors, rect, scale, |monitor| {
+            captured.push(monitor.id);
+            solid_capture(monitor)
+        })
+        .unwrap();
+
+        assert_eq!(captured, vec![1, 2]);
+        assert_eq!(image.dimensions(), (80, 40));
+        assert_eq!(image.get_pixel(0, 0), &Rgba([1, 0, 0, 255]));
+        assert_eq!(image.get_pixel(39, 39), &Rgba([1, 0, 0, 255]));
+        assert_eq!(image.get_pixel(40, 0), &Rgba([2, 0, 0, 255]));
+        assert_eq!(image.get_pixel(79, 39), &Rgba([2, 0, 0, 255]));
+    }
+
+    #[test]
+    fn downscaling_keeps_the_region_size_in_points() {
+        let monitors = vec![
+            monitor(1, 0, 0, 200, 100, 1.0),
+            monitor(2, 200, 0, 100, 100, 2.0),
+        ];
+        let image = composite_region(
+            &monitors,
+            LogicalRect::new(180, 10, 40, 20),
+            1.0,
+            solid_capture,
+        )
+        .unwrap();
+        assert_eq!(image.dimensions(), (40, 20));
+        assert_eq!(image.get_pixel(19, 10)[0], 1);
+        assert_eq!(image.get_pixel(20, 10)[0], 2);
+    }
+
+    #[test]
+    fn gaps_between_displays_are_transparent() {
+        // Two displays with a 50pt gap, the second one lower.
+        let monitors = vec![
+            monitor(1, 0, 0, 100, 100, 1.0),
+            monitor(2, 150, 40, 100, 100, 1.0),
+        ];
+        let image = composite_region(
+            &monitors,
+            LogicalRect::new(80, 20, 100, 60),
+            1.0,
+            solid_capture,
+        )
+        .unwrap();
+
+        assert_eq!(image.dimensions(), (100, 60));
+        assert_eq!(image.get_pixel(10, 10), &Rgba([1, 0, 0, 255]));
+        assert_eq!(image.get_pixel(45, 30)[3], 0);
+        assert_eq!(image.get_pixel(80, 10)[3], 0);
+        assert_eq!(image.get_pixel(80, 30), &Rgba([2, 0, 0, 255]));
+    }
+
+    #[test]
+    fn region_is_clipped_to_the_covered_displays() {
+        let monitors = vec![monitor(1, 0, 0, 200, 100, 1.0)];
+        let image = composite_region(
+            &monitors,
+            LogicalRect::new(150, 50, 80, 80),
+            1.0,
+            solid_capture,
+        )
+        .unwrap();
+        assert_eq!(image.dimensions(), (50, 50));
+
+        let error = composite_region(
+            &monitors,
+            LogicalRect::new(300, 0, 50, 50),
+            1.0,
+            solid_capture,
+        )
+        .unwrap_err();
+        assert_eq!(error.kind, CaptureErrorKind::CaptureFailed);
+    }
+
+    #[test]
+    fn desktop_composite_places_monitors_by_logical_position() {
+        // A Retina laptop below and left of a 1x external display.
+        let monitors = vec![
+            monitor(1, 0, 60, 150, 100, 2.0),
+            monitor(2, 150, 0, 200, 120, 1.0),
+        ];
+        let (image, layout) = composite_desktop(&monitors, 1.0, solid_capture).unwrap();
+
+        assert_eq!(layout.bounds, LogicalRect::new(0, 0, 350, 160));
+        assert_eq!((layout.width, layout.height), image.dimensions());
+        assert_eq!(image.dimensions(), (350, 160));
+        assert_eq!(
+            layout.monitors[0].pixels,
+            PhysicalRect::new(0, 60, 150, 100)
+        );
+        assert_eq!(
+            layout.monitors[1].pixels,
+            PhysicalRect::new(150, 0, 200, 120)
+        );
+        assert_eq!(image.get_pixel(10, 70)[0], 1);
+        assert_eq!(image.get_pixel(200, 10)[0], 2);
+        // Above the laptop and below the external display.
+        assert_eq!(image.get_pixel(10, 10)[3], 0);
+        assert_eq!(image.get_pixel(200, 150)[3], 0);
+
+        let (image, layout) = composite_desktop(&monitors, 2.0, solid_capture).unwrap();
+        assert_eq!(image.dimensions(), (700, 320));
+        assert_eq!(
+            layout.monitors[1].pixels,
+            PhysicalRect::new(300, 0, 400, 240)
+        );
+    }
+
+    #[test]
+    fn desktop_layout_serializes_to_json() {
+        let monitors = vec![monitor(1, 0, 0, 100, 50, 1.0)];
+        let (_, layout) = composite_desktop(&monitors, 1.0, solid_capture).unwrap();
+        let value = serde_json::to_value(&layout).unwrap();
+        assert_eq!(value["outputScale"], 1.0);
+        assert_eq!(value["monitors"][0]["isPrimary"], true);
+        assert_eq!(value["monitors"][0]["scaleFactor"], 1.0);
+        assert_eq!(value["monitors"][0]["pixels"]["width"], 100);
+        assert_eq!(value["monitors"][0]["logical"]["y"], 0);
+    }
+}
@@ -0,0 +1,471 @@
+//! Desktop geometry.
+//!
+//! Monitors, windows and selections are in logical points in the global
+//! desktop space; captured images are in physical pixels. Conversions go
+//! through [`LogicalRect::to_physical`] and [`PhysicalRect::to_logical`],
+//! which round each edge rather than the size, so rects that share an edge
+//! in one space still share it in the other on any scale factor.
+
+use serde::{Deserialize, Serialize};
+
+macro_rules! rect_type {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+        pub struct $name {
+            pub x: i32,
+            pub y: i32,
+            pub width: u32,
+            pub height: u32,
+        }
+
+        impl $name {
+            pub const fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+                Self {
+                    x,
+                    y,
+                    width,
+                    height,
+                }
+            }
+
+            /// A `width` x `height` rect at the origin.
+            pub const fn from_size(width: u32, height: u32) -> Self {
+                Self::new(0, 0, width, height)
+            }
+
+            /// The rect spanning `left..right` and `top..bottom`; empty when
+            /// an edge is inverted.
+            pub fn from_edges(left: i32, top: i32, right: i32, bottom: i32) -> Self {
+                Self::new(
+                    left,
+                    top,
+                    right.saturating_sub(left).max(0) as u32,
+                    bottom.saturating_sub(top).max(0) as u32,
+                )
+            }
+
+            pub fn right(&self) -> i32 {
+                self.x.saturating_add_unsigned(self.width)
+            }
+
+            pub fn bottom(&self) -> i32 {
+                self.y.saturating_add_unsigned(self.height)
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.width == 0 || self.height == 0
+            }
+
+            pub fn area(&self) -> u64 {
+                u64::from(self.width) * u64::from(self.height)
+            }
+
+            pub fn center(&self) -> (f32, f32) {
+                (
+                    self.x as f32 + self.width as f32 / 2.0,
+                    self.y as f32 + self.height as f32 / 2.0,
+                )
+            }
+
+            /// Half-open: the right and bottom edges are outside.
+            pub fn contains_point(&self, x: f32, y: f32) -> bool {
+                x >= self.x as f32
+                    && x < self.right() as f32
+                    && y >= self.y as f32
+                    && y < self.bottom() as f32
+            }
+
+            pub fn contains(&self, other: &Self) -> bool {
+                !other.is_empty()
+                    && other.x >= self.x
+                    && other.y >= self.y
+                    && other.right() <= self.right()
+                    && other.bottom() <= self.bottom()
+            }
+
+            pub fn intersects(&self, other: &Self) -> bool {
+                self.intersection(other).is_some()
+            }
+
+            /// The overlapping area, or `None` if the rects do not overlap.
+            pub fn intersection(&self, other: &Self) -> Option<Self> {
+                let rect = Self::from_edges(
+                    self.x.max(other.x),
+                    self.y.max(other.y),
+                    self.right().min(other.right()),
+                    self.bottom().min(other.bottom()),
+                );
+                (!rect.is_empty()).then_some(rect)
+            }
+
+            /// The bounding box of both rects. Empty rects are ignored.
+            pub fn union(&self, other: &Self) -> Self {
+                if other.is_empty() {
+                    return *self;
+                }
+                if self.is_empty() {
+                    return *other;
+                }
+                Self::from_edges(
+                    self.x.min(other.x),
+                    self.y.min(other.y),
+                    self.right().max(other.right()),
+                    self.bottom().max(other.bottom()),
+                )
+            }
+
+            pub fn translate(&self, dx: i32, dy: i32) -> Self {
+                Self::new(
+                    self.x.saturating_add(dx),
+                    self.y.saturating_add(dy),
+                    self.width,
+                    self.height,
+                )
+            }
+        }
+    };
+}
+
+rect_type!(
+    /// A rect in logical points.
+    LogicalRect
+);
+
+rect_type!(
+    /// A rect in physical pixels.
+    PhysicalRect
+);
+
+/// Scale both edges of a span and round them, keeping a non-empty span at
+/// least one unit wide.
+fn scale_span(start: i32, len: u32, factor: f64) -> (i32, u32) {
+    let scaled_start = (f64::from(start) * factor).round();
+    let scaled_end = ((f64::from(start) + f64::from(len)) * factor).round();
+    let scaled_len = (scaled_end - scaled_start).max(0.0) as u32;
+    let scaled_len = if len > 0 { scaled_len.max(1) } else { 0 };
+    (scaled_start as i32, scaled_len)
+}
+
+impl LogicalRect {
+    pub fn to_physical(&self, scale_factor: f32) -> PhysicalRect {
+        let factor = f64::from(scale_factor);
+        let (x, width) = scale_span(self.x, self.width, factor);
+        let (y, height) = scale_span(self.y, self.height, factor);
+        PhysicalRect::new(x, y, width, height)
+    }
+}
+
+impl PhysicalRect {
+    pub fn to_logical(&self, scale_factor: f32) -> LogicalRect {
+        let factor = 1.0 / f64::from(scale_factor);
+        let (x, width) = scale_span(self.x, self.width, factor);
+        let (y, height) = scale_span(self.y, self.height, factor);
+        LogicalRect::new(x, y, width, height)
+    }
+
+    /// The part of the rect inside a `width` x `height` image. A rect that
+    /// misses the image comes back empty, with its origin pulled onto the
+    /// image.
+    pub fn clamp_to_size(&self, width: u32, height: u32) -> Self {
+        let image = Self::from_size(width, height);
+        self.intersection(&image).unwrap_or_else(|| {
+            Self::new(
+                self.x.clamp(0, image.right()),
+                self.y.clamp(0, image.bottom()),
+                0,
+                0,
+            )
+        })
+    }
+}
+
+/// A display as reported by a capture backend.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MonitorInfo {
+    pub id: u32,
+    pub name: String,
+    /// Logical position and size.
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub scale_factor: f32,
+    pub is_primary: bool,
+}
+
+impl MonitorInfo {
+    pub fn bounds(&self) -> LogicalRect {
+        LogicalRect::new(self.x, self.y, self.width, self.height)
+    }
+
+    pub fn contains_point(&self, x: f32, y: f32) -> bool {
+        self.bounds().contains_point(x, y)
+    }
+
+    /// Size of a full capture of this monitor, in pixels.
+    pub fn physical_size(&self) -> (u32, u32) {
+        let size = LogicalRect::from_size(self.width, self.height).to_physical(self.scale_factor);
+        (size.width, size.height)
+    }
+
+    /// `rect`, in desktop points, as pixels of a capture of this monitor.
+    pub fn to_local_physical(&self, rect: LogicalRect) -> PhysicalRect {
+        rect.translate(-self.x, -self.y)
+            .to_physical(self.scale_factor)
+    }
+
+    /// Pixels of a capture of this monitor as desktop points.
+    pub fn to_desktop_logical(&self, rect: PhysicalRect) -> LogicalRect {
+        rect.to_logical(self.scale_factor).translate(self.x, self.y)
+    }
+}
+
+/// The monitor containing the point `(x, y)`.
+pub fn monitor_at_point(monitors: &[MonitorInfo], x: f32, y: f32) -> Option<&MonitorInfo> {
+    monitors.iter().find(|monitor| monitor.contains_point(x, y))
+}
+
+/// The monitor under the center of `rect`.
+pub fn monitor_at(monitors: &[MonitorInfo], rect: LogicalRect) -> Option<&MonitorInfo> {
+    let (x, y) = rect.center();
+    monitor_at_point(monitors, x, y)
+}
+
+/// Monitors overlapping `rect`, in the order given.
+pub fn monitors_intersecting(monitors: &[MonitorInfo], rect: LogicalRect) -> Vec<&MonitorInfo> {
+    monitors
+        .iter()
+        .filter(|monitor| monitor.bounds().intersects(&rect))
+        .collect()
+}
+
+/// The bounding box of all monitors, or `None` if there are none.
+pub fn desktop_bounds(monitors: &[MonitorInfo]) -> Option<LogicalRect> {
+    monitors
+        .iter()
+        .map(MonitorInfo::bounds)
+        .reduce(|bounds, monitor| bounds.union(&monitor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn monitor(id: u32, x: i32, y: i32, width: u32, height: u32, scale: f32) -> MonitorInfo {
+        MonitorInfo {
+            id,
+            name: format!("Display {}", id),
+            x,
+            y,
+            width,
+            height,
+            scale_factor: scale,
+            is_primary: id == 1,
+        }
+    }
+
+    /// A 1440x900 Retina laptop with a 1920x1080 external display to its
+    /// right, top-aligned, and a 1.5x display below.
+    fn mixed_dpi_desktop() -> Vec<MonitorInfo> {
+        vec![
+            monitor(1, 0, 0, 1440, 900, 2.0),
+            monitor(2, 1440, 0, 1920, 1080, 1.0),
+            monitor(3, 0, 900, 1280, 720, 1.5),
+        ]
+    }
+
+    #[test]
+    fn intersection_and_union_of_overlapping_rects() {
+        let a = LogicalRect::new(0, 0, 100, 50);
+        let b = LogicalRect::new(60, 20, 100, 100);
+        assert_eq!(a.intersection(&b), Some(LogicalRect::new(60, 20, 40, 30)));
+        assert_eq!(a.union(&b), LogicalRect::new(0, 0, 160, 120));
+
+        let touching = LogicalRect::new(100, 0, 10, 10);
+        assert_eq!(a.intersection(&touching), None);
+        assert_eq!(a.union(&LogicalRect::default()), a);
+    }
+
+    #[test]
+    fn hit_testing_uses_half_open_monitor_bounds() {
+        let monitors = mixed_dpi_desktop();
+        assert_eq!(monitor_at_point(&monitors, 1439.5, 10.0).unwrap().id, 1);
+        assert_eq!(monitor_at_point(&monitors, 1440.0, 10.0).unwrap().id, 2);
+        assert_eq!(monitor_at_point(&monitors, 10.0, 900.0).unwrap().id, 3);
+        assert!(monitor_at_point(&monitors, 2000.0, 1000.0).is_some());
+        assert!(monitor_at_point(&monitors, 2000.0, 1100.0).is_none());
+
+        let straddling = LogicalRect::new(1200, 880, 300, 40);
+        let ids: Vec<u32> = monitors_intersecting(&monitors, straddling)
+            .iter()
+            .map(|m| m.id)
+            .collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert_eq!(
+            desktop_bounds(&monitors),
+            Some(LogicalRect::new(0, 0, 3360, 1620))
+        );
+    }
+
+    #[test]
+    fn monitor_local_pixels_follow_each_scale_factor() {
+        let monitors = mixed_dpi_desktop();
+        let rect = LogicalRect::new(1450, 10, 30, 20);
+        assert_eq!(
+            monitors[1].to_local_physical(rect),
+            PhysicalRect::new(10, 10, 30, 20)
+        );
+        let rect = LogicalRect::new(10, 910, 31, 21);
+        let local = monitors[2].to_local_physical(rect);
+        assert_eq!(local, PhysicalRect::new(15, 15, 47, 32));
+        assert_eq!(monitors[2].physical_size(), (1920, 1080));
+        assert_eq!(monitors[0].physical_size(), (2880, 1800));
+    }
+
+    #[test]
+    fn clamp_to_size_within_bounds() {
+        let region = PhysicalRect::new(100, 100, 200, 200).clamp_to_size(1920, 1080);
+        assert_eq!(region, PhysicalRect::new(100, 100, 200, 200));
+    }
+
+    #[test]
+    fn clamp_to_size_cuts_at_image_edge() {
+        let region = PhysicalRect::new(1800, 1000, 500, 500).clamp_to_size(1920, 1080);
+        assert_eq!(region, PhysicalRect::new(1800, 1000, 120, 80));
+    }
+
+    #[test]
+    fn clamp_to_size_cuts_negative_origins() {
+        let region = PhysicalRect::new(-50, 0, 200, 100).clamp_to_size(1920, 1080);
+        assert_eq!(region, PhysicalRect::new(0, 0, 150, 100));
+        let region = PhysicalRect::new(10, -30, 40, 40).clamp_to_size(1920, 1080);
+        assert_eq!(region, PhysicalRect::new(10, 0, 40, 10));
+    }
+
+    #[test]
+    fn clamp_to_size_is_empty_outside_the_image() {
+        let region = PhysicalRect::new(2000, 2000, 100, 100).clamp_to_size(1920, 1080);
+        assert!(region.is_empty());
+        assert_eq!((region.x, region.y), (1920, 1080));
+        assert!(PhysicalRect::new(-200, 0, 100, 100)
+            .clamp_to_size(1920, 1080)
+            .is_empty());
+
+        let full = PhysicalRect::new(0, 0, 1920, 1080).clamp_to_size(1920, 1080);
+        assert_eq!(full, PhysicalRect::from_size(1920, 1080));
+        assert!(PhysicalRect::new(0, 0, 0, 100).is_empty());
+    }
+
+    fn rect_strategy() -> impl Strategy<Value = LogicalRect> {
+        (-5000..5000i32, -5000..5000i32, 0..3000u32, 0..3000u32)
+            .prop_map(|(x, y, width, height)| LogicalRect::new(x, y, width, height))
+    }
+
+    fn scale_strategy() -> impl Strategy<Value = f32> {
+        prop_oneof![
+            Just(1.0f32),
+            Just(1.25),
+            Just(1.5),
+            Just(1.75),
+            Just(2.0),
+            Just(3.0)
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn intersection_lies_in_both_rects(a in rect_strategy(), b in rect_strategy()) {
+            prop_assert_eq!(a.intersection(&b), b.intersection(&a));
+            if let Some(overlap) = a.intersection(&b) {
+                prop_assert!(a.contains(&overlap));
+                prop_assert!(b.contains(&overlap));
+            }
+        }
+
+        #[test]
+        fn union_contains_both_rects(a in rect_strategy(), b in rect_strategy()) {
+            let union = a.union(&b);
+            prop_assert_eq!(union, b.union(&a));
+            if !a.is_empty() {
+                prop_assert!(union.contains(&a));
+            }
+            if !b.is_empty() {
+                prop_assert!(union.contains(&b));
+            }
+        }
+
+        #[test]
+        fn adjacent_rects_stay_adjacent_in_pixels(
+            rect in rect_strategy(),
+            extra in 1..500u32,
+            scale in scale_strategy(),
+        ) {
+            let next = LogicalRect::new(rect.right(), rect.y, extra, rect.height);
+            let left = rect.to_physical(scale);
+            let right = next.to_physical(scale);
+            if !rect.is_empty() {
+                prop_assert_eq!(left.right(), right.x);
+                prop_assert!(left.intersection(&right).is_none());
+            }
+        }
+
+        #[test]
+        fn logical_rects_survive_a_physical_round_trip(rect in rect_strategy(), scale in scale_strategy()) {
+            // Pixels are at least as fine as points, so rounding each edge
+            // to pixels and back is lossless.
+            prop_assert_eq!(rect.to_physical(scale).to_logical(scale), rect);
+        }
+
+        #[test]
+        fn clamped_rect_fits_the_image(
+            x in -5000..5000i32,
+            y in -5000..5000i32,
+            width in 0..3000u32,
+            height in 0..3000u32,
+            image_width in 1..4000u32,
+            image_height in 1..4000u32,
+        ) {
+            let rect = PhysicalRect::new(x, y, width, height);
+            let clamped = rect.clamp_to_size(image_width, image_height);
+            let image = PhysicalRect::from_size(image_width, image_height);
+            if rect.intersects(&image) {
+                prop_assert!(image.contains(&clamped));
+            } else {
+                prop_assert!(clamped.is_empty());
+            }
+            prop_assert!(clamped.right() <= image.right());
+            prop_assert!(clamped.bottom() <= image.bottom());
+        }
+
+        #[test]
+        fn clamping_negative_origins_keeps_the_same_pixels(
+            x in -5000..0i32,
+            y in -5000..0i32,
+            width in 1..6000u32,
+            height in 1..6000u32,
+            image_width in 1..4000u32,
+            image_height in 1..4000u32,
+        ) {
+            let rect = PhysicalRect::new(x, y, width, height);
+            let clamped = rect.clamp_to_size(image_width, image_height);
+            if rect.right() <= 0 || rect.bottom() <= 0 {
+                prop_assert!(clamped.is_empty());
+            } else {
+                prop_assert_eq!((clamped.x, clamped.y), (0, 0));
+                prop_assert_eq!(clamped.right(), rect.right().min(image_width as i32));
+                prop_assert_eq!(clamped.bottom(), rect.bottom().min(image_height as i32));
+                prop_assert!(rect.contains(&clamped));
+            }
+        }
+
+        #[test]
+        fn every_desktop_point_hits_at_most_one_monitor(x in -100.0..3500.0f32, y in -100.0..1700.0f32) {
+            let monitors = mixed_dpi_desktop();
+            let hits = monitors.iter().filter(|m| m.contains_point(x, y)).count();
+            prop_assert!(hits <= 1);
+            prop_assert_eq!(hits == 1, monitor_at_point(&monitors, x, y).is_some());
+        }
+    }
+}
@@ -1,6 +1,23 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+pub mod composite;
+pub mod geometry;
+pub mod provider;
+#[cfg(feature = "replay")]
+pub mod replay;
+pub mod stitch;
+
+pub use geometry::{LogicalRect, PhysicalRect};
+pub use provider::ScreenCaptureProvider;
+
+/// Error categories shared with the frontend, serialized in snake_case
+/// (`"permission"`, `"ocr_empty"`, ...).
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
 pub enum CaptureErrorKind {
     Permission,
     Cancelled,
@@ -8,26 +25,79 @@ pub enum CaptureErrorKind {
     StitchFailed,
     CommandFailed,
     ValidationFailed,
+    /// Reading, writing, encoding or decoding files and images.
+    Io,
+    Timeout,
+    /// OCR ran but found no text.
+    OcrEmpty,
+    /// Another capture is already running.
+    Busy,
+    /// The operation is not available on this platform or backend.
+    Unsupported,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct CaptureError {
     pub kind: CaptureErrorKind,
     pub message: String,
+    /// Structured context such as the backend or session id.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<BTreeMap<String, String>>,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
-pub struct CaptureRect {
-    pub x: i32,
-    pub y: i32,
-    pub width: u32,
-    pub height: u32,
+impl CaptureError {
+    pub fn new(kind: CaptureErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn io(message: impl Into<String>) -> Self {
+        Self::new(CaptureErrorKind::Io, message)
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::new(CaptureErrorKind::ValidationFailed, message)
+    }
+
+    pub fn command(message: impl Into<String>) -> Self {
+        Self::new(CaptureErrorKind::CommandFailed, message)
+    }
+
+    /// `operation` is not available on the `backend` capture backend.
+    pub fn unsupported(backend: &str, operation: &str) -> Self {
+        Self::new(
+            CaptureErrorKind::Unsupported,
+            format!("{} is not supported by the {} backend", operation, backend),
+        )
+        .with_detail("backend", backend)
+    }
+
+    pub fn with_detail(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.details
+            .get_or_insert_with(BTreeMap::new)
+            .insert(key.into(), value.into());
+        self
+    }
+
+    pub fn detail(&self, key: &str) -> Option<&str> {
+        self.details.as_ref()?.get(key).map(String::as_str)
+    }
 }
 
-pub trait ScreenCaptureProvider: Send + Sync {
-    fn capture_region(&self, rect: CaptureRect) -> Result<Vec<u8>, CaptureError>;
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
 }
 
+impl std::error::Error for CaptureError {}
+
+/// A selection in logical desktop points.
+pub type CaptureRect = LogicalRect;
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum ScrollSessionState {
     Ready,
@@ -42,6 +112,10 @@ pub enum SkipReason {
     Duplicate,
     TooSmallDelta,
     MatchFailed,
+    /// The frame arrived within `throttle_ms` of the previous one.
+    Throttled,
+    /// The session is paused.
+    Paused,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -49,6 +123,7 @@ pub enum StopReason {
     User,
     Timeout,
     ReachedMaxHeight,
+    ReachedMaxFrames,
     NoNewContent,
     ConsecutiveFailures,
 }
@@ -60,12 +135,72 @@ pub enum AppendResult {
     AutoStopped(StopReason),
 }
 
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ScrollDirection {
+    #[default]
+    Vertical,
+    Horizontal,
+}
+
+/// Band of the frame, across the scroll direction, that is compared when
+/// matching frames and detecting changes. Percentages of the frame width for
+/// vertical scrolling, or of the frame height for horizontal scrolling.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MatchRegion {
+    pub start_percent: u8,
+    pub end_percent: u8,
+}
+
+impl Default for MatchRegion {
+    fn default() -> Self {
+        Self {
+            start_percent: 15,
+            end_percent: 85,
+        }
+    }
+}
+
+impl MatchRegion {
+    /// Pixel range `start..end` covered within `size`. Never empty unless
+    /// `size` is zero.
+    pub fn span(&self, size: u32) -> (u32, u32) {
+        let start_percent = u32::from(self.start_percent.min(99));
+        let end_percent = u32::from(self.end_percent).clamp(start_percent + 1, 100);
+        let start = (size as u64 * start_percent as u64 / 100) as u32;
+        let end = (size as u64 * end_percent as u64 / 100) as u32;
+        (
+            start.min(size.saturating_sub(1)),
+            end.max(start + 1).min(size),
+        )
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ScrollConfig {
     pub max_height_px: u32,
     pub max_frames: usize,
     pub throttle_ms: u64,
     pub max_consecutive_failures: u8,
+    #[serde(default)]
+    pub direction: ScrollDirection,
+    #[serde(default)]
+    pub match_region: MatchRegion,
+    /// Stop with `NoNewContent` after this many duplicate or too-small frames
+    /// in a row. 0 disables the check.
+    #[serde(default = "default_max_idle_frames")]
+    pub max_idle_frames: u32,
+    /// Stop with `Timeout` when no content has been added for this long while
+    /// capturing. Time spent paused does not count. 0 disables the check.
+    #[serde(default = "default_idle_timeout_ms")]
+    pub idle_timeout_ms: u64,
+}
+
+fn default_max_idle_frames() -> u32 {
+    20
+}
+
+fn default_idle_timeout_ms() -> u64 {
+    120_000
 }
 
 impl Default for ScrollConfig {
@@ -75,8 +210,60 @@ impl Default for ScrollConfig {
             max_frames: 300,
             throttle_ms: 100,
             max_consecutive_failures: 3,
+            direction: ScrollDirection::Vertical,
+            match_region: MatchRegion::default(),
+            max_idle_frames: default_max_idle_frames(),
+            idle_timeout_ms: default_idle_timeout_ms(),
+        }
+    }
+}
+
+/// Time source for `ScrollSession` throttle and timeout checks.
+pub trait Clock: Send + Sync + fmt::Debug {
+    fn now(&self) -> Instant;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Clock that only moves when told to, for tests and replayed sessions.
+/// Clones share the same time.
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    origin: Instant,
+    elapsed: Arc<Mutex<Duration>>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            elapsed: Arc::new(Mutex::new(Duration::ZERO)),
         }
     }
+
+    pub fn advance(&self, by: Duration) {
+        let mut elapsed = self.elapsed.lock().unwrap_or_else(|e| e.into_inner());
+        *elapsed += by;
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.origin + *self.elapsed.lock().unwrap_or_else(|e| e.into_inner())
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -86,45 +273,197 @@ pub struct ScrollProgress {
     pub state: ScrollSessionState,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+/// Scroll capture state machine.
+///
+/// - `Ready` -> `Capturing` on `mark_capturing` or the first `begin_frame`.
+/// - `Capturing` <-> `Paused` via `pause` and `resume`.
+/// - Any live state -> `Done` on `cancel`, a size or frame limit, the idle
+///   timeout, or too many frames without new content.
+/// - Any live state -> `Error` after `max_consecutive_failures` failed matches.
+///
+/// Callers gate each frame with `begin_frame` before doing any matching work,
+/// then report the outcome with `append_accepted`, `append_skipped` or
+/// `append_failed`. `Done` and `Error` are final.
+#[derive(Debug, Clone)]
 pub struct ScrollSession {
     state: ScrollSessionState,
     config: ScrollConfig,
+    clock: Arc<dyn Clock>,
     frames: usize,
     captured_height_px: u32,
     consecutive_failures: u8,
+    idle_frames: u32,
+    stop_reason: Option<StopReason>,
+    last_frame_at: Option<Instant>,
+    last_progress_at: Option<Instant>,
+    /// When a running session was paused, so the idle timeout can skip it.
+    paused_at: Option<Instant>,
 }
 
 impl ScrollSession {
     pub fn new(config: ScrollConfig) -> Self {
+        Self::with_clock(config, Arc::new(SystemClock))
+    }
+
+    pub fn with_clock(config: ScrollConfig, clock: Arc<dyn Clock>) -> Self {
         Self {
             state: ScrollSessionState::Ready,
             config,
+            clock,
             frames: 0,
             captured_height_px: 0,
             consecutive_failures: 0,
+            idle_frames: 0,
+            stop_reason: None,
+            last_frame_at: None,
+            last_progress_at: None,
+            paused_at: None,
         }
     }
 
+    /// Start capturing, or resume a paused session. No-op otherwise. The idle
+    /// timeout starts on the first call; a resumed session keeps the idle
+    /// time it had before the pause.
     pub fn mark_capturing(&mut self) {
-        if self.state == ScrollSessionState::Ready || self.state == ScrollSessionState::Paused {
-            self.state = ScrollSessionState::Capturing;
+        let now = self.clock.now();
+        match self.state {
+            ScrollSessionState::Ready => self.last_progress_at = Some(now),
+            ScrollSessionState::Paused => {
+                let paused = self
+                    .paused_at
+                    .take()
+                    .map_or(Duration::ZERO, |at| now.saturating_duration_since(at));
+                self.last_progress_at = Some(self.last_progress_at.map_or(now, |at| at + paused));
+            }
+            _ => return,
+        }
+        self.state = ScrollSessionState::Capturing;
+    }
+
+    pub fn pause(&mut self) -> Result<(), CaptureError> {
+        match self.state {
+            ScrollSessionState::Capturing | ScrollSessionState::Paused => {
+                if self.state == ScrollSessionState::Capturing {
+                    self.paused_at = Some(self.clock.now());
+                }
+                self.state = ScrollSessionState::Paused;
+                Ok(())
+            }
+            state => Err(invalid_transition(state, "pause")),
+        }
+    }
+
+    pub fn resume(&mut self) -> Result<(), CaptureError> {
+        match self.state {
+            ScrollSessionState::Paused | ScrollSessionState::Capturing => {
+                self.mark_capturing();
+                Ok(())
+            }
+            state => Err(invalid_transition(state, "resume")),
+        }
+    }
+
+    /// Stop at the user's request. Finished sessions keep their reason.
+    pub fn cancel(&mut self) {
+        if !self.is_finished() {
+            self.stop(ScrollSessionState::Done, StopReason::User);
+        }
+    }
+
+    /// Put a session rebuilt from saved frames back in the state it was
+    /// saved in, including how it stopped. A stop reason is required for,
+    /// and only allowed with, a finished state.
+    pub fn restore_state(
+        &mut self,
+        state: ScrollSessionState,
+        stop_reason: Option<StopReason>,
+    ) -> Result<(), CaptureError> {
+        let finished = state == ScrollSessionState::Done || state == ScrollSessionState::Error;
+        if finished != stop_reason.is_some() {
+            return Err(CaptureError::new(
+                CaptureErrorKind::ValidationFailed,
+                format!(
+                    "Cannot restore a scroll session that is {:?} with stop reason {:?}",
+                    state, stop_reason
+                ),
+            ));
+        }
+        self.state = state;
+        self.stop_reason = stop_reason;
+        Ok(())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.state == ScrollSessionState::Done || self.state == ScrollSessionState::Error
+    }
+
+    pub fn state(&self) -> ScrollSessionState {
+        self.state
+    }
+
+    pub fn stop_reason(&self) -> Option<StopReason> {
+        self.stop_reason
+    }
+
+    /// Stop with `Timeout` if nothing was added for `idle_timeout_ms` while
+    /// capturing. Meant to be polled even when no frames arrive.
+    pub fn check_timeout(&mut self) -> Option<AppendResult> {
+        if self.state != ScrollSessionState::Capturing || self.config.idle_timeout_ms == 0 {
+            return None;
+        }
+        let since = self.last_progress_at?;
+        if self.clock.now().saturating_duration_since(since)
+            < Duration::from_millis(self.config.idle_timeout_ms)
+        {
+            return None;
         }
+        Some(self.stop(ScrollSessionState::Done, StopReason::Timeout))
+    }
+
+    /// Decide whether a new frame should be processed at all. Returns the
+    /// result to report instead when the session is finished, paused, timed
+    /// out or throttled; otherwise starts the session if needed and returns
+    /// `None`. Only accepted frames start a new throttle interval.
+    pub fn begin_frame(&mut self) -> Option<AppendResult> {
+        if let Some(stopped) = self.stopped() {
+            return Some(stopped);
+        }
+        if self.state == ScrollSessionState::Paused {
+            return Some(AppendResult::Skipped(SkipReason::Paused));
+        }
+        self.mark_capturing();
+        if let Some(stopped) = self.check_timeout() {
+            return Some(stopped);
+        }
+
+        let throttle = Duration::from_millis(self.config.throttle_ms);
+        if self
+            .last_frame_at
+            .is_some_and(|last| self.clock.now().saturating_duration_since(last) < throttle)
+        {
+            return Some(AppendResult::Skipped(SkipReason::Throttled));
+        }
+        None
     }
 
     pub fn append_accepted(&mut self, added_height: u32, score: f64) -> AppendResult {
+        if let Some(stopped) = self.stopped() {
+            return stopped;
+        }
         self.frames += 1;
         self.captured_height_px = self.captured_height_px.saturating_add(added_height);
         self.consecutive_failures = 0;
+        self.idle_frames = 0;
+        let now = self.clock.now();
+        self.last_frame_at = Some(now);
+        self.last_progress_at = Some(now);
 
         if self.captured_height_px >= self.config.max_height_px {
-            self.state = ScrollSessionState::Done;
-            return AppendResult::AutoStopped(StopReason::ReachedMaxHeight);
+            return self.stop(ScrollSessionState::Done, StopReason::ReachedMaxHeight);
         }
 
         if self.frames >= self.config.max_frames {
-            self.state = ScrollSessionState::Done;
-            return AppendResult::AutoStopped(StopReason::ReachedMaxHeight);
+            return self.stop(ScrollSessionState::Done, StopReason::ReachedMaxFrames);
         }
 
         self.state = ScrollSessionState::Capturing;
@@ -134,17 +473,41 @@ impl ScrollSession {
         }
     }
 
+    /// Record a frame that added nothing. Duplicates and too-small deltas
+    /// count towards `max_idle_frames`; match failures go through
+    /// `append_failed`.
+    pub fn append_skipped(&mut self, reason: SkipReason) -> AppendResult {
+        if let Some(stopped) = self.stopped() {
+            return stopped;
+        }
+        match reason {
+            SkipReason::MatchFailed => self.append_failed(),
+            SkipReason::Duplicate | SkipReason::TooSmallDelta => {
+                self.idle_frames = self.idle_frames.saturating_add(1);
+                if self.config.max_idle_frames > 0
+                    && self.idle_frames >= self.config.max_idle_frames
+                {
+                    return self.stop(ScrollSessionState::Done, StopReason::NoNewContent);
+                }
+                AppendResult::Skipped(reason)
+            }
+            SkipReason::Throttled | SkipReason::Paused => AppendResult::Skipped(reason),
+        }
+    }
+
     pub fn append_failed(&mut self) -> AppendResult {
+        if let Some(stopped) = self.stopped() {
+            return stopped;
+        }
         self.consecutive_failures = self.consecutive_failures.saturating_add(1);
         if self.consecutive_failures >= self.config.max_consecutive_failures {
-            self.state = ScrollSessionState::Error;
-            return AppendResult::AutoStopped(StopReason::ConsecutiveFailures);
+            return self.stop(ScrollSessionState::Error, StopReason::ConsecutiveFailures);
         }
         AppendResult::Skipped(SkipReason::MatchFailed)
     }
 
-    pub fn cancel(&mut self) {
-        self.state = ScrollSessionState::Done;
+    pub fn config(&self) -> &ScrollConfig {
+        &self.config
     }
 
     pub fn progress(&self) -> ScrollProgress {
@@ -154,6 +517,29 @@ impl ScrollSession {
             state: self.state,
         }
     }
+
+    fn stop(&mut self, state: ScrollSessionState, reason: StopReason) -> AppendResult {
+        self.state = state;
+        self.stop_reason = Some(reason);
+        AppendResult::AutoStopped(reason)
+    }
+
+    /// The stop result to keep reporting once the session has finished.
+    fn stopped(&self) -> Option<AppendResult> {
+        if !self.is_finished() {
+            return None;
+        }
+        Some(AppendResult::AutoStopped(
+            self.stop_reason.unwrap_or(StopReason::User),
+        ))
+    }
+}
+
+fn invalid_transition(state: ScrollSessionState, action: &str) -> CaptureError {
+    CaptureError::new(
+        CaptureErrorKind::ValidationFailed,
+        format!("Cannot {} a scroll session that is {:?}", action, state),
+    )
 }
 
 #[cfg(test)]
@@ -192,4 +578,298 @@ mod tests {
         ));
         assert_eq!(session.progress().state, ScrollSessionState::Error);
     }
+
+    fn session_in(state: ScrollSessionState, clock: &ManualClock) -> ScrollSession {
+        let mut session = ScrollSession::with_clock(
+            ScrollConfig {
+                max_consecutive_failures: 1,
+                ..ScrollConfig::default()
+            },
+            Arc::new(clock.clone()),
+        );
+        match state {
+            ScrollSessionState::Ready => {}
+            ScrollSessionState::Capturing => session.mark_capturing(),
+            ScrollSessionState::Paused => {
+                session.mark_capturing();
+                session.pause().unwrap();
+            }
+            ScrollSessionState::Done => session.cancel(),
+            ScrollSessionState::Error => {
+                session.append_failed();
+            }
+        }
+        assert_eq!(session.state(), state);
+        session
+    }
+
+    const ALL_STATES: [ScrollSessionState; 5] = [
+        ScrollSessionState::Ready,
+        ScrollSessionState::Capturing,
+        ScrollSessionState::Paused,
+        ScrollSessionState::Done,
+        ScrollSessionState::Error,
+    ];
+
+    #[test]
+    fn transitions_follow_state_table() {
+        use ScrollSessionState::*;
+        let clock = ManualClock::new();
+        // (from, after mark_capturing, after pause, after resume, after cancel)
+        let table = [
+            (Ready, Capturing, None, None, Done),
+            (Capturing, Capturing, Some(Paused), Some(Capturing), Done),
+            (Paused, Capturing, Some(Paused), Some(Capturing), Done),
+            (Done, Done, None, None, Done),
+            (Error, Error, None, None, Error),
+        ];
+        assert_eq!(table.len(), ALL_STATES.len());
+        for (from, capturing, paused, resumed, cancelled) in table {
+            let mut session = session_in(from, &clock);
+            session.mark_capturing();
+            assert_eq!(session.state(), capturing, "mark_capturing from {:?}", from);
+
+            let mut session = session_in(from, &clock);
+            let result = session.pause();
+            assert_eq!(result.is_ok(), paused.is_some(), "pause from {:?}", from);
+            assert_eq!(session.state(), paused.unwrap_or(from));
+
+            let mut session = session_in(from, &clock);
+            let result = session.resume();
+            assert_eq!(result.is_ok(), resumed.is_some(), "resume from {:?}", from);
+            assert_eq!(session.state(), resumed.unwrap_or(from));
+
+            let mut session = session_in(from, &clock);
+            session.cancel();
+            assert_eq!(session.state(), cancelled, "cancel from {:?}", from);
+        }
+    }
+
+    #[test]
+    fn begin_frame_gates_each_state() {
+        let clock = ManualClock::new();
+        for state in ALL_STATES {
+            let mut session = session_in(state, &clock);
+            let gate = session.begin_frame();
+            match state {
+                ScrollSessionState::Ready | ScrollSessionState::Capturing => {
+                    assert_eq!(gate, None);
+                    assert_eq!(session.state(), ScrollSessionState::Capturing);
+                }
+                ScrollSessionState::Paused => {
+                    assert_eq!(gate, Some(AppendResult::Skipped(SkipReason::Paused)));
+                }
+                ScrollSessionState::Done => {
+                    assert_eq!(gate, Some(AppendResult::AutoStopped(StopReason::User)));
+                }
+                ScrollSessionState::Error => assert_eq!(
+                    gate,
+                    Some(AppendResult::AutoStopped(StopReason::ConsecutiveFailures))
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn finished_sessions_ignore_appends() {
+        let clock = ManualClock::new();
+        for state in [ScrollSessionState::Done, ScrollSessionState::Error] {
+            let mut session = session_in(state, &clock);
+            let reason = session.stop_reason().unwrap();
+            let stopped = AppendResult::AutoStopped(reason);
+            assert_eq!(session.append_accepted(100, 1.0), stopped);
+            assert_eq!(session.append_skipped(SkipReason::Duplicate), stopped);
+            assert_eq!(session.append_failed(), stopped);
+            assert_eq!(session.progress().frames, 0);
+            assert_eq!(session.state(), state);
+            session.cancel();
+            assert_eq!(session.stop_reason(), Some(reason));
+        }
+    }
+
+    #[test]
+    fn throttle_skips_frames_until_interval_elapses() {
+        let clock = ManualClock::new();
+        let mut session = ScrollSession::with_clock(
+            ScrollConfig {
+                throttle_ms: 100,
+                ..ScrollConfig::default()
+            },
+            Arc::new(clock.clone()),
+        );
+        assert_eq!(session.begin_frame(), None);
+        session.append_accepted(120, 1.0);
+        clock.advance(Duration::from_millis(60));
+        assert_eq!(
+            session.begin_frame(),
+            Some(AppendResult::Skipped(SkipReason::Throttled))
+        );
+        clock.advance(Duration::from_millis(40));
+        assert_eq!(session.begin_frame(), None);
+    }
+
+    #[test]
+    fn rejected_frames_do_not_use_the_throttle_slot() {
+        let clock = ManualClock::new();
+        let mut session = ScrollSession::with_clock(
+            ScrollConfig {
+                throttle_ms: 100,
+                ..ScrollConfig::default()
+            },
+            Arc::new(clock.clone()),
+        );
+        assert_eq!(session.begin_frame(), None);
+        session.append_skipped(SkipReason::Duplicate);
+        clock.advance(Duration::from_millis(10));
+        assert_eq!(session.begin_frame(), None);
+        session.append_failed();
+        assert_eq!(session.begin_frame(), None);
+    }
+
+    #[test]
+    fn idle_timeout_stops_session_excluding_paused_time() {
+        let clock = ManualClock::new();
+        let mut session = ScrollSession::with_clock(
+            ScrollConfig {
+                idle_timeout_ms: 1_000,
+                ..ScrollConfig::default()
+            },
+            Arc::new(clock.clone()),
+        );
+        assert_eq!(session.check_timeout(), None);
+        session.mark_capturing();
+        session.append_accepted(200, 1.0);
+
+        clock.advance(Duration::from_millis(600));
+        session.pause().unwrap();
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(session.check_timeout(), None);
+        session.pause().unwrap();
+        session.resume().unwrap();
+
+        // The 600ms idle before the pause still count.
+        clock.advance(Duration::from_millis(399));
+        assert_eq!(session.check_timeout(), None);
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(
+            session.begin_frame(),
+            Some(AppendResult::AutoStopped(StopReason::Timeout))
+        );
+        assert_eq!(session.state(), ScrollSessionState::Done);
+        assert_eq!(session.stop_reason(), Some(StopReason::Timeout));
+    }
+
+    #[test]
+    fn repeated_idle_frames_stop_with_no_new_content() {
+        let mut session = ScrollSession::new(ScrollConfig {
+            max_idle_frames: 3,
+            ..ScrollConfig::default()
+        });
+        session.mark_capturing();
+        assert_eq!(
+            session.append_skipped(SkipReason::Duplicate),
+            AppendResult::Skipped(SkipReason::Duplicate)
+        );
+        assert_eq!(
+            session.append_skipped(SkipReason::TooSmallDelta),
+            AppendResult::Skipped(SkipReason::TooSmallDelta)
+        );
+        // Accepted content resets the count; throttled frames do not count.
+        session.append_accepted(80, 1.0);
+        session.append_skipped(SkipReason::Duplicate);
+        session.append_skipped(SkipReason::Throttled);
+        session.append_skipped(SkipReason::Duplicate);
+        assert_eq!(
+            session.append_skipped(SkipReason::Duplicate),
+            AppendResult::AutoStopped(StopReason::NoNewContent)
+        );
+        assert_eq!(session.state(), ScrollSessionState::Done);
+    }
+
+    #[test]
+    fn match_failures_route_through_append_failed() {
+        let mut session = ScrollSession::new(ScrollConfig {
+            max_consecutive_failures: 2,
+            ..ScrollConfig::default()
+        });
+        assert_eq!(
+            session.append_skipped(SkipReason::MatchFailed),
+            AppendResult::Skipped(SkipReason::MatchFailed)
+        );
+        assert_eq!(
+            session.append_skipped(SkipReason::MatchFailed),
+            AppendResult::AutoStopped(StopReason::ConsecutiveFailures)
+        );
+    }
+
+    #[test]
+    fn limits_stop_with_distinct_reasons() {
+        let mut by_frames = ScrollSession::new(ScrollConfig {
+            max_frames: 2,
+            ..ScrollConfig::default()
+        });
+        by_frames.append_accepted(10, 1.0);
+        assert_eq!(
+            by_frames.append_accepted(10, 1.0),
+            AppendResult::AutoStopped(StopReason::ReachedMaxFrames)
+        );
+
+        let mut by_height = ScrollSession::new(ScrollConfig {
+            max_height_px: 500,
+            ..ScrollConfig::default()
+        });
+        assert_eq!(
+            by_height.append_accepted(500, 1.0),
+            AppendResult::AutoStopped(StopReason::ReachedMaxHeight)
+        );
+        assert_eq!(by_height.progress().state, ScrollSessionState::Done);
+    }
+
+    #[test]
+    fn config_without_new_fields_keeps_idle_checks() {
+        let config: ScrollConfig = serde_json::from_str(
+            r#"{"max_height_px":1000,"max_frames":10,"throttle_ms":0,"max_consecutive_failures":3}"#,
+        )
+        .unwrap();
+        let defaults = ScrollConfig::default();
+        assert_eq!(config.max_idle_frames, defaults.max_idle_frames);
+        assert_eq!(config.idle_timeout_ms, defaults.idle_timeout_ms);
+    }
+
+    #[test]
+    fn match_region_span_is_clamped_to_size() {
+        assert_eq!(MatchRegion::default().span(200), (30, 170));
+        let inverted = MatchRegion {
+            start_percent: 90,
+            end_percent: 10,
+        };
+        assert_eq!(inverted.span(100), (90, 91));
+        let full = MatchRegion {
+            start_percent: 0,
+            end_percent: 255,
+        };
+        assert_eq!(full.span(50), (0, 50));
+        assert_eq!(full.span(0), (0, 0));
+    }
+
+    #[test]
+    fn capture_error_serializes_kind_and_details() {
+        let plain = CaptureError::new(CaptureErrorKind::OcrEmpty, "No text found");
+        assert_eq!(
+            serde_json::to_value(&plain).unwrap(),
+            serde_json::json!({"kind": "ocr_empty", "message": "No text found"})
+        );
+
+        let unsupported = CaptureError::unsupported("xcap", "window capture");
+        assert_eq!(unsupported.kind, CaptureErrorKind::Unsupported);
+        assert_eq!(unsupported.detail("backend"), Some("xcap"));
+        let value = serde_json::to_value(&unsupported).unwrap();
+        assert_eq!(value["kind"], "unsupported");
+        assert_eq!(value["details"]["backend"], "xcap");
+
+        let parsed: CaptureError =
+            serde_json::from_str(r#"{"kind":"busy","message":"Capture in progress"}"#).unwrap();
+        assert_eq!(parsed.kind, CaptureErrorKind::Busy);
+        assert_eq!(parsed.details, None);
+    }
 }
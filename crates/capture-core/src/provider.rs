@@ -0,0 +1,289 @@
+//! Screen capture backends.
+//!
+//! A [`ScreenCaptureProvider`] lists displays and windows and captures them
+//! as RGBA images. Backends only have to implement what they natively
+//! support and report it through [`ScreenCaptureProvider::supports`]; region
+//! and full-screen capture fall back to compositing monitor captures.
+//!
+//! Coordinates are logical points in the global desktop space, the same
+//! space the overlay reports selections in. Captured images are in physical
+//! pixels.
+
+use image::imageops::crop_imm;
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+
+use crate::composite::{composite_region, region_output_scale};
+use crate::geometry::PhysicalRect;
+pub use crate::geometry::{monitor_at, MonitorInfo};
+use crate::{CaptureError, CaptureErrorKind, CaptureRect};
+
+/// Regions narrower or shorter than this, in pixels, are rejected.
+pub const MIN_CAPTURE_SIZE_PX: u32 = 10;
+
+/// A top-level window as reported by a capture backend.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WindowInfo {
+    pub id: u32,
+    /// Owning process, used to leave out the app's own windows.
+    pub pid: u32,
+    pub app_name: String,
+    pub title: String,
+    /// Logical position and size.
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    /// Stacking order; higher is closer to the front.
+    pub z: i32,
+    pub is_minimized: bool,
+}
+
+/// System-driven selection where the user picks what to capture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InteractiveMode {
+    Region,
+    Window,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureOperation {
+    ListMonitors,
+    ListWindows,
+    Monitor,
+    Window,
+    Region,
+    FullScreen,
+    Interactive(InteractiveMode),
+}
+
+pub trait ScreenCaptureProvider: Send + Sync {
+    /// Short backend name for logs and error messages.
+    fn name(&self) -> &'static str;
+
+    fn supports(&self, operation: CaptureOperation) -> bool;
+
+    /// Whether captures round-trip through an image file the backend
+    /// writes, which is too slow for repeated polling.
+    fn captures_via_file(&self) -> bool {
+        false
+    }
+
+    fn monitors(&self) -> Result<Vec<MonitorInfo>, CaptureError> {
+        Err(CaptureError::unsupported(self.name(), "listing monitors"))
+    }
+
+    fn windows(&self) -> Result<Vec<WindowInfo>, CaptureError> {
+        Err(CaptureError::unsupported(self.name(), "listing windows"))
+    }
+
+    fn capture_monitor(&self, _id: u32) -> Result<RgbaImage, CaptureError> {
+        Err(CaptureError::unsupported(self.name(), "monitor capture"))
+    }
+
+    fn capture_window(&self, _id: u32) -> Result<RgbaImage, CaptureError> {
+        Err(CaptureError::unsupported(self.name(), "window capture"))
+    }
+
+    /// Capture `rect` from every monitor it overlaps, at the scale of the
+    /// sharpest one. See [`composite_region`].
+    fn capture_region(&self, rect: CaptureRect) -> Result<RgbaImage, CaptureError> {
+        let monitors = self.monitors()?;
+        let output_scale = region_output_scale(&monitors, rect).ok_or_else(|| {
+            CaptureError::new(
+                CaptureErrorKind::CaptureFailed,
+                "Selected area is outside available monitors",
+            )
+        })?;
+        composite_region(&monitors, rect, output_scale, |monitor| {
+            self.capture_monitor(monitor.id)
+        })
+    }
+
+    /// Capture the primary monitor.
+    fn capture_full_screen(&self) -> Result<RgbaImage, CaptureError> {
+        let monitors = self.monitors()?;
+        let monitor = monitors
+            .iter()
+            .find(|monitor| monitor.is_primary)
+            .or_else(|| monitors.first())
+            .ok_or_else(|| {
+                CaptureError::new(CaptureErrorKind::CaptureFailed, "No monitors available")
+            })?;
+        self.capture_monitor(monitor.id)
+    }
+
+    /// Let the user pick a region or window. Returns a `Cancelled` error when
+    /// the user dismisses the selection.
+    fn capture_interactive(&self, _mode: InteractiveMode) -> Result<RgbaImage, CaptureError> {
+        Err(CaptureError::unsupported(
+            self.name(),
+            "interactive capture",
+        ))
+    }
+}
+
+/// Crop the logical `rect` out of a full capture of `monitor`, clamped to the
+/// image bounds.
+pub fn crop_monitor_region(
+    image: &RgbaImage,
+    monitor: &MonitorInfo,
+    rect: CaptureRect,
+) -> Result<RgbaImage, CaptureError> {
+    let bounds = PhysicalRect::from_size(image.width(), image.height());
+    let crop = monitor
+        .to_local_physical(rect)
+        .intersection(&bounds)
+        .ok_or_else(|| {
+            CaptureError::new(
+                CaptureErrorKind::CaptureFailed,
+                "Selected area is outside monitor bounds",
+            )
+        })?;
+
+    if crop.width < MIN_CAPTURE_SIZE_PX || crop.height < MIN_CAPTURE_SIZE_PX {
+        return Err(CaptureError::new(
+            CaptureErrorKind::CaptureFailed,
+            "Selected area is too small",
+        ));
+    }
+
+    Ok(crop_imm(image, crop.x as u32, crop.y as u32, crop.width, crop.height).to_image())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn monitor(id: u32, x: i32, width: u32, scale_factor: f32, is_primary: bool) -> MonitorInfo {
+        MonitorInfo {
+            id,
+            name: format!("Display {}", id),
+            x,
+            y: 0,
+            width,
+            height: 100,
+            scale_factor,
+            is_primary,
+        }
+    }
+
+    /// Two side-by-side monitors whose pixels encode their id and position.
+    struct FakeProvider {
+        monitors: Vec<MonitorInfo>,
+    }
+
+    impl FakeProvider {
+        fn new() -> Self {
+            Self {
+                monitors: vec![
+                    monitor(1, 0, 200, 1.0, false),
+                    monitor(2, 200, 100, 2.0, true),
+                ],
+            }
+        }
+    }
+
+    impl ScreenCaptureProvider for FakeProvider {
+        fn name(&self) -> &'static str {
+            "fake"
+        }
+
+        fn supports(&self, operation: CaptureOperation) -> bool {
+            matches!(
+                operation,
+                CaptureOperation::ListMonitors | CaptureOperation::Monitor
+            )
+        }
+
+        fn monitors(&self) -> Result<Vec<MonitorInfo>, CaptureError> {
+            Ok(self.monitors.clone())
+        }
+
+        fn capture_monitor(&self, id: u32) -> Result<RgbaImage, CaptureError> {
+            let monitor = self.monitors.iter().find(|m| m.id == id).unwrap();
+            let width = (monitor.width as f32 * monitor.scale_factor) as u32;
+            let height = (monitor.height as f32 * monitor.scale_factor) as u32;
+            Ok(RgbaImage::from_fn(width, height, |x, y| {
+                Rgba([id as u8, x as u8, y as u8, 255])
+            }))
+        }
+    }
+
+    #[test]
+    fn monitor_at_uses_rect_center() {
+        let monitors = FakeProvider::new().monitors;
+        let straddling = CaptureRect {
+            x: 170,
+            y: 10,
+            width: 80,
+            height: 20,
+        };
+        assert_eq!(monitor_at(&monitors, straddling).map(|m| m.id), Some(2));
+
+        let outside = CaptureRect {
+            x: 400,
+            y: 10,
+            width: 20,
+            height: 20,
+        };
+        assert!(monitor_at(&monitors, outside).is_none());
+    }
+
+    #[test]
+    fn region_capture_crops_in_physical_pixels() {
+        let provider = FakeProvider::new();
+        let rect = CaptureRect {
+            x: 210,
+            y: 5,
+            width: 30,
+            height: 20,
+        };
+        let image = provider.capture_region(rect).unwrap();
+        assert_eq!(image.dimensions(), (60, 40));
+        assert_eq!(image.get_pixel(0, 0), &Rgba([2, 20, 10, 255]));
+    }
+
+    #[test]
+    fn region_capture_clamps_to_monitor_edge() {
+        let provider = FakeProvider::new();
+        let rect = CaptureRect {
+            x: 150,
+            y: 50,
+            width: 40,
+            height: 80,
+        };
+        let image = provider.capture_region(rect).unwrap();
+        assert_eq!(image.dimensions(), (40, 50));
+        assert_eq!(image.get_pixel(0, 0), &Rgba([1, 150, 50, 255]));
+    }
+
+    #[test]
+    fn region_capture_spans_both_monitors() {
+        let provider = FakeProvider::new();
+        let rect = CaptureRect::new(190, 10, 20, 20);
+        let image = provider.capture_region(rect).unwrap();
+        assert_eq!(image.dimensions(), (40, 40));
+        assert_eq!(image.get_pixel(0, 0)[0], 1);
+        assert_eq!(image.get_pixel(20, 0), &Rgba([2, 0, 20, 255]));
+    }
+
+    #[test]
+    fn full_screen_prefers_primary_monitor() {
+        let image = FakeProvider::new().capture_full_screen().unwrap();
+        assert_eq!(image.dimensions(), (200, 200));
+        assert_eq!(image.get_pixel(0, 0)[0], 2);
+    }
+
+    #[test]
+    fn unsupported_operations_fail_with_backend_name() {
+        let provider = FakeProvider::new();
+        let error = provider.capture_window(7).unwrap_err();
+        assert!(error.message.contains("fake"));
+        let error = provider
+            .capture_interactive(InteractiveMode::Region)
+            .unwrap_err();
+        assert!(error.message.contains("interactive capture"));
+    }
+}
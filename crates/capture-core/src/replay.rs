@@ -0,0 +1,437 @@
+//! Scripted capture backend for deterministic scroll tests.
+//!
+//! [`ReplayProvider`] shows a [`ReplayScene`] (a tall synthetic page seen
+//! through a fixed viewport, with an optional static header and footer and
+//! areas redrawn on every frame) and scrolls it along a [`ReplayTimeline`].
+//! Each capture plays the next step and moves the provider's [`ManualClock`]
+//! to that step's time, so throttling and timeouts behave as they would live.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use image::{Rgba, RgbaImage};
+
+use crate::provider::{CaptureOperation, MonitorInfo, ScreenCaptureProvider};
+use crate::{CaptureError, CaptureErrorKind, CaptureRect, ManualClock};
+
+pub const REPLAY_MONITOR_ID: u32 = 1;
+
+fn mix(mut value: u64) -> u64 {
+    value ^= value >> 33;
+    value = value.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    value ^= value >> 33;
+    value = value.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    value ^ (value >> 33)
+}
+
+/// Deterministic noise-like colour for `(x, y)` in the layer `salt`.
+fn synthetic_pixel(salt: u64, x: u32, y: u32) -> Rgba<u8> {
+    let bits = mix(salt ^ (u64::from(y) << 32) ^ u64::from(x / 2)).to_le_bytes();
+    Rgba([bits[0], bits[1], bits[2], 255])
+}
+
+/// Area of the viewport, in frame pixels, redrawn differently on every
+/// frame, such as a spinner or a blinking caret.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayAnimation {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplayScene {
+    /// Full scrollable content.
+    pub page: RgbaImage,
+    /// Frame height, including header and footer.
+    pub viewport_height: u32,
+    /// Rows at the top of each frame that never scroll.
+    pub header: u32,
+    /// Rows at the bottom of each frame that never scroll.
+    pub footer: u32,
+    pub animations: Vec<ReplayAnimation>,
+}
+
+impl ReplayScene {
+    /// A `width` x `page_height` page of non-repeating content, seen through
+    /// a `viewport_height` tall viewport.
+    pub fn synthetic(width: u32, page_height: u32, viewport_height: u32) -> Self {
+        Self {
+            page: RgbaImage::from_fn(width, page_height, |x, y| synthetic_pixel(0x5eed, x, y)),
+            viewport_height,
+            header: 0,
+            footer: 0,
+            animations: Vec::new(),
+        }
+    }
+
+    pub fn with_header(mut self, rows: u32) -> Self {
+        self.header = rows;
+        self
+    }
+
+    pub fn with_footer(mut self, rows: u32) -> Self {
+        self.footer = rows;
+        self
+    }
+
+    pub fn with_animation(mut self, animation: ReplayAnimation) -> Self {
+        self.animations.push(animation);
+        self
+    }
+
+    pub fn width(&self) -> u32 {
+        self.page.width()
+    }
+
+    /// Rows of the page visible at once.
+    pub fn body_height(&self) -> u32 {
+        self.viewport_height - self.header - self.footer
+    }
+
+    /// Largest offset that still fills the viewport.
+    pub fn max_offset(&self) -> u32 {
+        self.page.height().saturating_sub(self.body_height())
+    }
+
+    /// The viewport scrolled to `offset`, as captured on frame `index`.
+    pub fn render(&self, offset: u32, index: usize) -> RgbaImage {
+        let offset = offset.min(self.max_offset());
+        let footer_top = self.viewport_height - self.footer;
+        let mut frame = RgbaImage::from_fn(self.width(), self.viewport_height, |x, y| {
+            if y < self.header {
+                synthetic_pixel(0x4ead, x, y)
+            } else if y >= footer_top {
+                synthetic_pixel(0xf007, x, y - footer_top)
+            } else {
+                *self.page.get_pixel(x, offset + y - self.header)
+            }
+        });
+
+        for animation in &self.animations {
+            let x_end = (animation.x + animation.width).min(frame.width());
+            let y_end = (animation.y + animation.height).min(frame.height());
+            for y in animation.y..y_end {
+                for x in animation.x..x_end {
+                    frame.put_pixel(x, y, synthetic_pixel(0xa000 + index as u64, x, y));
+                }
+            }
+        }
+        frame
+    }
+}
+
+/// One captured frame: the page offset shown `at_ms` into the session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayStep {
+    pub at_ms: u64,
+    pub offset: u32,
+}
+
+/// Scripted scroll offsets over time, built step by step.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayTimeline {
+    steps: Vec<ReplayStep>,
+}
+
+impl ReplayTimeline {
+    /// Timeline whose first frame shows `offset` at time zero.
+    pub fn starting_at(offset: u32) -> Self {
+        Self {
+            steps: vec![ReplayStep { at_ms: 0, offset }],
+        }
+    }
+
+    pub fn steps(&self) -> &[ReplayStep] {
+        &self.steps
+    }
+
+    fn last(&self) -> ReplayStep {
+        self.steps.last().copied().unwrap_or(ReplayStep {
+            at_ms: 0,
+            offset: 0,
+        })
+    }
+
+    /// Show `offset` `after_ms` after the previous frame. Offsets may move
+    /// backwards, like a scroll bouncing back.
+    pub fn then(mut self, after_ms: u64, offset: u32) -> Self {
+        let at_ms = self.last().at_ms + after_ms;
+        self.steps.push(ReplayStep { at_ms, offset });
+        self
+    }
+
+    /// Scroll from the current offset to `to`, `step_px` per frame with a
+    /// frame every `interval_ms`.
+    pub fn scroll_to(mut self, to: u32, step_px: u32, interval_ms: u64) -> Self {
+        let step_px = step_px.max(1);
+        let mut offset = self.last().offset;
+        while offset != to {
+            offset = if to > offset {
+                (offset + step_px).min(to)
+            } else {
+                offset.saturating_sub(step_px).max(to)
+            };
+            self = self.then(interval_ms, offset);
+        }
+        self
+    }
+
+    /// Keep the current offset for `frames` more frames.
+    pub fn hold(mut self, frames: usize, interval_ms: u64) -> Self {
+        let offset = self.last().offset;
+        for _ in 0..frames {
+            self = self.then(interval_ms, offset);
+        }
+        self
+    }
+
+    /// Shake every step except the first and last by `amplitude` pixels,
+    /// alternating direction.
+    pub fn jitter(mut self, amplitude: u32) -> Self {
+        let count = self.steps.len();
+        for (index, step) in self.steps.iter_mut().enumerate() {
+            if index == 0 || index + 1 == count {
+                continue;
+            }
+            step.offset = if index % 2 == 0 {
+                step.offset + amplitude
+            } else {
+                step.offset.saturating_sub(amplitude)
+            };
+        }
+        self
+    }
+}
+
+#[derive(Debug, Default)]
+struct ReplayCursor {
+    next: usize,
+    now_ms: u64,
+}
+
+/// Capture backend playing a timeline over a scene. It exposes one monitor,
+/// the viewport, at the origin with a scale factor of 1.
+#[derive(Debug)]
+pub struct ReplayProvider {
+    scene: ReplayScene,
+    steps: Vec<ReplayStep>,
+    clock: ManualClock,
+    cursor: Mutex<ReplayCursor>,
+}
+
+impl ReplayProvider {
+    pub fn new(scene: ReplayScene, timeline: ReplayTimeline) -> Self {
+        Self {
+            scene,
+            steps: timeline.steps,
+            clock: ManualClock::new(),
+            cursor: Mutex::new(ReplayCursor::default()),
+        }
+    }
+
+    /// Clock moved to each step's time as it is captured. Hand a clone to
+    /// the session under test.
+    pub fn clock(&self) -> ManualClock {
+        self.clock.clone()
+    }
+
+    pub fn scene(&self) -> &ReplayScene {
+        &self.scene
+    }
+
+    /// The whole viewport, in logical points.
+    pub fn viewport(&self) -> CaptureRect {
+        CaptureRect {
+            x: 0,
+            y: 0,
+            width: self.scene.width(),
+            height: self.scene.viewport_height,
+        }
+    }
+
+    /// Whether every step has been captured.
+    pub fn is_finished(&self) -> bool {
+        self.lock().next >= self.steps.len()
+    }
+
+    /// What a perfect stitch of the whole timeline looks like: the first
+    /// frame's header, every page row shown, then the first frame's footer.
+    pub fn expected_stitch(&self) -> RgbaImage {
+        let scene = &self.scene;
+        let first = self.steps.first().map_or(0, |step| step.offset);
+        let last = self.steps.iter().map(|step| step.offset).max().unwrap_or(0);
+        let first = first.min(scene.max_offset());
+        let last = last.min(scene.max_offset());
+
+        let first_frame = scene.render(first, 0);
+        let footer_top = scene.viewport_height - scene.footer;
+        let page_rows = last + scene.body_height() - first;
+        RgbaImage::from_fn(
+            scene.width(),
+            scene.header + page_rows + scene.footer,
+            |x, y| {
+                if y < scene.header {
+                    *first_frame.get_pixel(x, y)
+                } else if y < scene.header + page_rows {
+                    *scene.page.get_pixel(x, first + y - scene.header)
+                } else {
+                    *first_frame.get_pixel(x, footer_top + y - scene.header - page_rows)
+                }
+            },
+        )
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ReplayCursor> {
+        self.cursor.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Play the next step.
+    fn next_frame(&self) -> Result<RgbaImage, CaptureError> {
+        let mut cursor = self.lock();
+        let index = cursor.next;
+        let step = self.steps.get(index).copied().ok_or_else(|| {
+            CaptureError::new(CaptureErrorKind::CaptureFailed, "Replay timeline has ended")
+        })?;
+        self.clock.advance(Duration::from_millis(
+            step.at_ms.saturating_sub(cursor.now_ms),
+        ));
+        cursor.now_ms = cursor.now_ms.max(step.at_ms);
+        cursor.next += 1;
+        Ok(self.scene.render(step.offset, index))
+    }
+}
+
+impl ScreenCaptureProvider for ReplayProvider {
+    fn name(&self) -> &'static str {
+        "replay"
+    }
+
+    fn supports(&self, operation: CaptureOperation) -> bool {
+        matches!(
+            operation,
+            CaptureOperation::ListMonitors
+                | CaptureOperation::Monitor
+                | CaptureOperation::Region
+                | CaptureOperation::FullScreen
+        )
+    }
+
+    fn monitors(&self) -> Result<Vec<MonitorInfo>, CaptureError> {
+        Ok(vec![MonitorInfo {
+            id: REPLAY_MONITOR_ID,
+            name: "Replay".to_string(),
+            x: 0,
+            y: 0,
+            width: self.scene.width(),
+            height: self.scene.viewport_height,
+            scale_factor: 1.0,
+            is_primary: true,
+        }])
+    }
+
+    fn capture_monitor(&self, id: u32) -> Result<RgbaImage, CaptureError> {
+        if id != REPLAY_MONITOR_ID {
+            return Err(CaptureError::new(
+                CaptureErrorKind::CaptureFailed,
+                format!("Monitor {} is not available", id),
+            ));
+        }
+        self.next_frame()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Clock;
+
+    #[test]
+    fn timeline_builders_schedule_offsets() {
+        let timeline = ReplayTimeline::starting_at(0)
+            .scroll_to(250, 100, 40)
+            .hold(2, 10)
+            .then(5, 240);
+        let offsets: Vec<(u64, u32)> = timeline
+            .steps()
+            .iter()
+            .map(|step| (step.at_ms, step.offset))
+            .collect();
+        assert_eq!(
+            offsets,
+            vec![
+                (0, 0),
+                (40, 100),
+                (80, 200),
+                (120, 250),
+                (130, 250),
+                (140, 250),
+                (145, 240),
+            ]
+        );
+    }
+
+    #[test]
+    fn jitter_keeps_first_and_last_offsets() {
+        let steps = ReplayTimeline::starting_at(0)
+            .scroll_to(400, 100, 10)
+            .jitter(4);
+        let offsets: Vec<u32> = steps.steps().iter().map(|step| step.offset).collect();
+        assert_eq!(offsets, vec![0, 96, 204, 296, 400]);
+    }
+
+    #[test]
+    fn render_keeps_header_and_footer_static() {
+        let scene = ReplayScene::synthetic(40, 300, 100)
+            .with_header(10)
+            .with_footer(5);
+        let top = scene.render(0, 0);
+        let scrolled = scene.render(50, 1);
+        for x in 0..40 {
+            assert_eq!(top.get_pixel(x, 3), scrolled.get_pixel(x, 3));
+            assert_eq!(top.get_pixel(x, 97), scrolled.get_pixel(x, 97));
+            assert_eq!(scrolled.get_pixel(x, 10), scene.page.get_pixel(x, 50));
+        }
+    }
+
+    #[test]
+    fn animations_change_between_frames() {
+        let scene = ReplayScene::synthetic(40, 300, 100).with_animation(ReplayAnimation {
+            x: 0,
+            y: 0,
+            width: 4,
+            height: 4,
+        });
+        assert_ne!(
+            scene.render(0, 0).get_pixel(1, 1),
+            scene.render(0, 1).get_pixel(1, 1)
+        );
+        assert_eq!(
+            scene.render(0, 0).get_pixel(8, 8),
+            scene.render(0, 1).get_pixel(8, 8)
+        );
+    }
+
+    #[test]
+    fn captures_advance_the_clock_and_end_with_the_timeline() {
+        let provider = ReplayProvider::new(
+            ReplayScene::synthetic(40, 300, 100),
+            ReplayTimeline::starting_at(0).then(250, 60),
+        );
+        let clock = provider.clock();
+        let start = clock.now();
+
+        let first = provider.capture_region(provider.viewport()).unwrap();
+        assert_eq!(first.dimensions(), (40, 100));
+        let second = provider.capture_full_screen().unwrap();
+        assert_eq!(
+            second.get_pixel(0, 0),
+            provider.scene().page.get_pixel(0, 60)
+        );
+        assert_eq!(clock.now() - start, Duration::from_millis(250));
+
+        assert!(provider.is_finished());
+        assert!(provider.capture_full_screen().is_err());
+    }
+}
@@ -0,0 +1,1577 @@
+//! Scroll frame stitching.
+//!
+//! `Stitcher` takes frames one at a time, matches each one against the last
+//! accepted frame and appends only the newly revealed rows to a growing
+//! canvas. Every append goes through the owned `ScrollSession`, so callers see
+//! the same `AppendResult` and `ScrollProgress` values whether frames come from
+//! live polling or disk.
+//!
+//! Matching first looks for an exact overlap using per-row hashes, which
+//! covers editors, terminals and documents that scroll by whole pixels, and
+//! only falls back to the fuzzy coarse-to-fine search when that fails.
+//!
+//! Horizontal scrolling is handled by transposing frames on the way in and
+//! the result on the way out, so the matcher itself only ever works on rows.
+
+use std::sync::Arc;
+
+use image::{imageops, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppendResult, CaptureError, CaptureErrorKind, Clock, MatchRegion, ScrollConfig,
+    ScrollDirection, ScrollProgress, ScrollSession, ScrollSessionState, SkipReason, StopReason,
+};
+
+pub const MIN_OVERLAP: u32 = 24;
+pub const MIN_NEW_CONTENT: u32 = 40;
+pub const MAX_MATCH_ERROR: f64 = 42.0;
+/// Mean sampled channel difference below which two frames count as identical.
+pub const MIN_FRAME_DIFFERENCE: f64 = 1.8;
+pub const MIN_SLICE_HEIGHT: u32 = 10;
+pub const MIN_FRAME_SIZE: u32 = 20;
+/// Mean row difference at or below which a row counts as static.
+pub const STATIC_ROW_TOLERANCE: f64 = 1.0;
+/// Sticky headers and footers may each cover at most 1/N of the frame.
+pub const MAX_STICKY_FRACTION: u32 = 3;
+/// The coarse search pass aims for bands of roughly this many rows.
+pub const COARSE_TARGET_ROWS: u32 = 160;
+pub const MAX_PYRAMID_FACTOR: u32 = 8;
+pub const COARSE_COLUMNS: u32 = 96;
+/// Number of distinct coarse candidates refined at full resolution.
+pub const COARSE_CANDIDATES: usize = 3;
+
+fn pixel_difference(p: &image::Rgba<u8>, c: &image::Rgba<u8>) -> f64 {
+    ((p[0] as f64 - c[0] as f64).abs()
+        + (p[1] as f64 - c[1] as f64).abs()
+        + (p[2] as f64 - c[2] as f64).abs())
+        / 3.0
+}
+
+/// Mean channel difference between two equally sized frames, sampled on an
+/// ~80x80 grid inside `region`. Returns 255.0 for empty frames.
+pub fn sample_frame_difference(
+    prev: &RgbaImage,
+    current: &RgbaImage,
+    region: MatchRegion,
+    direction: ScrollDirection,
+) -> f64 {
+    let width = prev.width();
+    let height = prev.height();
+    if width == 0 || height == 0 {
+        return 255.0;
+    }
+
+    let ((x_start, x_end), (y_start, y_end)) = match direction {
+        ScrollDirection::Vertical => (region.span(width), (0, height)),
+        ScrollDirection::Horizontal => ((0, width), region.span(height)),
+    };
+    let col_step = ((x_end - x_start) / 80).max(1);
+    let row_step = ((y_end - y_start) / 80).max(1);
+    let mut total = 0.0;
+    let mut count = 0u64;
+
+    let mut y = y_start;
+    while y < y_end {
+        let mut x = x_start;
+        while x < x_end {
+            total += pixel_difference(prev.get_pixel(x, y), current.get_pixel(x, y));
+            count += 1;
+            x = x.saturating_add(col_step);
+        }
+        y = y.saturating_add(row_step);
+    }
+
+    if count == 0 {
+        return 255.0;
+    }
+    total / count as f64
+}
+
+/// Rows of a frame that actually scroll. Rows above `top` form a sticky
+/// header and rows from `bottom` down form a sticky footer.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ScrollBand {
+    pub top: u32,
+    pub bottom: u32,
+}
+
+impl ScrollBand {
+    pub fn full(height: u32) -> Self {
+        Self {
+            top: 0,
+            bottom: height,
+        }
+    }
+
+    pub fn height(&self) -> u32 {
+        self.bottom.saturating_sub(self.top)
+    }
+}
+
+/// Mean difference of row `y` between two frames, sampled across `region`.
+fn row_difference(prev: &RgbaImage, current: &RgbaImage, region: MatchRegion, y: u32) -> f64 {
+    let (x_start, x_end) = region.span(prev.width());
+    let col_step = ((x_end - x_start) / 160).max(1);
+    let mut total = 0.0;
+    let mut samples = 0u64;
+    let mut x = x_start;
+    while x < x_end {
+        total += pixel_difference(prev.get_pixel(x, y), current.get_pixel(x, y));
+        samples += 1;
+        x = x.saturating_add(col_step);
+    }
+    if samples == 0 {
+        return f64::MAX;
+    }
+    total / samples as f64
+}
+
+/// Whether row `y` carries visible content within `region`, rather than a
+/// flat fill such as a page margin or background.
+fn row_has_content(frame: &RgbaImage, region: MatchRegion, y: u32) -> bool {
+    let (x_start, x_end) = region.span(frame.width());
+    let first = frame.get_pixel(x_start, y);
+    (x_start..x_end).any(|x| pixel_difference(first, frame.get_pixel(x, y)) > STATIC_ROW_TOLERANCE)
+}
+
+/// Length of the static run along `rows`, trimmed to its last row with
+/// content.
+fn sticky_rows(
+    prev: &RgbaImage,
+    current: &RgbaImage,
+    region: MatchRegion,
+    rows: impl Iterator<Item = u32>,
+) -> u32 {
+    let mut sticky = 0;
+    for (run, y) in (1..).zip(rows) {
+        if row_difference(prev, current, region, y) > STATIC_ROW_TOLERANCE {
+            break;
+        }
+        if row_has_content(current, region, y) {
+            sticky = run;
+        }
+    }
+    sticky
+}
+
+/// Detect rows that stay put between two consecutive, scrolled frames (fixed
+/// nav bars, toolbars, status bars) and return the band in between.
+///
+/// Flat rows look static whether or not they scroll, so a sticky run only
+/// extends as far as its last row with content; blank margins and plain page
+/// backgrounds stay in the band.
+pub fn detect_scroll_band(
+    prev: &RgbaImage,
+    current: &RgbaImage,
+    region: MatchRegion,
+) -> ScrollBand {
+    let height = prev.height();
+    let max_sticky = height / MAX_STICKY_FRACTION;
+    let header = sticky_rows(prev, current, region, 0..max_sticky);
+    let footer = sticky_rows(
+        prev,
+        current,
+        region,
+        (0..max_sticky).map(|i| height - 1 - i),
+    );
+
+    let band = ScrollBand {
+        top: header,
+        bottom: height - footer,
+    };
+    if band.height() < MIN_OVERLAP + MIN_NEW_CONTENT {
+        return ScrollBand::full(height);
+    }
+    band
+}
+
+/// Mean difference between the bottom `overlap` rows of `prev`'s band and the
+/// top `overlap` rows of `current`'s band, sampled over the columns in
+/// `region`.
+pub fn overlap_error(
+    prev: &RgbaImage,
+    current: &RgbaImage,
+    band: ScrollBand,
+    region: MatchRegion,
+    overlap: u32,
+) -> f64 {
+    overlap_error_with_step(prev, current, band, region, overlap, (overlap / 80).max(1))
+}
+
+/// Like `overlap_error`, but comparing every row of the overlap.
+pub fn dense_overlap_error(
+    prev: &RgbaImage,
+    current: &RgbaImage,
+    band: ScrollBand,
+    region: MatchRegion,
+    overlap: u32,
+) -> f64 {
+    overlap_error_with_step(prev, current, band, region, overlap, 1)
+}
+
+fn overlap_error_with_step(
+    prev: &RgbaImage,
+    current: &RgbaImage,
+    band: ScrollBand,
+    region: MatchRegion,
+    overlap: u32,
+    row_step: u32,
+) -> f64 {
+    if overlap == 0 || overlap > band.height() {
+        return f64::MAX;
+    }
+
+    let (x_start, x_end) = region.span(prev.width());
+    let col_step = ((x_end.saturating_sub(x_start)) / 70).max(1);
+
+    let mut total = 0.0;
+    let mut samples = 0u64;
+    let mut r = 0;
+    while r < overlap {
+        let prev_y = band.bottom - overlap + r;
+        let curr_y = band.top + r;
+        let mut x = x_start;
+        while x < x_end {
+            total += pixel_difference(prev.get_pixel(x, prev_y), current.get_pixel(x, curr_y));
+            samples += 1;
+            x = x.saturating_add(col_step);
+        }
+        r = r.saturating_add(row_step);
+    }
+
+    if samples == 0 {
+        return f64::MAX;
+    }
+    total / samples as f64
+}
+
+/// Outcome of an overlap search.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OverlapMatch {
+    /// Rows shared by the bottom of the previous band and the top of the
+    /// current band.
+    pub overlap: u32,
+    /// Mean channel error at `overlap`, 0-255.
+    pub error: f64,
+    /// Confidence in 0.0-1.0: low error and a clear margin over the best
+    /// match elsewhere both push it up.
+    pub score: f64,
+}
+
+/// Candidate overlaps for a band of `height` rows.
+fn overlap_range(height: u32) -> (u32, u32) {
+    let min_overlap = MIN_OVERLAP.min(height.saturating_sub(1));
+    let max_overlap = height.saturating_sub(MIN_NEW_CONTENT).max(min_overlap);
+    (min_overlap, max_overlap)
+}
+
+/// Row downscale factor for the coarse pass over a band of `height` rows.
+fn pyramid_factor(height: u32) -> u32 {
+    (height / COARSE_TARGET_ROWS).clamp(1, MAX_PYRAMID_FACTOR)
+}
+
+/// Coarse level for the first search pass: `factor` rows are averaged into
+/// one, and at most `COARSE_COLUMNS` columns of `region` are kept.
+fn coarse_level(frame: &RgbaImage, region: MatchRegion, factor: u32) -> RgbaImage {
+    let (x_start, x_end) = region.span(frame.width());
+    let span = x_end - x_start;
+    let columns = span.clamp(1, COARSE_COLUMNS);
+    RgbaImage::from_fn(columns, frame.height() / factor, |cx, cy| {
+        let x = x_start + cx * span / columns;
+        let mut sum = [0u32; 4];
+        for y in cy * factor..(cy + 1) * factor {
+            let pixel = frame.get_pixel(x, y);
+            for (total, channel) in sum.iter_mut().zip(pixel.0) {
+                *total += u32::from(channel);
+            }
+        }
+        image::Rgba(sum.map(|total| (total / factor) as u8))
+    })
+}
+
+fn confidence(error: f64, runner_up: Option<f64>) -> f64 {
+    let quality = (1.0 - error / MAX_MATCH_ERROR).clamp(0.0, 1.0);
+    let distinctness = match runner_up {
+        Some(other) if other > 0.0 => ((other - error) / other).clamp(0.0, 1.0),
+        Some(_) => 0.0,
+        None => 1.0,
+    };
+    quality * distinctness
+}
+
+fn validate_match(best: Option<OverlapMatch>) -> Result<OverlapMatch, CaptureError> {
+    let Some(best) = best else {
+        return Err(CaptureError::new(
+            CaptureErrorKind::StitchFailed,
+            "Failed to detect overlap between captured frames",
+        ));
+    };
+
+    if best.error > MAX_MATCH_ERROR {
+        return Err(CaptureError::new(
+            CaptureErrorKind::StitchFailed,
+            "Scroll frame matching failed. Try slower scrolling and keep region stable.",
+        ));
+    }
+
+    Ok(best)
+}
+
+/// Hash every row of `frame` over the columns in `region`. Rows with equal
+/// hashes are identical barring collisions, which `find_exact_overlap`
+/// rules out before trusting a match.
+pub fn row_hashes(frame: &RgbaImage, region: MatchRegion) -> Vec<u64> {
+    let (x_start, x_end) = region.span(frame.width());
+    let row_bytes = frame.width() as usize * 4;
+    if row_bytes == 0 {
+        return vec![0; frame.height() as usize];
+    }
+    frame
+        .as_raw()
+        .chunks_exact(row_bytes)
+        .map(|row| hash_bytes(&row[x_start as usize * 4..x_end as usize * 4]))
+        .collect()
+}
+
+/// Multiply-rotate hash over 8-byte words; fast enough to run on every row
+/// of every frame.
+fn hash_bytes(bytes: &[u8]) -> u64 {
+    const K: u64 = 0x517c_c1b7_2722_0a95;
+    let mut hash = bytes.len() as u64;
+    let mut words = bytes.chunks_exact(8);
+    for word in &mut words {
+        let word = u64::from_le_bytes(word.try_into().expect("chunk is 8 bytes"));
+        hash = (hash.rotate_left(5) ^ word).wrapping_mul(K);
+    }
+    for &byte in words.remainder() {
+        hash = (hash.rotate_left(5) ^ u64::from(byte)).wrapping_mul(K);
+    }
+    hash
+}
+
+/// Byte-compare the overlapping rows of two frames inside `region`.
+fn rows_identical(
+    prev: &RgbaImage,
+    current: &RgbaImage,
+    band: ScrollBand,
+    region: MatchRegion,
+    overlap: u32,
+) -> bool {
+    let (x_start, x_end) = region.span(prev.width());
+    let row_bytes = prev.width() as usize * 4;
+    let row = |y: u32| {
+        let start = y as usize * row_bytes;
+        start + x_start as usize * 4..start + x_end as usize * 4
+    };
+    (0..overlap).all(|r| {
+        prev.as_raw()[row(band.bottom - overlap + r)] == current.as_raw()[row(band.top + r)]
+    })
+}
+
+/// Exact-match fast path for content that scrolls by whole pixels without
+/// animation.
+///
+/// For each candidate overlap the run of equal row hashes from the top of
+/// `current`'s band is compared against the bottom of `prev`'s band; the
+/// overlap matches when the run covers it entirely. Returns `None` when no
+/// overlap or more than one overlap matches exactly (repeating content), in
+/// which case the caller should fall back to `find_best_overlap`.
+pub fn find_exact_overlap(
+    prev: &RgbaImage,
+    prev_hashes: &[u64],
+    current: &RgbaImage,
+    current_hashes: &[u64],
+    band: ScrollBand,
+    region: MatchRegion,
+) -> Option<OverlapMatch> {
+    let (min_overlap, max_overlap) = overlap_range(band.height());
+    let range = band.top as usize..band.bottom as usize;
+    let (Some(prev_band), Some(current_band)) =
+        (prev_hashes.get(range.clone()), current_hashes.get(range))
+    else {
+        return None;
+    };
+    if min_overlap == 0 {
+        return None;
+    }
+
+    let height = band.height() as usize;
+    let mut found = None;
+    for overlap in min_overlap..=max_overlap {
+        let size = overlap as usize;
+        let run = prev_band[height - size..]
+            .iter()
+            .zip(&current_band[..size])
+            .take_while(|(p, c)| p == c)
+            .count();
+        if run < size {
+            continue;
+        }
+        if found.is_some() {
+            return None;
+        }
+        found = Some(overlap);
+    }
+
+    let overlap = found?;
+    rows_identical(prev, current, band, region, overlap).then_some(OverlapMatch {
+        overlap,
+        error: 0.0,
+        score: 1.0,
+    })
+}
+
+/// Find the overlap (in rows) between the bottom of `prev`'s band and the top
+/// of `current`'s band.
+///
+/// A coarse pass over row-averaged frames picks a few candidate overlaps,
+/// each is refined at full resolution, and the winner is verified against
+/// its direct neighbours using every row of the overlap.
+pub fn find_best_overlap(
+    prev: &RgbaImage,
+    current: &RgbaImage,
+    band: ScrollBand,
+    region: MatchRegion,
+) -> Result<OverlapMatch, CaptureError> {
+    let (min_overlap, max_overlap) = overlap_range(band.height());
+    if min_overlap == 0 {
+        return validate_match(None);
+    }
+
+    let factor = pyramid_factor(band.height());
+    let coarse_prev = coarse_level(prev, region, factor);
+    let coarse_current = coarse_level(current, region, factor);
+    let coarse_band = ScrollBand {
+        top: band.top.div_ceil(factor),
+        bottom: band.bottom / factor,
+    };
+    let whole = MatchRegion {
+        start_percent: 0,
+        end_percent: 100,
+    };
+
+    let mut coarse: Vec<(u32, f64)> = (min_overlap / factor..=max_overlap / factor)
+        .filter(|overlap| *overlap > 0)
+        .map(|overlap| {
+            let error = overlap_error(&coarse_prev, &coarse_current, coarse_band, whole, overlap);
+            (overlap, error)
+        })
+        .collect();
+    coarse.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    let mut candidates: Vec<u32> = Vec::with_capacity(COARSE_CANDIDATES);
+    for (overlap, _) in coarse {
+        if candidates.len() == COARSE_CANDIDATES {
+            break;
+        }
+        if candidates.iter().all(|picked| picked.abs_diff(overlap) > 2) {
+            candidates.push(overlap);
+        }
+    }
+
+    // Rows lost to rounding the band onto the coarse grid.
+    let slack = band.height() - coarse_band.height() * factor;
+    let mut refined: Vec<(u32, f64)> = candidates
+        .into_iter()
+        .filter_map(|coarse_overlap| {
+            let center = coarse_overlap * factor + slack;
+            let low = center.saturating_sub(factor).max(min_overlap);
+            let high = (center + factor).min(max_overlap);
+            (low..=high)
+                .map(|overlap| (overlap, overlap_error(prev, current, band, region, overlap)))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+        })
+        .collect();
+    refined.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    let Some(&(candidate, _)) = refined.first() else {
+        return validate_match(None);
+    };
+    let (overlap, error) = (candidate.saturating_sub(1)..=candidate + 1)
+        .filter(|overlap| (min_overlap..=max_overlap).contains(overlap))
+        .map(|overlap| {
+            (
+                overlap,
+                dense_overlap_error(prev, current, band, region, overlap),
+            )
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or((candidate, f64::MAX));
+    let runner_up = refined
+        .get(1)
+        .map(|&(other, _)| dense_overlap_error(prev, current, band, region, other));
+
+    validate_match(Some(OverlapMatch {
+        overlap,
+        error,
+        score: confidence(error, runner_up),
+    }))
+}
+
+/// Single-resolution search stepping two rows at a time, as used before the
+/// coarse-to-fine search. Kept as a reference for tests and benchmarks.
+pub fn find_best_overlap_exhaustive(
+    prev: &RgbaImage,
+    current: &RgbaImage,
+    band: ScrollBand,
+    region: MatchRegion,
+) -> Result<OverlapMatch, CaptureError> {
+    let (min_overlap, max_overlap) = overlap_range(band.height());
+
+    let mut best: Option<OverlapMatch> = None;
+    let mut overlap = min_overlap;
+    while overlap > 0 && overlap <= max_overlap {
+        let error = overlap_error(prev, current, band, region, overlap);
+        if best.is_none_or(|best| error < best.error) {
+            best = Some(OverlapMatch {
+                overlap,
+                error,
+                score: confidence(error, None),
+            });
+        }
+        overlap = overlap.saturating_add(2);
+    }
+
+    validate_match(best)
+}
+
+/// Copy of `image` scaled down (aspect preserved) to at most `max_width`.
+pub fn downscale_to_width(image: &RgbaImage, max_width: u32) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    if width <= max_width || max_width == 0 {
+        return image.clone();
+    }
+    let scaled_height = ((height as u64 * max_width as u64) / width as u64).max(1) as u32;
+    imageops::thumbnail(image, max_width, scaled_height)
+}
+
+/// Copy of `image` scaled by `numerator / denominator`, if that shrinks it.
+fn downscale_by(image: &RgbaImage, numerator: u32, denominator: u32) -> RgbaImage {
+    if numerator >= denominator || numerator == 0 {
+        return image.clone();
+    }
+    let scale = |size: u32| ((size as u64 * numerator as u64) / denominator as u64).max(1) as u32;
+    imageops::thumbnail(image, scale(image.width()), scale(image.height()))
+}
+
+/// Swap rows and columns, mapping horizontal scrolling onto vertical.
+fn transpose(image: &RgbaImage) -> RgbaImage {
+    RgbaImage::from_fn(image.height(), image.width(), |x, y| *image.get_pixel(y, x))
+}
+
+/// Stack images of equal width top to bottom.
+fn stack(top: &RgbaImage, bottom: &RgbaImage) -> RgbaImage {
+    let mut raw = Vec::with_capacity(top.as_raw().len() + bottom.as_raw().len());
+    raw.extend_from_slice(top.as_raw());
+    raw.extend_from_slice(bottom.as_raw());
+    RgbaImage::from_raw(top.width(), top.height() + bottom.height(), raw)
+        .expect("stacked buffer matches dimensions")
+}
+
+/// Incremental scroll stitcher.
+///
+/// The scrolling band is detected from the first two pairs of differing
+/// frames; rows only count as sticky if they stayed put in both. Any sticky
+/// header stays at the top of the canvas; a sticky footer is held aside and
+/// emitted once below the stitched content. For horizontal sessions all of
+/// this happens on transposed frames: "rows" are columns of the original,
+/// and `captured_height_px` counts stitched width.
+#[derive(Debug, Clone)]
+pub struct Stitcher {
+    session: ScrollSession,
+    direction: ScrollDirection,
+    region: MatchRegion,
+    canvas: Option<RgbaImage>,
+    footer: Option<RgbaImage>,
+    band: Option<ScrollBand>,
+    /// Set once a second pair confirmed `band`.
+    band_locked: bool,
+    /// Shared with the caller for vertical scrolling, when frames are
+    /// pushed as `Arc`s.
+    prev_frame: Option<Arc<RgbaImage>>,
+    prev_hashes: Vec<u64>,
+    /// Rows each frame after the first added to the canvas.
+    slices: Vec<u32>,
+    total_frames: usize,
+    used_frames: usize,
+    skipped_frames: usize,
+}
+
+impl Stitcher {
+    pub fn new(config: ScrollConfig) -> Self {
+        Self::with_session(ScrollSession::new(config))
+    }
+
+    /// Stitcher driven by `clock` for throttle and timeout checks.
+    pub fn with_clock(config: ScrollConfig, clock: Arc<dyn Clock>) -> Self {
+        Self::with_session(ScrollSession::with_clock(config, clock))
+    }
+
+    fn with_session(session: ScrollSession) -> Self {
+        let config = *session.config();
+        Self {
+            session,
+            direction: config.direction,
+            region: config.match_region,
+            canvas: None,
+            footer: None,
+            band: None,
+            band_locked: false,
+            prev_frame: None,
+            prev_hashes: Vec::new(),
+            slices: Vec::new(),
+            total_frames: 0,
+            used_frames: 0,
+            skipped_frames: 0,
+        }
+    }
+
+    /// Append the next frame. Frames that do not add content, or arrive while
+    /// paused or throttled, are reported as `Skipped`; once the session
+    /// stops, further frames are rejected. An `Arc` is kept as is rather
+    /// than copied.
+    pub fn push(&mut self, frame: impl Into<Arc<RgbaImage>>) -> Result<AppendResult, CaptureError> {
+        let frame = frame.into();
+        if self.session.is_finished() {
+            return Err(CaptureError::new(
+                CaptureErrorKind::ValidationFailed,
+                "Scroll session has already stopped",
+            ));
+        }
+
+        if frame.width() < MIN_FRAME_SIZE || frame.height() < MIN_FRAME_SIZE {
+            return Err(CaptureError::new(
+                CaptureErrorKind::ValidationFailed,
+                "Captured frame is too small",
+            ));
+        }
+        if let Some(result) = self.session.begin_frame() {
+            return Ok(result);
+        }
+        let frame = match self.direction {
+            ScrollDirection::Vertical => frame,
+            ScrollDirection::Horizontal => Arc::new(transpose(&frame)),
+        };
+        let hashes = row_hashes(&frame, self.region);
+
+        let Some(prev_frame) = self.prev_frame.as_ref() else {
+            self.total_frames += 1;
+            let result = self.session.append_accepted(frame.height(), 1.0);
+            self.canvas = Some(RgbaImage::clone(&frame));
+            self.used_frames = 1;
+            self.prev_frame = Some(frame);
+            self.prev_hashes = hashes;
+            return Ok(result);
+        };
+
+        if frame.dimensions() != prev_frame.dimensions() {
+            return Err(CaptureError::new(
+                CaptureErrorKind::StitchFailed,
+                "Scroll frames have different dimensions",
+            ));
+        }
+        self.total_frames += 1;
+
+        let difference =
+            sample_frame_difference(prev_frame, &frame, self.region, ScrollDirection::Vertical);
+        if difference < MIN_FRAME_DIFFERENCE {
+            self.skipped_frames += 1;
+            return Ok(self.session.append_skipped(SkipReason::Duplicate));
+        }
+
+        if !self.band_locked {
+            let detected = detect_scroll_band(prev_frame, &frame, self.region);
+            self.observe_band(detected);
+        }
+        let band = self.band.expect("band observed above");
+        let prev_frame = self.prev_frame.as_ref().expect("checked above");
+        let matched = find_exact_overlap(
+            prev_frame,
+            &self.prev_hashes,
+            &frame,
+            &hashes,
+            band,
+            self.region,
+        )
+        .map_or_else(
+            || find_best_overlap(prev_frame, &frame, band, self.region),
+            Ok,
+        );
+
+        let OverlapMatch { overlap, score, .. } = match matched {
+            Ok(found) => found,
+            Err(_) => {
+                self.skipped_frames += 1;
+                return Ok(self.session.append_failed());
+            }
+        };
+
+        let slice_height = band.height().saturating_sub(overlap);
+        if slice_height < MIN_SLICE_HEIGHT {
+            self.skipped_frames += 1;
+            return Ok(self.session.append_skipped(SkipReason::TooSmallDelta));
+        }
+
+        let result = self.session.append_accepted(slice_height, score);
+        self.append_rows(&frame, band.top + overlap, band.bottom);
+        self.used_frames += 1;
+        self.prev_frame = Some(frame);
+        self.prev_hashes = hashes;
+        Ok(result)
+    }
+
+    /// Rebuild a stitcher from frames accepted in an earlier run, given with
+    /// the slice height each one added. Slices are re-applied as recorded
+    /// rather than matched again, so the canvas comes out identical. `band`
+    /// is that run's `scroll_band()` and is required once there is more than
+    /// one frame.
+    pub fn restore(
+        config: ScrollConfig,
+        band: Option<ScrollBand>,
+        frames: impl IntoIterator<Item = (RgbaImage, u32)>,
+    ) -> Result<Self, CaptureError> {
+        let mut stitcher = Self::new(config);
+        stitcher.session.mark_capturing();
+
+        for (frame, dy) in frames {
+            if frame.width() < MIN_FRAME_SIZE || frame.height() < MIN_FRAME_SIZE {
+                return Err(CaptureError::new(
+                    CaptureErrorKind::ValidationFailed,
+                    "Captured frame is too small",
+                ));
+            }
+            let frame = match stitcher.direction {
+                ScrollDirection::Vertical => frame,
+                ScrollDirection::Horizontal => transpose(&frame),
+            };
+            let hashes = row_hashes(&frame, stitcher.region);
+
+            match stitcher.prev_frame.as_ref() {
+                None => {
+                    stitcher.session.append_accepted(frame.height(), 1.0);
+                    stitcher.canvas = Some(frame.clone());
+                    if let Some(band) = band {
+                        stitcher.split_footer(band);
+                        stitcher.band = Some(band);
+                    }
+                }
+                Some(prev_frame) => {
+                    let Some(band) = band else {
+                        return Err(CaptureError::new(
+                            CaptureErrorKind::ValidationFailed,
+                            "Restored scroll frames need a scroll band",
+                        ));
+                    };
+                    if frame.dimensions() != prev_frame.dimensions()
+                        || band.bottom > frame.height()
+                        || dy == 0
+                        || dy > band.height()
+                    {
+                        return Err(CaptureError::new(
+                            CaptureErrorKind::ValidationFailed,
+                            "Restored scroll frame does not fit the recorded layout",
+                        ));
+                    }
+                    stitcher.session.append_accepted(dy, 1.0);
+                    stitcher.append_rows(&frame, band.bottom - dy, band.bottom);
+                }
+            }
+            stitcher.total_frames += 1;
+            stitcher.used_frames += 1;
+            stitcher.prev_frame = Some(Arc::new(frame));
+            stitcher.prev_hashes = hashes;
+        }
+        stitcher.band_locked = stitcher.used_frames > 2;
+
+        Ok(stitcher)
+    }
+
+    /// Carry over what a restored run recorded beyond its accepted frames:
+    /// its state and stop reason, and how many frames it captured and
+    /// skipped in total. Fails if the stop reason does not fit the state.
+    pub fn restore_status(
+        &mut self,
+        state: ScrollSessionState,
+        stop_reason: Option<StopReason>,
+        total_frames: usize,
+        skipped_frames: usize,
+    ) -> Result<(), CaptureError> {
+        self.session.restore_state(state, stop_reason)?;
+        self.skipped_frames = skipped_frames;
+        self.total_frames = total_frames.max(self.used_frames + skipped_frames);
+        Ok(())
+    }
+
+    /// Fold the band detected for a new pair of frames into the current one.
+    /// Rows stay sticky only while every pair agrees; the band is locked after
+    /// the second pair.
+    fn observe_band(&mut self, detected: ScrollBand) {
+        let Some(band) = self.band else {
+            self.split_footer(detected);
+            self.band = Some(detected);
+            return;
+        };
+        self.widen_band(ScrollBand {
+            top: band.top.min(detected.top),
+            bottom: band.bottom.max(detected.bottom),
+        });
+        self.band_locked = true;
+    }
+
+    /// Grow the band to `wider`. Footer rows of the previous frame that turned
+    /// out to scroll move from the held-aside footer onto the canvas; header
+    /// rows need nothing, since the canvas starts with the whole first frame.
+    fn widen_band(&mut self, wider: ScrollBand) {
+        let Some(band) = self.band else {
+            return;
+        };
+        if wider.bottom > band.bottom {
+            if let Some(prev_frame) = self.prev_frame.take() {
+                self.append_rows(&prev_frame, band.bottom, wider.bottom);
+                self.prev_frame = Some(prev_frame);
+            }
+            self.footer = self.footer.take().and_then(|footer| {
+                let (width, height) = footer.dimensions();
+                let keep = height.checked_sub(wider.bottom - band.bottom)?;
+                (keep > 0)
+                    .then(|| imageops::crop_imm(&footer, 0, height - keep, width, keep).to_image())
+            });
+        }
+        self.band = Some(wider);
+    }
+
+    /// Move the first frame's footer rows off the canvas so later slices land
+    /// above it. Only valid while the canvas still holds just the first frame.
+    fn split_footer(&mut self, band: ScrollBand) {
+        let Some(canvas) = self.canvas.take() else {
+            return;
+        };
+        let (width, height) = canvas.dimensions();
+        if band.bottom >= height {
+            self.canvas = Some(canvas);
+            return;
+        }
+        let row_bytes = width as usize * 4;
+        let mut raw = canvas.into_raw();
+        let footer = raw.split_off(band.bottom as usize * row_bytes);
+        self.footer = RgbaImage::from_raw(width, height - band.bottom, footer);
+        self.canvas = RgbaImage::from_raw(width, band.bottom, raw);
+    }
+
+    /// Extend the canvas with rows `from_row..to_row` of `frame`.
+    fn append_rows(&mut self, frame: &RgbaImage, from_row: u32, to_row: u32) {
+        let Some(canvas) = self.canvas.take() else {
+            return;
+        };
+        let (width, height) = canvas.dimensions();
+        let row_bytes = width as usize * 4;
+        let mut raw = canvas.into_raw();
+        raw.extend_from_slice(
+            &frame.as_raw()[from_row as usize * row_bytes..to_row as usize * row_bytes],
+        );
+        self.canvas = RgbaImage::from_raw(width, height + (to_row - from_row), raw);
+        self.slices.push(to_row - from_row);
+    }
+
+    pub fn progress(&self) -> ScrollProgress {
+        self.session.progress()
+    }
+
+    pub fn stop_reason(&self) -> Option<StopReason> {
+        self.session.stop_reason()
+    }
+
+    pub fn pause(&mut self) -> Result<(), CaptureError> {
+        self.session.pause()
+    }
+
+    pub fn resume(&mut self) -> Result<(), CaptureError> {
+        self.session.resume()
+    }
+
+    pub fn cancel(&mut self) {
+        self.session.cancel();
+    }
+
+    /// See `ScrollSession::check_timeout`.
+    pub fn check_timeout(&mut self) -> Option<AppendResult> {
+        self.session.check_timeout()
+    }
+
+    pub fn total_frames(&self) -> usize {
+        self.total_frames
+    }
+
+    /// Number of frames that contributed content, including the first one.
+    pub fn used_frames(&self) -> usize {
+        self.used_frames
+    }
+
+    pub fn skipped_frames(&self) -> usize {
+        self.skipped_frames
+    }
+
+    pub fn config(&self) -> &ScrollConfig {
+        self.session.config()
+    }
+
+    /// The scrolling band, once two differing frames have been compared. For
+    /// horizontal sessions it spans columns rather than rows.
+    pub fn scroll_band(&self) -> Option<ScrollBand> {
+        self.band
+    }
+
+    /// Downscaled copy of the stitched image no wider than `max_width`. For
+    /// horizontal sessions the parts are scaled before they are transposed,
+    /// so only the preview-sized result is transposed.
+    pub fn preview(&self, max_width: u32) -> Option<RgbaImage> {
+        let canvas = self.canvas.as_ref()?;
+        // The canvas extent that ends up as the preview's width.
+        let span = match self.direction {
+            ScrollDirection::Vertical => canvas.width(),
+            ScrollDirection::Horizontal => {
+                canvas.height() + self.footer.as_ref().map_or(0, |footer| footer.height())
+            }
+        };
+        let canvas = downscale_by(canvas, max_width, span);
+        let composed = match self.footer.as_ref() {
+            Some(footer) => stack(&canvas, &downscale_by(footer, max_width, span)),
+            None => canvas,
+        };
+        Some(self.to_capture_orientation(composed))
+    }
+
+    /// Copy `rows` rows of `source` starting at `from_row` to `to_row` of
+    /// `out`, transposing them for horizontal sessions.
+    fn copy_rows(
+        &self,
+        out: &mut RgbaImage,
+        source: &RgbaImage,
+        from_row: u32,
+        rows: u32,
+        to_row: u32,
+    ) {
+        match self.direction {
+            ScrollDirection::Vertical => {
+                let row_bytes = source.width() as usize * 4;
+                let from = from_row as usize * row_bytes;
+                let to = to_row as usize * row_bytes;
+                let len = rows as usize * row_bytes;
+                let out: &mut [u8] = out;
+                out[to..to + len].copy_from_slice(&source.as_raw()[from..from + len]);
+            }
+            ScrollDirection::Horizontal => {
+                for y in 0..rows {
+                    for x in 0..source.width() {
+                        out.put_pixel(to_row + y, x, *source.get_pixel(x, from_row + y));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Map a composed image back to the capture orientation.
+    fn to_capture_orientation(&self, image: RgbaImage) -> RgbaImage {
+        match self.direction {
+            ScrollDirection::Vertical => image,
+            ScrollDirection::Horizontal => transpose(&image),
+        }
+    }
+
+    fn ensure_stitchable(&self) -> Result<(), CaptureError> {
+        if self.used_frames < 2 || self.canvas.is_none() {
+            return Err(CaptureError::new(
+                CaptureErrorKind::StitchFailed,
+                "Not enough unique frames after filtering similar ones. Scroll further between captures.",
+            ));
+        }
+        Ok(())
+    }
+
+    /// The stitched image, once at least two frames contributed content.
+    pub fn stitched(&self) -> Result<RgbaImage, CaptureError> {
+        self.stitched_with_progress(|_, _| {})
+    }
+
+    /// Like `stitched`, but copies the canvas one frame's slice at a time
+    /// and calls `on_frame(done, total)` after each of the used frames.
+    pub fn stitched_with_progress(
+        &self,
+        mut on_frame: impl FnMut(usize, usize),
+    ) -> Result<RgbaImage, CaptureError> {
+        self.ensure_stitchable()?;
+        let canvas = self.canvas.as_ref().expect("canvas checked above");
+        let footer_rows = self.footer.as_ref().map_or(0, |footer| footer.height());
+        let (width, rows) = (canvas.width(), canvas.height() + footer_rows);
+        let mut out = match self.direction {
+            ScrollDirection::Vertical => RgbaImage::new(width, rows),
+            ScrollDirection::Horizontal => RgbaImage::new(rows, width),
+        };
+
+        let first_rows = canvas.height() - self.slices.iter().sum::<u32>();
+        let total = self.slices.len() + 1;
+        let mut top = 0;
+        for (index, slice) in std::iter::once(first_rows)
+            .chain(self.slices.iter().copied())
+            .enumerate()
+        {
+            self.copy_rows(&mut out, canvas, top, slice, top);
+            top += slice;
+            on_frame(index + 1, total);
+        }
+        if let Some(footer) = self.footer.as_ref() {
+            self.copy_rows(&mut out, footer, 0, footer_rows, top);
+        }
+        Ok(out)
+    }
+
+    /// Consume the stitcher and return the final image.
+    pub fn finish(mut self) -> Result<RgbaImage, CaptureError> {
+        self.ensure_stitchable()?;
+        let canvas = self.canvas.take().expect("canvas checked above");
+        let composed = match self.footer.take() {
+            Some(footer) => stack(&canvas, &footer),
+            None => canvas,
+        };
+        Ok(self.to_capture_orientation(composed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ManualClock;
+    use std::time::Duration;
+
+    /// Default config without throttling, since tests push frames back to back.
+    fn test_config() -> ScrollConfig {
+        ScrollConfig {
+            throttle_ms: 0,
+            ..ScrollConfig::default()
+        }
+    }
+
+    fn build_frame(width: u32, height: u32, start: u32) -> RgbaImage {
+        let mut img = RgbaImage::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let value = ((start + y + x / 3) % 255) as u8;
+                img.put_pixel(x, y, image::Rgba([value, value / 2, 255 - value, 255]));
+            }
+        }
+        img
+    }
+
+    fn content_pixel(x: u32, y: u32) -> image::Rgba<u8> {
+        let mut h = (x / 2).wrapping_mul(73_856_093) ^ y.wrapping_mul(19_349_663);
+        h ^= h >> 13;
+        h = h.wrapping_mul(0x5bd1_e995);
+        image::Rgba([h as u8, (h >> 8) as u8, (h >> 16) as u8, 255])
+    }
+
+    /// Frame of scrolled content at `offset` between a fixed header and footer.
+    fn sticky_frame(width: u32, header: u32, body: u32, footer: u32, offset: u32) -> RgbaImage {
+        RgbaImage::from_fn(width, header + body + footer, |x, y| {
+            if y < header {
+                image::Rgba([20, (x % 200) as u8, 90, 255])
+            } else if y < header + body {
+                content_pixel(x, y - header + offset)
+            } else {
+                image::Rgba([(x % 100) as u8, 200, 40, 255])
+            }
+        })
+    }
+
+    #[test]
+    fn sample_frame_difference_detects_identical_frame() {
+        let frame1 = build_frame(120, 180, 0);
+        let frame2 = build_frame(120, 180, 0);
+        let diff = sample_frame_difference(
+            &frame1,
+            &frame2,
+            MatchRegion::default(),
+            ScrollDirection::Vertical,
+        );
+        assert!(diff < 0.5);
+    }
+
+    #[test]
+    fn find_best_overlap_detects_scroll_delta() {
+        let frame1 = build_frame(160, 240, 0);
+        let scroll_delta = 80;
+        let frame2 = build_frame(160, 240, scroll_delta);
+        let band = ScrollBand::full(240);
+        let found = find_best_overlap(&frame1, &frame2, band, MatchRegion::default())
+            .expect("overlap should be found");
+        assert_eq!(found.overlap, 240 - scroll_delta);
+        assert_eq!(found.error, 0.0);
+        assert_eq!(found.score, 1.0);
+    }
+
+    #[test]
+    fn find_best_overlap_is_pixel_exact_on_tall_frames() {
+        let frame_at =
+            |offset: u32| RgbaImage::from_fn(400, 1200, |x, y| content_pixel(x, y + offset));
+        let band = ScrollBand::full(1200);
+        let region = MatchRegion::default();
+        for delta in [41, 331, 700, 1152] {
+            let found = find_best_overlap(&frame_at(0), &frame_at(delta), band, region).unwrap();
+            assert_eq!(found.overlap, 1200 - delta, "delta {}", delta);
+            assert_eq!(found.error, 0.0);
+            assert!(found.score > 0.99);
+
+            // The two-row stepping reference only lands on even deltas.
+            if delta % 2 == 0 {
+                let reference =
+                    find_best_overlap_exhaustive(&frame_at(0), &frame_at(delta), band, region)
+                        .unwrap();
+                assert_eq!(reference.overlap, found.overlap);
+            }
+        }
+    }
+
+    #[test]
+    fn find_exact_overlap_matches_odd_deltas() {
+        let frame_at =
+            |offset: u32| RgbaImage::from_fn(400, 1200, |x, y| content_pixel(x, y + offset));
+        let band = ScrollBand::full(1200);
+        let region = MatchRegion::default();
+        let prev = frame_at(0);
+        let prev_hashes = row_hashes(&prev, region);
+        for delta in [41, 333, 1159] {
+            let current = frame_at(delta);
+            let current_hashes = row_hashes(&current, region);
+            let found =
+                find_exact_overlap(&prev, &prev_hashes, &current, &current_hashes, band, region)
+                    .expect("exact overlap");
+            assert_eq!(found.overlap, 1200 - delta, "delta {}", delta);
+            assert_eq!(found.score, 1.0);
+        }
+    }
+
+    #[test]
+    fn find_exact_overlap_defers_on_repeating_or_noisy_content() {
+        let region = MatchRegion::default();
+        let band = ScrollBand::full(300);
+        let stripes = |offset: u32| {
+            RgbaImage::from_fn(200, 300, |x, y| {
+                let value = if (y + offset) % 30 < 3 { 0 } else { 250 };
+                image::Rgba([value, value, (x % 256) as u8, 255])
+            })
+        };
+        let (prev, current) = (stripes(0), stripes(90));
+        let ambiguous = find_exact_overlap(
+            &prev,
+            &row_hashes(&prev, region),
+            &current,
+            &row_hashes(&current, region),
+            band,
+            region,
+        );
+        assert_eq!(ambiguous, None);
+
+        // One-level noise on every pixel defeats exact matching but not the
+        // fuzzy search.
+        let prev = build_frame(200, 300, 0);
+        let mut current = build_frame(200, 300, 70);
+        for pixel in current.pixels_mut() {
+            pixel[0] = pixel[0].saturating_add(1);
+        }
+        let exact = find_exact_overlap(
+            &prev,
+            &row_hashes(&prev, region),
+            &current,
+            &row_hashes(&current, region),
+            band,
+            region,
+        );
+        assert_eq!(exact, None);
+        let fuzzy = find_best_overlap(&prev, &current, band, region).unwrap();
+        assert_eq!(fuzzy.overlap, 230);
+    }
+
+    #[test]
+    fn find_best_overlap_scores_ambiguous_content_low() {
+        // Stripes repeating every 30 rows match equally well at many offsets.
+        let frame_at = |offset: u32| {
+            RgbaImage::from_fn(200, 300, |x, y| {
+                let value = if (y + offset) % 30 < 3 { 0 } else { 250 };
+                image::Rgba([value, value, (x % 256) as u8, 255])
+            })
+        };
+        let found = find_best_overlap(
+            &frame_at(0),
+            &frame_at(90),
+            ScrollBand::full(300),
+            MatchRegion::default(),
+        )
+        .unwrap();
+        assert_eq!(found.error, 0.0);
+        assert!(found.score < 0.1, "score {}", found.score);
+    }
+
+    #[test]
+    fn stitcher_appends_new_rows_and_reports_progress() {
+        let mut stitcher = Stitcher::new(test_config());
+        let first = stitcher.push(build_frame(160, 240, 0)).unwrap();
+        assert!(matches!(first, AppendResult::Accepted { dy: 240, .. }));
+
+        let second = stitcher.push(build_frame(160, 240, 80)).unwrap();
+        let AppendResult::Accepted { dy, score } = second else {
+            panic!("expected accepted frame, got {:?}", second);
+        };
+        assert!((dy as i32 - 80).abs() <= 8);
+        assert!(score > 0.8);
+
+        let progress = stitcher.progress();
+        assert_eq!(progress.frames, 2);
+        assert_eq!(progress.captured_height_px, 240 + dy);
+        assert_eq!(progress.state, ScrollSessionState::Capturing);
+
+        let image = stitcher.finish().unwrap();
+        assert_eq!(image.dimensions(), (160, 240 + dy));
+    }
+
+    #[test]
+    fn stitcher_skips_duplicate_frames() {
+        let mut stitcher = Stitcher::new(test_config());
+        stitcher.push(build_frame(120, 180, 0)).unwrap();
+        let result = stitcher.push(build_frame(120, 180, 0)).unwrap();
+        assert_eq!(result, AppendResult::Skipped(SkipReason::Duplicate));
+        assert_eq!(stitcher.total_frames(), 2);
+        assert_eq!(stitcher.used_frames(), 1);
+        assert_eq!(stitcher.skipped_frames(), 1);
+        assert!(stitcher.finish().is_err());
+    }
+
+    #[test]
+    fn stitcher_skips_throttled_and_paused_frames() {
+        let clock = ManualClock::new();
+        let mut stitcher = Stitcher::with_clock(
+            ScrollConfig {
+                throttle_ms: 100,
+                ..ScrollConfig::default()
+            },
+            Arc::new(clock.clone()),
+        );
+        stitcher.push(build_frame(160, 240, 0)).unwrap();
+        assert_eq!(
+            stitcher.push(build_frame(160, 240, 80)).unwrap(),
+            AppendResult::Skipped(SkipReason::Throttled)
+        );
+
+        clock.advance(Duration::from_millis(100));
+        stitcher.pause().unwrap();
+        assert_eq!(
+            stitcher.push(build_frame(160, 240, 80)).unwrap(),
+            AppendResult::Skipped(SkipReason::Paused)
+        );
+        assert_eq!(stitcher.total_frames(), 1);
+
+        stitcher.resume().unwrap();
+        let resumed = stitcher.push(build_frame(160, 240, 80)).unwrap();
+        assert!(matches!(resumed, AppendResult::Accepted { dy: 80, .. }));
+
+        stitcher.cancel();
+        assert_eq!(stitcher.stop_reason(), Some(StopReason::User));
+        assert!(stitcher.push(build_frame(160, 240, 160)).is_err());
+        assert_eq!(stitcher.finish().unwrap().height(), 320);
+    }
+
+    #[test]
+    fn stitcher_restore_rebuilds_identical_canvas() {
+        let frames: Vec<RgbaImage> = [0, 70, 150, 150, 230]
+            .into_iter()
+            .map(|offset| sticky_frame(160, 30, 200, 20, offset))
+            .collect();
+        let mut live = Stitcher::new(test_config());
+        let mut accepted = Vec::new();
+        for frame in &frames {
+            let before = live.progress();
+            live.push(frame.clone()).unwrap();
+            let after = live.progress();
+            if after.frames > before.frames {
+                accepted.push((
+                    frame.clone(),
+                    after.captured_height_px - before.captured_height_px,
+                ));
+            }
+        }
+        assert_eq!(accepted.len(), 4);
+
+        let band = live.scroll_band();
+        let restored = Stitcher::restore(test_config(), band, accepted.clone()).unwrap();
+        assert_eq!(restored.progress(), live.progress());
+        assert_eq!(restored.scroll_band(), live.scroll_band());
+        assert_eq!(restored.finish().unwrap(), live.finish().unwrap());
+
+        let mut stopped = Stitcher::restore(test_config(), band, accepted).unwrap();
+        assert!(stopped
+            .restore_status(ScrollSessionState::Done, None, 5, 1)
+            .is_err());
+        assert!(stopped
+            .restore_status(ScrollSessionState::Capturing, Some(StopReason::User), 5, 1)
+            .is_err());
+        stopped
+            .restore_status(ScrollSessionState::Done, Some(StopReason::User), 5, 1)
+            .unwrap();
+        assert_eq!(stopped.progress().state, ScrollSessionState::Done);
+        assert_eq!(stopped.stop_reason(), Some(StopReason::User));
+        assert_eq!((stopped.total_frames(), stopped.skipped_frames()), (5, 1));
+        assert!(stopped.push(frames[4].clone()).is_err());
+
+        let missing_band = Stitcher::restore(
+            test_config(),
+            None,
+            vec![(frames[0].clone(), 270), (frames[1].clone(), 70)],
+        );
+        assert!(missing_band.is_err());
+    }
+
+    #[test]
+    fn stitcher_canvas_matches_source_rows() {
+        let mut stitcher = Stitcher::new(test_config());
+        stitcher.push(build_frame(160, 240, 0)).unwrap();
+        stitcher.push(build_frame(160, 240, 80)).unwrap();
+        stitcher.push(build_frame(160, 240, 160)).unwrap();
+
+        let canvas = stitcher.stitched().unwrap();
+        let expected = build_frame(160, canvas.height(), 0);
+        let top = imageops::crop_imm(&canvas, 0, 0, 160, 240).to_image();
+        assert_eq!(
+            top,
+            imageops::crop_imm(&expected, 0, 0, 160, 240).to_image()
+        );
+        assert_eq!(canvas.height(), stitcher.progress().captured_height_px);
+    }
+
+    #[test]
+    fn stitcher_preview_is_downscaled() {
+        let mut stitcher = Stitcher::new(test_config());
+        assert!(stitcher.preview(80).is_none());
+        stitcher.push(build_frame(160, 240, 0)).unwrap();
+        let preview = stitcher.preview(80).unwrap();
+        assert_eq!(preview.dimensions(), (80, 120));
+        assert_eq!(stitcher.preview(400).unwrap().dimensions(), (160, 240));
+    }
+
+    #[test]
+    fn detect_scroll_band_finds_sticky_rows() {
+        let prev = sticky_frame(160, 30, 200, 20, 0);
+        let current = sticky_frame(160, 30, 200, 20, 60);
+        assert_eq!(
+            detect_scroll_band(&prev, &current, MatchRegion::default()),
+            ScrollBand {
+                top: 30,
+                bottom: 230
+            }
+        );
+
+        let plain = build_frame(160, 240, 0);
+        let scrolled = build_frame(160, 240, 80);
+        assert_eq!(
+            detect_scroll_band(&plain, &scrolled, MatchRegion::default()),
+            ScrollBand::full(240)
+        );
+    }
+
+    #[test]
+    fn blank_margins_are_not_sticky() {
+        // A blank top margin, then content, then a blank gap that reaches the
+        // bottom of the first frames.
+        let page = |x: u32, y: u32| match y {
+            0..=59 | 180..=299 => image::Rgba([255, 255, 255, 255]),
+            _ => content_pixel(x, y),
+        };
+        let frame_at = |offset: u32| RgbaImage::from_fn(160, 240, |x, y| page(x, y + offset));
+        assert_eq!(
+            detect_scroll_band(&frame_at(0), &frame_at(40), MatchRegion::default()),
+            ScrollBand::full(240)
+        );
+
+        let mut stitcher = Stitcher::new(test_config());
+        for offset in [0, 40, 80] {
+            let result = stitcher.push(frame_at(offset)).unwrap();
+            assert!(
+                matches!(result, AppendResult::Accepted { .. }),
+                "{:?}",
+                result
+            );
+        }
+        assert_eq!(stitcher.scroll_band(), Some(ScrollBand::full(240)));
+        assert_eq!(
+            stitcher.stitched().unwrap(),
+            RgbaImage::from_fn(160, 320, page)
+        );
+    }
+
+    #[test]
+    fn sticky_rows_must_hold_across_two_pairs() {
+        // Rows 180..300 repeat every 40 rows, so the first 40-row scroll
+        // leaves the bottom of the frame looking sticky.
+        let page = |x: u32, y: u32| match y {
+            180..=299 => content_pixel(x, 1_000 + y % 40),
+            _ => content_pixel(x, y),
+        };
+        let frame_at = |offset: u32| RgbaImage::from_fn(160, 240, |x, y| page(x, y + offset));
+
+        let mut stitcher = Stitcher::new(test_config());
+        stitcher.push(frame_at(0)).unwrap();
+        stitcher.push(frame_at(40)).unwrap();
+        assert_eq!(
+            stitcher.scroll_band(),
+            Some(ScrollBand {
+                top: 0,
+                bottom: 180
+            })
+        );
+
+        let result = stitcher.push(frame_at(80)).unwrap();
+        assert!(
+            matches!(result, AppendResult::Accepted { dy: 40, .. }),
+            "{:?}",
+            result
+        );
+        assert_eq!(stitcher.scroll_band(), Some(ScrollBand::full(240)));
+        assert_eq!(
+            stitcher.stitched().unwrap(),
+            RgbaImage::from_fn(160, 320, page)
+        );
+    }
+
+    #[test]
+    fn stitcher_emits_sticky_header_and_footer_once() {
+        let mut stitcher = Stitcher::new(test_config());
+        for offset in [0, 60, 120] {
+            let result = stitcher
+                .push(sticky_frame(160, 30, 200, 20, offset))
+                .unwrap();
+            assert!(matches!(result, AppendResult::Accepted { .. }));
+        }
+
+        let image = stitcher.stitched().unwrap();
+        let expected = sticky_frame(160, 30, 320, 20, 0);
+        assert_eq!(image, expected);
+        assert_eq!(stitcher.progress().captured_height_px, expected.height());
+
+        let preview = stitcher.preview(80).unwrap();
+        assert_eq!(preview.width(), 80);
+    }
+
+    #[test]
+    fn stitcher_reports_progress_per_used_frame() {
+        let mut stitcher = Stitcher::new(test_config());
+        for offset in [0, 60, 60, 120] {
+            stitcher
+                .push(sticky_frame(160, 30, 200, 20, offset))
+                .unwrap();
+        }
+
+        let mut calls = Vec::new();
+        let image = stitcher
+            .stitched_with_progress(|done, total| calls.push((done, total)))
+            .unwrap();
+        assert_eq!(calls, vec![(1, 3), (2, 3), (3, 3)]);
+        assert_eq!(image, stitcher.clone().finish().unwrap());
+    }
+
+    #[test]
+    fn sample_frame_difference_ignores_changes_outside_region() {
+        let frame1 = build_frame(200, 120, 0);
+        let mut frame2 = frame1.clone();
+        for y in 0..120 {
+            for x in 190..200 {
+                frame2.put_pixel(x, y, image::Rgba([255, 255, 255, 255]));
+            }
+        }
+        let narrow = MatchRegion {
+            start_percent: 0,
+            end_percent: 90,
+        };
+        let full = MatchRegion {
+            start_percent: 0,
+            end_percent: 100,
+        };
+        let vertical = ScrollDirection::Vertical;
+        assert_eq!(
+            sample_frame_difference(&frame1, &frame2, narrow, vertical),
+            0.0
+        );
+        assert!(sample_frame_difference(&frame1, &frame2, full, vertical) > 0.0);
+        assert!(
+            sample_frame_difference(&frame1, &frame2, narrow, ScrollDirection::Horizontal) > 0.0
+        );
+    }
+
+    #[test]
+    fn stitcher_ignores_scrollbar_outside_match_region() {
+        let mut stitcher = Stitcher::new(ScrollConfig {
+            match_region: MatchRegion {
+                start_percent: 0,
+                end_percent: 90,
+            },
+            ..test_config()
+        });
+        for (index, offset) in [0, 60, 120].into_iter().enumerate() {
+            let mut frame = sticky_frame(160, 30, 200, 20, offset);
+            // A scrollbar thumb in the right-most columns that moves every frame.
+            for y in 0..frame.height() {
+                for x in 150..160 {
+                    let lit = (y / 40) as usize == index;
+                    let value = if lit { 40 } else { 230 };
+                    frame.put_pixel(x, y, image::Rgba([value, value, value, 255]));
+                }
+            }
+            let result = stitcher.push(frame).unwrap();
+            assert!(matches!(result, AppendResult::Accepted { .. }));
+        }
+        assert_eq!(
+            stitcher.scroll_band(),
+            Some(ScrollBand {
+                top: 30,
+                bottom: 230
+            })
+        );
+        assert_eq!(stitcher.stitched().unwrap().height(), 30 + 320 + 20);
+    }
+
+    #[test]
+    fn stitcher_handles_horizontal_scrolling() {
+        let mut stitcher = Stitcher::new(ScrollConfig {
+            direction: ScrollDirection::Horizontal,
+            ..test_config()
+        });
+        let wide_frame =
+            |offset: u32| RgbaImage::from_fn(200, 120, |x, y| content_pixel(y * 2, x + offset));
+        for offset in [0, 70, 140] {
+            let result = stitcher.push(wide_frame(offset)).unwrap();
+            assert!(matches!(result, AppendResult::Accepted { .. }));
+        }
+
+        let image = stitcher.stitched().unwrap();
+        let expected = RgbaImage::from_fn(340, 120, |x, y| content_pixel(y * 2, x));
+        assert_eq!(image, expected);
+        assert_eq!(stitcher.progress().captured_height_px, 340);
+        assert_eq!(stitcher.preview(170).unwrap().dimensions(), (170, 60));
+        assert_eq!(
+            stitcher.preview(170).unwrap(),
+            downscale_to_width(&image, 170)
+        );
+    }
+
+    #[test]
+    fn stitcher_rejects_mismatched_dimensions() {
+        let mut stitcher = Stitcher::new(test_config());
+        stitcher.push(build_frame(120, 180, 0)).unwrap();
+        let err = stitcher.push(build_frame(120, 160, 40)).unwrap_err();
+        assert_eq!(err.kind, CaptureErrorKind::StitchFailed);
+    }
+
+    #[test]
+    fn stitcher_rejects_tiny_first_frame() {
+        let mut stitcher = Stitcher::new(test_config());
+        let err = stitcher.push(build_frame(10, 180, 0)).unwrap_err();
+        assert_eq!(err.kind, CaptureErrorKind::ValidationFailed);
+    }
+}
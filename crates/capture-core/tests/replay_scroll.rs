@@ -0,0 +1,159 @@
+//! End-to-end scroll sessions against the replay capture backend. Each test
+//! captures every frame through `ScreenCaptureProvider::capture_region`,
+//! feeds it to a `Stitcher` on the provider's clock and compares the result
+//! with the source page pixel for pixel.
+
+use std::sync::Arc;
+
+use capture_core::replay::{ReplayAnimation, ReplayProvider, ReplayScene, ReplayTimeline};
+use capture_core::stitch::Stitcher;
+use capture_core::{AppendResult, ScreenCaptureProvider, ScrollConfig, SkipReason, StopReason};
+use image::RgbaImage;
+
+const WIDTH: u32 = 160;
+const VIEWPORT: u32 = 240;
+
+fn run_session(provider: &ReplayProvider, config: ScrollConfig) -> (Stitcher, Vec<AppendResult>) {
+    let mut stitcher = Stitcher::with_clock(config, Arc::new(provider.clock()));
+    let mut results = Vec::new();
+    while !provider.is_finished() && stitcher.stop_reason().is_none() {
+        let frame = provider
+            .capture_region(provider.viewport())
+            .expect("replay frame");
+        results.push(stitcher.push(frame).expect("push frame"));
+    }
+    (stitcher, results)
+}
+
+fn assert_same_image(actual: &RgbaImage, expected: &RgbaImage) {
+    assert_eq!(actual.dimensions(), expected.dimensions());
+    if let Some((x, y, _)) = actual
+        .enumerate_pixels()
+        .find(|(x, y, pixel)| expected.get_pixel(*x, *y) != *pixel)
+    {
+        panic!("stitched image differs from the source at ({}, {})", x, y);
+    }
+}
+
+fn count(results: &[AppendResult], wanted: AppendResult) -> usize {
+    results.iter().filter(|result| **result == wanted).count()
+}
+
+#[test]
+fn uneven_scroll_reproduces_the_page() {
+    let provider = ReplayProvider::new(
+        ReplayScene::synthetic(WIDTH, 1400, VIEWPORT),
+        ReplayTimeline::starting_at(0).scroll_to(1160, 137, 200),
+    );
+
+    let (stitcher, results) = run_session(&provider, ScrollConfig::default());
+
+    assert!(results
+        .iter()
+        .all(|result| matches!(result, AppendResult::Accepted { .. })));
+    let stitched = stitcher.finish().unwrap();
+    assert_same_image(&stitched, &provider.scene().page);
+}
+
+#[test]
+fn sticky_header_and_footer_appear_once() {
+    let scene = ReplayScene::synthetic(WIDTH, 900, VIEWPORT)
+        .with_header(36)
+        .with_footer(24)
+        // A spinner in the toolbar, outside the matched columns.
+        .with_animation(ReplayAnimation {
+            x: 4,
+            y: 8,
+            width: 16,
+            height: 16,
+        });
+    let provider = ReplayProvider::new(
+        scene,
+        ReplayTimeline::starting_at(0).scroll_to(720, 110, 150),
+    );
+
+    let (stitcher, _) = run_session(&provider, ScrollConfig::default());
+
+    let band = stitcher.scroll_band().unwrap();
+    assert_eq!((band.top, band.bottom), (36, VIEWPORT - 24));
+    let stitched = stitcher.finish().unwrap();
+    assert_eq!(stitched.height(), 36 + 900 + 24);
+    assert_same_image(&stitched, &provider.expected_stitch());
+}
+
+#[test]
+fn jitter_bounces_and_fast_polling_still_match_the_page() {
+    let provider = ReplayProvider::new(
+        ReplayScene::synthetic(WIDTH, 1200, VIEWPORT)
+            .with_header(30)
+            .with_animation(ReplayAnimation {
+                x: WIDTH - 12,
+                y: 4,
+                width: 8,
+                height: 20,
+            }),
+        ReplayTimeline::starting_at(0)
+            .scroll_to(520, 80, 60)
+            .jitter(3)
+            // Overshoot bounce: one frame back, then onwards.
+            .then(60, 508)
+            .then(130, 590)
+            .hold(3, 120)
+            .scroll_to(990, 120, 130),
+    );
+
+    let (stitcher, results) = run_session(&provider, ScrollConfig::default());
+
+    assert!(count(&results, AppendResult::Skipped(SkipReason::Throttled)) > 0);
+    assert!(count(&results, AppendResult::Skipped(SkipReason::Duplicate)) > 0);
+    assert!(count(&results, AppendResult::Skipped(SkipReason::MatchFailed)) > 0);
+    assert_eq!(stitcher.stop_reason(), None);
+    let stitched = stitcher.finish().unwrap();
+    assert_same_image(&stitched, &provider.expected_stitch());
+}
+
+#[test]
+fn resting_at_the_end_stops_with_no_new_content() {
+    let provider = ReplayProvider::new(
+        ReplayScene::synthetic(WIDTH, 800, VIEWPORT),
+        ReplayTimeline::starting_at(0)
+            .scroll_to(560, 140, 200)
+            .hold(10, 200),
+    );
+    let config = ScrollConfig {
+        max_idle_frames: 4,
+        ..ScrollConfig::default()
+    };
+
+    let (stitcher, results) = run_session(&provider, config);
+
+    assert_eq!(
+        results.last(),
+        Some(&AppendResult::AutoStopped(StopReason::NoNewContent))
+    );
+    assert!(!provider.is_finished());
+    let stitched = stitcher.finish().unwrap();
+    assert_same_image(&stitched, &provider.expected_stitch());
+}
+
+#[test]
+fn idle_after_scrolling_times_out_on_the_replay_clock() {
+    let provider = ReplayProvider::new(
+        ReplayScene::synthetic(WIDTH, 800, VIEWPORT),
+        ReplayTimeline::starting_at(0)
+            .scroll_to(280, 140, 200)
+            .hold(1, 5_000),
+    );
+    let config = ScrollConfig {
+        idle_timeout_ms: 2_000,
+        ..ScrollConfig::default()
+    };
+
+    let (stitcher, results) = run_session(&provider, config);
+
+    assert_eq!(
+        results.last(),
+        Some(&AppendResult::AutoStopped(StopReason::Timeout))
+    );
+    assert_eq!(stitcher.progress().captured_height_px, VIEWPORT + 280);
+}
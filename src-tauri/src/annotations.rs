@@ -0,0 +1,845 @@
+//! Annotation rendering
+//!
+//! A serde model of the editor's `Annotation` union (`src/types/annotations.ts`)
+//! and a renderer that draws it the way `drawAnnotationOnCanvas` does, so
+//! exports can be annotated without a webview. Shapes are rasterized with
+//! tiny-skia; text uses the bundled DejaVu Sans in place of the editor's
+//! Arial, which cannot be shipped.
+
+use ab_glyph::{Font, FontRef, GlyphId, OutlineCurve};
+use capture_core::CaptureError;
+use image::{Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+use tiny_skia::{
+    Color as SkiaColor, ColorU8, FillRule, LineCap, Paint, Path, PathBuilder, Pixmap, Rect, Stroke,
+    StrokeDash, Transform,
+};
+
+use crate::utils::AppResult;
+
+static SANS: &[u8] = include_bytes!("../fonts/DejaVuSans.ttf");
+static SANS_BOLD: &[u8] = include_bytes!("../fonts/DejaVuSans-Bold.ttf");
+
+/// Stroke width the editor uses when an annotation has none.
+const DEFAULT_STROKE_WIDTH: f32 = 5.0;
+/// Blur radius the editor uses when a blur annotation has none.
+const DEFAULT_BLUR_RADIUS: f32 = 20.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// A hex color with an opacity from 0 to 100.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnnotationColor {
+    pub hex: String,
+    pub opacity: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnnotationBorder {
+    pub width: f32,
+    pub color: AnnotationColor,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HorizontalAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerticalAlign {
+    #[default]
+    Top,
+    Middle,
+    Bottom,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Alignment {
+    pub horizontal: HorizontalAlign,
+    pub vertical: VerticalAlign,
+}
+
+/// Fields every annotation has.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnnotationBase {
+    pub id: String,
+    pub x: f32,
+    pub y: f32,
+    pub fill: AnnotationColor,
+    pub border: AnnotationBorder,
+    #[serde(default)]
+    pub alignment: Alignment,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineType {
+    #[default]
+    Straight,
+    Curved,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArrowType {
+    Thin,
+    Thick,
+    None,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CircleAnnotation {
+    #[serde(flatten)]
+    pub base: AnnotationBase,
+    pub radius: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RectangleAnnotation {
+    #[serde(flatten)]
+    pub base: AnnotationBase,
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineAnnotation {
+    #[serde(flatten)]
+    pub base: AnnotationBase,
+    pub end_x: f32,
+    pub end_y: f32,
+    #[serde(default)]
+    pub line_type: LineType,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub control_points: Vec<Point>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArrowAnnotation {
+    #[serde(flatten)]
+    pub base: AnnotationBase,
+    pub end_x: f32,
+    pub end_y: f32,
+    #[serde(default)]
+    pub line_type: LineType,
+    pub arrow_type: ArrowType,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub control_points: Vec<Point>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextAnnotation {
+    #[serde(flatten)]
+    pub base: AnnotationBase,
+    pub text: String,
+    pub font_size: f32,
+    /// Kept for the editor. The backend draws every family in its bundled
+    /// DejaVu Sans, which stands in for the editor's Arial.
+    pub font_family: String,
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NumberAnnotation {
+    #[serde(flatten)]
+    pub base: AnnotationBase,
+    pub number: u32,
+    pub radius: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlurAnnotation {
+    #[serde(flatten)]
+    pub base: AnnotationBase,
+    pub width: f32,
+    pub height: f32,
+    pub blur_amount: f32,
+}
+
+/// An editor annotation, tagged by `type` as in the frontend.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Annotation {
+    Circle(CircleAnnotation),
+    Rectangle(RectangleAnnotation),
+    Line(LineAnnotation),
+    Arrow(ArrowAnnotation),
+    Text(TextAnnotation),
+    Number(NumberAnnotation),
+    Blur(BlurAnnotation),
+}
+
+/// Draw `annotations` onto `image` in order, shifted by `offset` pixels.
+pub fn draw_annotations(
+    image: &mut RgbaImage,
+    annotations: &[Annotation],
+    offset: (f32, f32),
+) -> AppResult<()> {
+    if annotations.is_empty() {
+        return Ok(());
+    }
+
+    let mut pixmap = to_pixmap(image)?;
+    let transform = Transform::from_translate(offset.0, offset.1);
+    for annotation in annotations {
+        match annotation {
+            Annotation::Circle(circle) => draw_circle(&mut pixmap, circle, transform),
+            Annotation::Rectangle(rect) => draw_rectangle(&mut pixmap, rect, transform),
+            Annotation::Line(line) => draw_line(&mut pixmap, line, transform),
+            Annotation::Arrow(arrow) => draw_arrow(&mut pixmap, arrow, transform),
+            Annotation::Text(text) => draw_text(&mut pixmap, text, transform)?,
+            Annotation::Number(number) => draw_number(&mut pixmap, number, transform)?,
+            Annotation::Blur(blur) => draw_blur(&mut pixmap, blur, offset),
+        }
+    }
+    from_pixmap(&pixmap, image);
+    Ok(())
+}
+
+fn to_pixmap(image: &RgbaImage) -> AppResult<Pixmap> {
+    let mut pixmap = Pixmap::new(image.width(), image.height()).ok_or_else(|| {
+        CaptureError::validation(format!(
+            "Cannot annotate a {}x{} image",
+            image.width(),
+            image.height()
+        ))
+    })?;
+    for (target, Rgba([r, g, b, a])) in pixmap.pixels_mut().iter_mut().zip(image.pixels()) {
+        *target = ColorU8::from_rgba(*r, *g, *b, *a).premultiply();
+    }
+    Ok(pixmap)
+}
+
+fn from_pixmap(pixmap: &Pixmap, image: &mut RgbaImage) {
+    for (target, pixel) in image.pixels_mut().zip(pixmap.pixels()) {
+        let color = pixel.demultiply();
+        *target = Rgba([color.red(), color.green(), color.blue(), color.alpha()]);
+    }
+}
+
+/// Parse `#rrggbb` like the editor's `hexToRgba`, which draws invalid
+/// colors in black.
+fn paint_for(color: &AnnotationColor) -> Paint<'static> {
+    let hex = color.hex.trim_start_matches('#');
+    let channel = |range: std::ops::Range<usize>| {
+        hex.get(range)
+            .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+    };
+    let (r, g, b) = match (hex.len(), channel(0..2), channel(2..4), channel(4..6)) {
+        (6, Some(r), Some(g), Some(b)) => (r, g, b),
+        _ => (0, 0, 0),
+    };
+    let alpha = (color.opacity / 100.0).clamp(0.0, 1.0);
+
+    let mut paint = Paint::default();
+    paint.set_color(
+        SkiaColor::from_rgba(
+            f32::from(r) / 255.0,
+            f32::from(g) / 255.0,
+            f32::from(b) / 255.0,
+            alpha,
+        )
+        .unwrap_or(SkiaColor::BLACK),
+    );
+    paint.anti_alias = true;
+    paint
+}
+
+fn stroke_width(base: &AnnotationBase) -> f32 {
+    if base.border.width > 0.0 {
+        base.border.width
+    } else {
+        DEFAULT_STROKE_WIDTH
+    }
+}
+
+fn draw_circle(pixmap: &mut Pixmap, circle: &CircleAnnotation, transform: Transform) {
+    let Some(path) = PathBuilder::from_circle(circle.base.x, circle.base.y, circle.radius) else {
+        return;
+    };
+    let stroke = Stroke {
+        width: stroke_width(&circle.base),
+        ..Stroke::default()
+    };
+    pixmap.stroke_path(
+        &path,
+        &paint_for(&circle.base.fill),
+        &stroke,
+        transform,
+        None,
+    );
+}
+
+fn draw_rectangle(pixmap: &mut Pixmap, rect: &RectangleAnnotation, transform: Transform) {
+    let Some(path) = rect_path(rect.base.x, rect.base.y, rect.width, rect.height) else {
+        return;
+    };
+    let stroke = Stroke {
+        width: stroke_width(&rect.base),
+        ..Stroke::default()
+    };
+    pixmap.stroke_path(&path, &paint_for(&rect.base.fill), &stroke, transform, None);
+}
+
+/// A rectangle path; negative sizes extend left or up, as in canvas.
+fn rect_path(x: f32, y: f32, width: f32, height: f32) -> Option<Path> {
+    let rect = Rect::from_ltrb(
+        x.min(x + width),
+        y.min(y + height),
+        x.max(x + width),
+        y.max(y + height),
+    )?;
+    Some(PathBuilder::from_rect(rect))
+}
+
+/// A straight line, or a quadratic curve through the first control point.
+fn line_path(start: Point, control: Option<Point>, end: Point) -> Option<Path> {
+    let mut builder = PathBuilder::new();
+    builder.move_to(start.x, start.y);
+    match control {
+        Some(control) => builder.quad_to(control.x, control.y, end.x, end.y),
+        None => builder.line_to(end.x, end.y),
+    }
+    builder.finish()
+}
+
+fn curve_control(line_type: LineType, control_points: &[Point]) -> Option<Point> {
+    match line_type {
+        LineType::Curved => control_points.first().copied(),
+        LineType::Straight => None,
+    }
+}
+
+fn round_stroke(width: f32) -> Stroke {
+    Stroke {
+        width,
+        line_cap: LineCap::Round,
+        ..Stroke::default()
+    }
+}
+
+fn draw_line(pixmap: &mut Pixmap, line: &LineAnnotation, transform: Transform) {
+    let start = Point {
+        x: line.base.x,
+        y: line.base.y,
+    };
+    let end = Point {
+        x: line.end_x,
+        y: line.end_y,
+    };
+    let control = curve_control(line.line_type, &line.control_points);
+    let Some(path) = line_path(start, control, end) else {
+        return;
+    };
+    let stroke = round_stroke(stroke_width(&line.base));
+    pixmap.stroke_path(&path, &paint_for(&line.base.fill), &stroke, transform, None);
+}
+
+fn draw_arrow(pixmap: &mut Pixmap, arrow: &ArrowAnnotation, transform: Transform) {
+    let width = stroke_width(&arrow.base);
+    let start = Point {
+        x: arrow.base.x,
+        y: arrow.base.y,
+    };
+    let end = Point {
+        x: arrow.end_x,
+        y: arrow.end_y,
+    };
+    let control = curve_control(arrow.line_type, &arrow.control_points);
+
+    // The head points along the last segment of the curve.
+    let from = control.unwrap_or(start);
+    let angle = (end.y - from.y).atan2(end.x - from.x);
+    let head_length = match arrow.arrow_type {
+        ArrowType::Thick => (width * 6.0).max(20.0),
+        ArrowType::Thin => (width * 3.0).max(12.0),
+        ArrowType::None => 0.0,
+    };
+    // Stop the shaft inside the head so its round cap does not poke out.
+    let shorten = head_length * 0.7;
+    let shaft_end = Point {
+        x: end.x - shorten * angle.cos(),
+        y: end.y - shorten * angle.sin(),
+    };
+
+    let paint = paint_for(&arrow.base.fill);
+    if let Some(path) = line_path(start, control, shaft_end) {
+        pixmap.stroke_path(&path, &paint, &round_stroke(width), transform, None);
+    }
+
+    if head_length <= 0.0 {
+        return;
+    }
+    let back =
+        |length: f32, angle: f32| (end.x - length * angle.cos(), end.y - length * angle.sin());
+    let mut head = PathBuilder::new();
+    head.move_to(end.x, end.y);
+    let (x, y) = back(head_length, angle - PI / 6.0);
+    head.line_to(x, y);
+    let (x, y) = back(head_length * 0.6, angle);
+    head.line_to(x, y);
+    let (x, y) = back(head_length, angle + PI / 6.0);
+    head.line_to(x, y);
+    head.close();
+    if let Some(path) = head.finish() {
+        pixmap.fill_path(&path, &paint, FillRule::Winding, transform, None);
+    }
+}
+
+fn load_font(bytes: &'static [u8]) -> AppResult<FontRef<'static>> {
+    FontRef::try_from_slice(bytes)
+        .map_err(|e| CaptureError::io(format!("Failed to load bundled font: {}", e)))
+}
+
+/// A line of text laid out in `font` at `size` pixels per em.
+struct TextRun<'a> {
+    font: &'a FontRef<'static>,
+    scale: f32,
+    glyphs: Vec<(GlyphId, f32)>,
+    width: f32,
+}
+
+impl<'a> TextRun<'a> {
+    fn new(font: &'a FontRef<'static>, text: &str, size: f32) -> Self {
+        let scale = size / font.units_per_em().unwrap_or(1000.0);
+        let mut glyphs = Vec::new();
+        let mut caret = 0.0;
+        let mut previous: Option<GlyphId> = None;
+        // Canvas draws line breaks and tabs as spaces.
+        for c in text
+            .chars()
+            .map(|c| if c.is_whitespace() { ' ' } else { c })
+        {
+            let id = font.glyph_id(c);
+            if let Some(previous) = previous {
+                caret += font.kern_unscaled(previous, id) * scale;
+            }
+            glyphs.push((id, caret));
+            caret += font.h_advance_unscaled(id) * scale;
+            previous = Some(id);
+        }
+        Self {
+            font,
+            scale,
+            glyphs,
+            width: caret,
+        }
+    }
+
+    /// Distance from the baseline to the middle of the em box, as used by
+    /// canvas `textBaseline = "middle"`.
+    fn middle_offset(&self) -> f32 {
+        (self.font.ascent_unscaled() + self.font.descent_unscaled()) * self.scale / 2.0
+    }
+
+    /// Outlines of the run with its baseline starting at `(x, y)`.
+    fn path(&self, x: f32, y: f32) -> Option<Path> {
+        let mut builder = PathBuilder::new();
+        for (id, caret) in &self.glyphs {
+            let Some(outline) = self.font.outline(*id) else {
+                continue;
+            };
+            let at = |point: ab_glyph::Point| {
+                (x + caret + point.x * self.scale, y - point.y * self.scale)
+            };
+            let mut last: Option<ab_glyph::Point> = None;
+            for curve in &outline.curves {
+                let start = match curve {
+                    OutlineCurve::Line(p0, _)
+                    | OutlineCurve::Quad(p0, _, _)
+                    | OutlineCurve::Cubic(p0, _, _, _) => *p0,
+                };
+                if last != Some(start) {
+                    if last.is_some() {
+                        builder.close();
+                    }
+                    let (sx, sy) = at(start);
+                    builder.move_to(sx, sy);
+                }
+                last = Some(match curve {
+                    OutlineCurve::Line(_, p1) => {
+                        let (px, py) = at(*p1);
+                        builder.line_to(px, py);
+                        *p1
+                    }
+                    OutlineCurve::Quad(_, p1, p2) => {
+                        let ((cx, cy), (px, py)) = (at(*p1), at(*p2));
+                        builder.quad_to(cx, cy, px, py);
+                        *p2
+                    }
+                    OutlineCurve::Cubic(_, p1, p2, p3) => {
+                        let ((ax, ay), (bx, by), (px, py)) = (at(*p1), at(*p2), at(*p3));
+                        builder.cubic_to(ax, ay, bx, by, px, py);
+                        *p3
+                    }
+                });
+            }
+            if last.is_some() {
+                builder.close();
+            }
+        }
+        builder.finish()
+    }
+}
+
+fn fill_text(
+    pixmap: &mut Pixmap,
+    run: &TextRun,
+    x: f32,
+    baseline: f32,
+    paint: &Paint,
+    transform: Transform,
+) {
+    if let Some(path) = run.path(x, baseline) {
+        pixmap.fill_path(&path, paint, FillRule::Winding, transform, None);
+    }
+}
+
+/// Text is drawn on one line with its top at `y`, ignoring the box size
+/// and alignment like the editor does, and always in the bundled sans.
+fn draw_text(pixmap: &mut Pixmap, text: &TextAnnotation, transform: Transform) -> AppResult<()> {
+    let font = load_font(SANS)?;
+    let run = TextRun::new(&font, &text.text, text.font_size);
+    let paint = paint_for(&text.base.fill);
+    let baseline = text.base.y + text.font_size;
+    fill_text(pixmap, &run, text.base.x, baseline, &paint, transform);
+    Ok(())
+}
+
+/// A filled badge with its number centered in bold white.
+fn draw_number(
+    pixmap: &mut Pixmap,
+    number: &NumberAnnotation,
+    transform: Transform,
+) -> AppResult<()> {
+    let (x, y) = (number.base.x, number.base.y);
+    let paint = paint_for(&number.base.fill);
+    if let Some(circle) = PathBuilder::from_circle(x, y, number.radius) {
+        pixmap.fill_path(&circle, &paint, FillRule::Winding, transform, None);
+        if number.base.border.width > 0.0 {
+            let stroke = Stroke {
+                width: number.base.border.width,
+                ..Stroke::default()
+            };
+            pixmap.stroke_path(&circle, &paint, &stroke, transform, None);
+        }
+    }
+
+    let font = load_font(SANS_BOLD)?;
+    let run = TextRun::new(&font, &number.number.to_string(), number.radius * 1.2);
+    let mut white = Paint::default();
+    white.set_color_rgba8(255, 255, 255, 255);
+    white.anti_alias = true;
+    let left = x - run.width / 2.0;
+    fill_text(
+        pixmap,
+        &run,
+        left,
+        y + run.middle_offset(),
+        &white,
+        transform,
+    );
+    Ok(())
+}
+
+/// Box blur the covered region, then outline it with the editor's dashed
+/// marker.
+fn draw_blur(pixmap: &mut Pixmap, blur: &BlurAnnotation, offset: (f32, f32)) {
+    let left = (blur.base.x + offset.0).floor().max(0.0) as u32;
+    let top = (blur.base.y + offset.1).floor().max(0.0) as u32;
+    let width = (blur.width.ceil().max(0.0) as u32).min(pixmap.width().saturating_sub(left));
+    let height = (blur.height.ceil().max(0.0) as u32).min(pixmap.height().saturating_sub(top));
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    let radius = if blur.blur_amount > 0.0 {
+        blur.blur_amount
+    } else {
+        DEFAULT_BLUR_RADIUS
+    };
+    box_blur_region(pixmap, left, top, width, height, radius.round() as i64);
+
+    let Some(outline) = rect_path(left as f32, top as f32, width as f32, height as f32) else {
+        return;
+    };
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(100, 100, 255, 77);
+    paint.anti_alias = true;
+    let stroke = Stroke {
+        width: 2.0,
+        dash: StrokeDash::new(vec![5.0, 5.0], 0.0),
+        ..Stroke::default()
+    };
+    pixmap.stroke_path(&outline, &paint, &stroke, Transform::identity(), None);
+}
+
+/// Horizontal then vertical box blur of `radius` over a region, clamping
+/// the window to the region's edges like the editor's `applyBoxBlur`.
+/// Premultiplied values are blurred, so transparent pixels do not bleed
+/// their color.
+fn box_blur_region(pixmap: &mut Pixmap, left: u32, top: u32, width: u32, height: u32, radius: i64) {
+    let stride = pixmap.width() as usize;
+    let (left, top, width, height) = (left as usize, top as usize, width as usize, height as usize);
+    let data = pixmap.data_mut();
+    let mut region: Vec<[u8; 4]> = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (y, x)))
+        .map(|(y, x)| {
+            let at = ((top + y) * stride + left + x) * 4;
+            [data[at], data[at + 1], data[at + 2], data[at + 3]]
+        })
+        .collect();
+
+    let pass = |source: &[[u8; 4]], horizontal: bool| -> Vec<[u8; 4]> {
+        let count = (2 * radius + 1) as f32;
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| (y, x)))
+            .map(|(y, x)| {
+                let mut sum = [0u32; 4];
+                for k in -radius..=radius {
+                    let (sx, sy) = if horizontal {
+                        ((x as i64 + k).clamp(0, width as i64 - 1) as usize, y)
+                    } else {
+                        (x, (y as i64 + k).clamp(0, height as i64 - 1) as usize)
+                    };
+                    let pixel = source[sy * width + sx];
+                    for (acc, channel) in sum.iter_mut().zip(pixel) {
+                        *acc += u32::from(channel);
+                    }
+                }
+                sum.map(|total| (total as f32 / count).round() as u8)
+            })
+            .collect()
+    };
+    region = pass(&region, true);
+    region = pass(&region, false);
+
+    for (i, pixel) in region.iter().enumerate() {
+        let (y, x) = (i / width, i % width);
+        let at = ((top + y) * stride + left + x) * 4;
+        // Averages of premultiplied pixels stay premultiplied.
+        data[at..at + 4].copy_from_slice(pixel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canvas() -> RgbaImage {
+        RgbaImage::from_pixel(120, 80, Rgba([255, 255, 255, 255]))
+    }
+
+    fn annotation(value: serde_json::Value) -> Annotation {
+        let mut base = serde_json::json!({
+            "id": "a1",
+            "x": 10.0,
+            "y": 10.0,
+            "fill": { "hex": "#ff0000", "opacity": 100 },
+            "border": { "width": 4, "color": { "hex": "#ff0000", "opacity": 100 } },
+            "alignment": { "horizontal": "left", "vertical": "top" },
+        });
+        base.as_object_mut()
+            .unwrap()
+            .extend(value.as_object().unwrap().clone());
+        serde_json::from_value(base).unwrap()
+    }
+
+    fn is_red(pixel: &Rgba<u8>) -> bool {
+        pixel[0] > 200 && pixel[1] < 60 && pixel[2] < 60
+    }
+
+    #[test]
+    fn test_editor_annotations_deserialize() {
+        let arrow = annotation(serde_json::json!({
+            "type": "arrow",
+            "endX": 80.0,
+            "endY": 40.0,
+            "lineType": "curved",
+            "arrowType": "thick",
+            "controlPoints": [{ "x": 40.0, "y": 0.0 }],
+        }));
+        let Annotation::Arrow(arrow) = &arrow else {
+            panic!("expected an arrow, got {:?}", arrow);
+        };
+        assert_eq!(arrow.arrow_type, ArrowType::Thick);
+        assert_eq!(arrow.control_points, vec![Point { x: 40.0, y: 0.0 }]);
+
+        let text = annotation(serde_json::json!({
+            "type": "text",
+            "text": "Hi",
+            "fontSize": 16,
+            "fontFamily": "Arial",
+            "width": 100,
+            "height": 20,
+        }));
+        let value = serde_json::to_value(&text).unwrap();
+        assert_eq!(value["type"], "text");
+        assert_eq!(value["fontSize"], 16.0);
+        assert_eq!(serde_json::from_value::<Annotation>(value).unwrap(), text);
+    }
+
+    #[test]
+    fn test_rectangle_is_stroked_not_filled() {
+        let mut img = canvas();
+        let rect =
+            annotation(serde_json::json!({ "type": "rectangle", "width": 60.0, "height": 40.0 }));
+        draw_annotations(&mut img, &[rect], (0.0, 0.0)).unwrap();
+
+        assert!(is_red(img.get_pixel(10, 30)));
+        assert!(is_red(img.get_pixel(40, 50)));
+        assert_eq!(img.get_pixel(40, 30), &Rgba([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn test_opacity_blends_with_the_image() {
+        let mut img = canvas();
+        let mut rect =
+            annotation(serde_json::json!({ "type": "rectangle", "width": 60.0, "height": 40.0 }));
+        if let Annotation::Rectangle(rect) = &mut rect {
+            rect.base.fill.opacity = 50.0;
+        }
+        draw_annotations(&mut img, &[rect], (0.0, 0.0)).unwrap();
+        let pixel = img.get_pixel(10, 30);
+        assert_eq!(pixel[0], 255);
+        assert!((i32::from(pixel[1]) - 128).abs() <= 2, "{:?}", pixel);
+    }
+
+    #[test]
+    fn test_arrow_head_reaches_the_end_point() {
+        let mut img = canvas();
+        let arrow = annotation(serde_json::json!({
+            "type": "arrow",
+            "x": 10.0,
+            "y": 40.0,
+            "endX": 100.0,
+            "endY": 40.0,
+            "lineType": "straight",
+            "arrowType": "thick",
+        }));
+        draw_annotations(&mut img, &[arrow], (0.0, 0.0)).unwrap();
+
+        assert!(is_red(img.get_pixel(98, 40)));
+        // The head is 24px long and 30 degrees either side of the shaft.
+        assert!(is_red(img.get_pixel(82, 48)));
+        assert!(is_red(img.get_pixel(82, 32)));
+        assert!(!is_red(img.get_pixel(82, 55)));
+        assert!(is_red(img.get_pixel(30, 40)));
+    }
+
+    #[test]
+    fn test_curved_lines_bend_towards_the_control_point() {
+        let mut img = canvas();
+        let line = annotation(serde_json::json!({
+            "type": "line",
+            "x": 10.0,
+            "y": 70.0,
+            "endX": 110.0,
+            "endY": 70.0,
+            "lineType": "curved",
+            "controlPoints": [{ "x": 60.0, "y": 10.0 }],
+        }));
+        draw_annotations(&mut img, &[line], (0.0, 0.0)).unwrap();
+
+        // A quadratic curve peaks halfway to its control point.
+        assert!(is_red(img.get_pixel(60, 40)));
+        assert!(!is_red(img.get_pixel(60, 70)));
+    }
+
+    #[test]
+    fn test_number_badge_has_white_digits_on_the_fill() {
+        let mut img = canvas();
+        let badge = annotation(serde_json::json!({
+            "type": "number",
+            "x": 60.0,
+            "y": 40.0,
+            "number": 1,
+            "radius": 20.0,
+        }));
+        draw_annotations(&mut img, &[badge], (0.0, 0.0)).unwrap();
+
+        assert!(is_red(img.get_pixel(45, 40)));
+        assert!(is_red(img.get_pixel(75, 40)));
+        let white = (30..50)
+            .flat_map(|y| (50..70).map(move |x| (x, y)))
+            .filter(|(x, y)| img.get_pixel(*x, *y).0 == [255, 255, 255, 255])
+            .count();
+        assert!(white > 20, "{} white pixels", white);
+    }
+
+    #[test]
+    fn test_text_is_drawn_below_its_origin() {
+        let mut img = canvas();
+        let text = annotation(serde_json::json!({
+            "type": "text",
+            "text": "Hello",
+            "fontSize": 20,
+            "fontFamily": "Arial",
+            "width": 100,
+            "height": 24,
+        }));
+        draw_annotations(&mut img, &[text], (0.0, 0.0)).unwrap();
+
+        let inked: Vec<(u32, u32)> = img
+            .enumerate_pixels()
+            .filter(|(_, _, pixel)| is_red(pixel))
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        assert!(!inked.is_empty());
+        assert!(inked.iter().all(|(x, y)| *x >= 10 && *y >= 10 && *y <= 30));
+        // "Hello" at 20px is roughly 50px wide.
+        let right = inked.iter().map(|(x, _)| *x).max().unwrap();
+        assert!((50..75).contains(&right), "{}", right);
+    }
+
+    #[test]
+    fn test_blur_smooths_only_its_region() {
+        let mut img = RgbaImage::from_fn(60, 40, |x, _| {
+            if x % 2 == 0 {
+                Rgba([0, 0, 0, 255])
+            } else {
+                Rgba([255, 255, 255, 255])
+            }
+        });
+        let blur = annotation(serde_json::json!({
+            "type": "blur",
+            "x": 0.0,
+            "y": 0.0,
+            "width": 30.0,
+            "height": 40.0,
+            "blurAmount": 4,
+        }));
+        draw_annotations(&mut img, &[blur], (0.0, 0.0)).unwrap();
+
+        let inside = img.get_pixel(15, 20);
+        assert!((100..160).contains(&inside[0]), "{:?}", inside);
+        assert_eq!(img.get_pixel(40, 20), &Rgba([0, 0, 0, 255]));
+        assert_eq!(img.get_pixel(41, 20), &Rgba([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn test_offset_moves_annotations() {
+        let mut img = canvas();
+        let rect =
+            annotation(serde_json::json!({ "type": "rectangle", "width": 20.0, "height": 20.0 }));
+        draw_annotations(&mut img, &[rect], (30.0, 20.0)).unwrap();
+
+        assert!(!is_red(img.get_pixel(10, 20)));
+        assert!(is_red(img.get_pixel(40, 40)));
+    }
+}
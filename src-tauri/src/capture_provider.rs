@@ -0,0 +1,884 @@
+//! Capture backends and runtime backend selection.
+//!
+//! Every capture command goes through [`CaptureProviders`], which checks
+//! Screen Recording permission once, runs the capture as a job on the
+//! blocking pool and tries each backend that supports the operation in
+//! preference order. Permission, cancellation and busy errors stop the
+//! search; anything else falls through to the next backend.
+
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex, TryLockError};
+use std::time::Duration;
+
+use capture_core::provider::{CaptureOperation, InteractiveMode, MonitorInfo, WindowInfo};
+use capture_core::{
+    CaptureError, CaptureErrorKind, CaptureRect, PhysicalRect, ScreenCaptureProvider,
+};
+use image::RgbaImage;
+use tauri::AppHandle;
+use xcap::{Monitor, Window};
+
+use crate::jobs::{current_job_cancelled, JobContext, JobKind, Jobs};
+use crate::utils::{generate_filename, AppResult};
+
+/// Serializes `screencapture` invocations; the CLI refuses to run twice.
+static SCREENCAPTURE_LOCK: Mutex<()> = Mutex::new(());
+/// How often a running `screencapture` is checked for exit or cancellation.
+const SCREENCAPTURE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+pub fn is_permission_error(message: &str) -> bool {
+    let lower = message.to_lowercase();
+    lower.contains("permission")
+        || lower.contains("denied")
+        || lower.contains("not authorized")
+        || lower.contains("could not create image from display")
+}
+
+pub fn permission_required_error() -> CaptureError {
+    CaptureError::new(
+        CaptureErrorKind::Permission,
+        "Screen Recording permission required. Please grant permission in System Settings > Privacy & Security > Screen Recording.",
+    )
+}
+
+/// Check screen recording permission using CoreGraphics API.
+/// Unlike the old screencapture CLI approach, this checks the TCC database
+/// directly and respects permission changes without requiring app restart.
+fn check_and_activate_permission() -> Result<(), CaptureError> {
+    #[cfg(target_os = "macos")]
+    {
+        use objc2_core_graphics::{CGPreflightScreenCaptureAccess, CGRequestScreenCaptureAccess};
+
+        if !CGPreflightScreenCaptureAccess() {
+            // Trigger the system permission dialog (non-blocking)
+            let _ = CGRequestScreenCaptureAccess();
+
+            // Re-check after the request
+            if !CGPreflightScreenCaptureAccess() {
+                return Err(permission_required_error());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if !linux_display_configured() {
+            return Err(CaptureError::new(
+                CaptureErrorKind::Unsupported,
+                "No X11 display available for screen capture. Is DISPLAY set?",
+            )
+            .with_detail("backend", "xcap"));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    Ok(())
+}
+
+/// Whether an X11 display is configured through `DISPLAY` (Xvfb works).
+/// Cheap enough to check before every capture; whether it has monitors is
+/// left to the capture job.
+#[cfg(target_os = "linux")]
+pub fn linux_display_configured() -> bool {
+    std::env::var_os("DISPLAY").is_some_and(|value| !value.is_empty())
+}
+
+/// Whether xcap can reach an X11 display with at least one monitor.
+/// Enumerates monitors, so call it off the async runtime.
+#[cfg(target_os = "linux")]
+pub fn linux_display_available() -> bool {
+    linux_display_configured() && Monitor::all().is_ok_and(|monitors| !monitors.is_empty())
+}
+
+/// Replace backend permission messages with the settings hint shown to
+/// users, keeping the original as the `cause` detail.
+pub fn to_ipc_error(error: CaptureError) -> CaptureError {
+    if error.kind != CaptureErrorKind::Permission {
+        return error;
+    }
+    let mut mapped = permission_required_error();
+    mapped.details = error.details;
+    mapped.with_detail("cause", error.message)
+}
+
+/// Wrap a backend failure, recognizing permission denials.
+fn backend_error(context: &str, error: impl Display) -> CaptureError {
+    let message = format!("{}: {}", context, error);
+    if is_permission_error(&message) {
+        CaptureError::new(CaptureErrorKind::Permission, message)
+    } else {
+        CaptureError::new(CaptureErrorKind::CaptureFailed, message)
+    }
+}
+
+/// Decode a backend's temporary capture file and remove it.
+fn take_image_file(path: &Path) -> Result<RgbaImage, CaptureError> {
+    let image = image::open(path).map_err(|e| backend_error("Failed to read capture", e));
+    let _ = std::fs::remove_file(path);
+    Ok(image?.to_rgba8())
+}
+
+fn xcap_monitor_info(monitor: &Monitor) -> Result<MonitorInfo, CaptureError> {
+    let id = monitor
+        .id()
+        .map_err(|e| backend_error("Failed to get monitor id", e))?;
+    let x = monitor
+        .x()
+        .map_err(|e| backend_error("Failed to get monitor x", e))?;
+    let y = monitor
+        .y()
+        .map_err(|e| backend_error("Failed to get monitor y", e))?;
+    let width = monitor
+        .width()
+        .map_err(|e| backend_error("Failed to get monitor width", e))?;
+    let height = monitor
+        .height()
+        .map_err(|e| backend_error("Failed to get monitor height", e))?;
+    let scale_factor = monitor
+        .scale_factor()
+        .map_err(|e| backend_error("Failed to get monitor scale factor", e))?;
+
+    let bounds = PhysicalRect::new(x, y, width, height).to_logical(scale_factor);
+    Ok(MonitorInfo {
+        id,
+        name: monitor.name().unwrap_or_default(),
+        x: bounds.x,
+        y: bounds.y,
+        width: bounds.width,
+        height: bounds.height,
+        scale_factor,
+        is_primary: monitor.is_primary().unwrap_or(false),
+    })
+}
+
+fn xcap_monitors() -> Result<Vec<MonitorInfo>, CaptureError> {
+    let monitors = Monitor::all().map_err(|e| backend_error("Failed to query monitors", e))?;
+    monitors.iter().map(xcap_monitor_info).collect()
+}
+
+fn find_xcap_monitor(id: u32) -> Result<Monitor, CaptureError> {
+    Monitor::all()
+        .map_err(|e| backend_error("Failed to query monitors", e))?
+        .into_iter()
+        .find(|monitor| monitor.id().ok() == Some(id))
+        .ok_or_else(|| {
+            CaptureError::new(
+                CaptureErrorKind::CaptureFailed,
+                format!("Monitor {} is not available", id),
+            )
+        })
+}
+
+fn find_xcap_window(id: u32) -> Result<Window, CaptureError> {
+    Window::all()
+        .map_err(|e| backend_error("Failed to list windows", e))?
+        .into_iter()
+        .find(|window| window.id().ok() == Some(id))
+        .ok_or_else(|| {
+            CaptureError::new(
+                CaptureErrorKind::CaptureFailed,
+                format!("Window {} is not available", id),
+            )
+        })
+}
+
+/// Cross-platform capture through the `xcap` crate.
+pub struct XcapProvider;
+
+impl ScreenCaptureProvider for XcapProvider {
+    fn name(&self) -> &'static str {
+        "xcap"
+    }
+
+    fn supports(&self, operation: CaptureOperation) -> bool {
+        !matches!(operation, CaptureOperation::Interactive(_))
+    }
+
+    fn monitors(&self) -> Result<Vec<MonitorInfo>, CaptureError> {
+        xcap_monitors()
+    }
+
+    fn windows(&self) -> Result<Vec<WindowInfo>, CaptureError> {
+        let windows = Window::all().map_err(|e| backend_error("Failed to list windows", e))?;
+        let mut list = Vec::with_capacity(windows.len());
+
+        for window in windows {
+            let (Ok(x), Ok(y), Ok(width), Ok(height)) =
+                (window.x(), window.y(), window.width(), window.height())
+            else {
+                continue;
+            };
+            let monitor_scale = window
+                .current_monitor()
+                .ok()
+                .and_then(|monitor| monitor.scale_factor().ok())
+                .filter(|value| *value > 0.0)
+                .unwrap_or(1.0);
+            let bounds = PhysicalRect::new(x, y, width, height).to_logical(monitor_scale);
+
+            list.push(WindowInfo {
+                id: window.id().unwrap_or_default(),
+                pid: window.pid().unwrap_or_default(),
+                app_name: window.app_name().unwrap_or_default(),
+                title: window.title().unwrap_or_default(),
+                x: bounds.x,
+                y: bounds.y,
+                width: bounds.width,
+                height: bounds.height,
+                z: window.z().unwrap_or_default(),
+                is_minimized: window.is_minimized().unwrap_or(false),
+            });
+        }
+
+        Ok(list)
+    }
+
+    fn capture_monitor(&self, id: u32) -> Result<RgbaImage, CaptureError> {
+        find_xcap_monitor(id)?
+            .capture_image()
+            .map_err(|e| backend_error(&format!("Failed to capture monitor {}", id), e))
+    }
+
+    fn capture_window(&self, id: u32) -> Result<RgbaImage, CaptureError> {
+        find_xcap_window(id)?
+            .capture_image()
+            .map_err(|e| backend_error(&format!("Failed to capture window {}", id), e))
+    }
+}
+
+/// macOS `screencapture` command line tool. The only backend offering the
+/// system's interactive region and window pickers.
+pub struct ScreencaptureCliProvider;
+
+impl ScreencaptureCliProvider {
+    /// Check if screencapture is already running
+    fn is_running() -> bool {
+        let output = Command::new("pgrep")
+            .arg("-x")
+            .arg("screencapture")
+            .output();
+
+        match output {
+            Ok(o) => o.status.success(),
+            Err(_) => false,
+        }
+    }
+
+    /// Run `screencapture` with `args` into a temporary file. Interactive
+    /// runs that exit without an error or a file were dismissed by the user.
+    /// Fails with `busy` rather than waiting when a capture is in flight, and
+    /// kills the CLI if the job running it is cancelled.
+    fn run(&self, args: &[&str], interactive: bool) -> Result<RgbaImage, CaptureError> {
+        let busy = || {
+            CaptureError::new(
+                CaptureErrorKind::Busy,
+                "Another screenshot capture is already in progress",
+            )
+        };
+        let _lock = match SCREENCAPTURE_LOCK.try_lock() {
+            Ok(lock) => lock,
+            Err(TryLockError::WouldBlock) => return Err(busy()),
+            Err(TryLockError::Poisoned(e)) => {
+                return Err(CaptureError::command(format!(
+                    "Failed to acquire lock: {}",
+                    e
+                )))
+            }
+        };
+
+        if Self::is_running() {
+            return Err(busy());
+        }
+
+        let filename = generate_filename("screencapture", "png")?;
+        let path = std::env::temp_dir().join(filename);
+
+        let mut child = Command::new("screencapture")
+            .args(args)
+            .arg(&path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                CaptureError::new(
+                    CaptureErrorKind::CommandFailed,
+                    format!("Failed to run screencapture: {}", e),
+                )
+            })?;
+
+        let wait_error = |e: std::io::Error| {
+            CaptureError::new(
+                CaptureErrorKind::CommandFailed,
+                format!("Failed to wait for screencapture: {}", e),
+            )
+        };
+        while child.try_wait().map_err(wait_error)?.is_none() {
+            if current_job_cancelled() {
+                let _ = child.kill();
+                let _ = child.wait();
+                let _ = std::fs::remove_file(&path);
+                return Err(CaptureError::new(
+                    CaptureErrorKind::Cancelled,
+                    "Screenshot was cancelled",
+                ));
+            }
+            std::thread::sleep(SCREENCAPTURE_POLL_INTERVAL);
+        }
+        let output = child.wait_with_output().map_err(wait_error)?;
+
+        let cancelled = || {
+            CaptureError::new(
+                CaptureErrorKind::Cancelled,
+                "Screenshot was cancelled or failed",
+            )
+        };
+
+        if !output.status.success() {
+            let _ = std::fs::remove_file(&path);
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            if is_permission_error(&stderr) {
+                return Err(CaptureError::new(CaptureErrorKind::Permission, stderr));
+            }
+            if stderr.is_empty() {
+                return Err(if interactive {
+                    cancelled()
+                } else {
+                    CaptureError::new(CaptureErrorKind::CommandFailed, "Screenshot failed")
+                });
+            }
+            return Err(CaptureError::new(
+                CaptureErrorKind::CommandFailed,
+                format!("Screenshot command failed: {}", stderr),
+            ));
+        }
+
+        if !path.exists() {
+            return Err(if interactive {
+                cancelled()
+            } else {
+                CaptureError::new(CaptureErrorKind::CommandFailed, "Screenshot failed")
+            });
+        }
+
+        take_image_file(&path)
+    }
+}
+
+impl ScreenCaptureProvider for ScreencaptureCliProvider {
+    fn name(&self) -> &'static str {
+        "screencapture"
+    }
+
+    fn supports(&self, operation: CaptureOperation) -> bool {
+        matches!(
+            operation,
+            CaptureOperation::Window
+                | CaptureOperation::Region
+                | CaptureOperation::FullScreen
+                | CaptureOperation::Interactive(_)
+        )
+    }
+
+    fn captures_via_file(&self) -> bool {
+        true
+    }
+
+    fn capture_window(&self, id: u32) -> Result<RgbaImage, CaptureError> {
+        self.run(&["-x", "-o", &format!("-l{}", id)], false)
+    }
+
+    fn capture_region(&self, rect: CaptureRect) -> Result<RgbaImage, CaptureError> {
+        let rect_arg = format!("{},{},{},{}", rect.x, rect.y, rect.width, rect.height);
+        self.run(&["-x", "-R", &rect_arg], false)
+    }
+
+    fn capture_full_screen(&self) -> Result<RgbaImage, CaptureError> {
+        self.run(&["-x"], false)
+    }
+
+    fn capture_interactive(&self, mode: InteractiveMode) -> Result<RgbaImage, CaptureError> {
+        let selection = match mode {
+            InteractiveMode::Region => "-i",
+            InteractiveMode::Window => "-w",
+        };
+        self.run(&[selection, "-x"], true)
+    }
+}
+
+/// `tauri-plugin-screenshots`, which uses ScreenCaptureKit on recent macOS.
+/// Monitor geometry comes from xcap; the plugin only reports ids and names.
+pub struct ScreenshotsPluginProvider {
+    app_handle: AppHandle,
+}
+
+impl ScreenshotsPluginProvider {
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self { app_handle }
+    }
+}
+
+impl ScreenCaptureProvider for ScreenshotsPluginProvider {
+    fn name(&self) -> &'static str {
+        "screenshots-plugin"
+    }
+
+    fn supports(&self, operation: CaptureOperation) -> bool {
+        !matches!(
+            operation,
+            CaptureOperation::ListWindows | CaptureOperation::Interactive(_)
+        )
+    }
+
+    /// The plugin only hands back the path of a PNG it saved.
+    fn captures_via_file(&self) -> bool {
+        true
+    }
+
+    fn monitors(&self) -> Result<Vec<MonitorInfo>, CaptureError> {
+        xcap_monitors()
+    }
+
+    fn capture_monitor(&self, id: u32) -> Result<RgbaImage, CaptureError> {
+        let path: PathBuf = tauri::async_runtime::block_on(
+            tauri_plugin_screenshots::get_monitor_screenshot(self.app_handle.clone(), id),
+        )
+        .map_err(|e| backend_error("Failed to capture monitor image", e))?;
+        take_image_file(&path)
+    }
+
+    fn capture_window(&self, id: u32) -> Result<RgbaImage, CaptureError> {
+        let path: PathBuf = tauri::async_runtime::block_on(
+            tauri_plugin_screenshots::get_window_screenshot(self.app_handle.clone(), id),
+        )
+        .map_err(|e| backend_error("Failed to capture window image", e))?;
+        take_image_file(&path)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureBackend {
+    ScreenshotsPlugin,
+    /// The `screencapture` tool, only preferred on macOS.
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    ScreencaptureCli,
+    Xcap,
+}
+
+/// Backends to try, most preferred first. ScreenCaptureKit (through the
+/// plugin) is preferred on macOS 14+, where `screencapture` is slower and
+/// shows a capture indicator.
+pub fn preferred_backends() -> Vec<CaptureBackend> {
+    #[cfg(target_os = "macos")]
+    {
+        if macos_major_version().is_some_and(|major| major >= 14) {
+            return vec![
+                CaptureBackend::ScreenshotsPlugin,
+                CaptureBackend::ScreencaptureCli,
+                CaptureBackend::Xcap,
+            ];
+        }
+        vec![
+            CaptureBackend::ScreencaptureCli,
+            CaptureBackend::Xcap,
+            CaptureBackend::ScreenshotsPlugin,
+        ]
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    vec![CaptureBackend::Xcap, CaptureBackend::ScreenshotsPlugin]
+}
+
+#[cfg(target_os = "macos")]
+fn macos_major_version() -> Option<u32> {
+    let output = Command::new("sw_vers")
+        .arg("-productVersion")
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let raw = String::from_utf8_lossy(&output.stdout);
+    let mut parts = raw.trim().split('.');
+    parts.next()?.parse::<u32>().ok()
+}
+
+/// The capture backends available at runtime, in preference order. Managed
+/// as Tauri state.
+#[derive(Clone)]
+pub struct CaptureProviders {
+    providers: Vec<Arc<dyn ScreenCaptureProvider>>,
+    jobs: Jobs,
+}
+
+impl CaptureProviders {
+    pub fn new(providers: Vec<Arc<dyn ScreenCaptureProvider>>) -> Self {
+        Self {
+            providers,
+            jobs: Jobs::default(),
+        }
+    }
+
+    /// Run captures as jobs of `jobs`, so they report progress and can be
+    /// cancelled through it.
+    pub fn with_jobs(mut self, jobs: Jobs) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
+    /// The same backends with those capturing in memory first, for callers
+    /// that capture repeatedly, such as scroll polling. Backends that go
+    /// through a temp file are kept as a fallback.
+    pub fn in_memory_first(&self) -> Self {
+        let mut providers = self.providers.clone();
+        providers.sort_by_key(|provider| provider.captures_via_file());
+        Self {
+            providers,
+            jobs: self.jobs.clone(),
+        }
+    }
+
+    pub fn detect(app_handle: &AppHandle) -> Self {
+        let providers = preferred_backends()
+            .into_iter()
+            .map(|backend| -> Arc<dyn ScreenCaptureProvider> {
+                match backend {
+                    CaptureBackend::ScreenshotsPlugin => {
+                        Arc::new(ScreenshotsPluginProvider::new(app_handle.clone()))
+                    }
+                    CaptureBackend::ScreencaptureCli => Arc::new(ScreencaptureCliProvider),
+                    CaptureBackend::Xcap => Arc::new(XcapProvider),
+                }
+            })
+            .collect();
+        Self::new(providers)
+    }
+
+    /// Run `capture` on each backend supporting `operation` until one
+    /// succeeds. Blocks; see [`CaptureProviders::run`]. Backends that failed
+    /// before the returned error are listed in its `attempts` detail.
+    pub fn try_each<T>(
+        &self,
+        operation: CaptureOperation,
+        capture: impl Fn(&dyn ScreenCaptureProvider) -> Result<T, CaptureError>,
+    ) -> Result<T, CaptureError> {
+        let mut attempts = Vec::new();
+        for provider in self.providers.iter().filter(|p| p.supports(operation)) {
+            if current_job_cancelled() {
+                return Err(CaptureError::new(
+                    CaptureErrorKind::Cancelled,
+                    "Capture was cancelled",
+                ));
+            }
+            match capture(provider.as_ref()).map_err(|error| tag_backend(error, provider.name())) {
+                Ok(value) => return Ok(value),
+                Err(error)
+                    if matches!(
+                        error.kind,
+                        CaptureErrorKind::Permission
+                            | CaptureErrorKind::Cancelled
+                            | CaptureErrorKind::Busy
+                    ) =>
+                {
+                    return Err(with_attempts(error, &attempts))
+                }
+                Err(error) => attempts.push(error),
+            }
+        }
+
+        Err(match attempts.pop() {
+            Some(error) => with_attempts(error, &attempts),
+            None => CaptureError::new(
+                CaptureErrorKind::Unsupported,
+                format!("No capture backend supports {:?}", operation),
+            ),
+        })
+    }
+
+    /// [`CaptureProviders::run_job`] under a generated job id, returning
+    /// the capture as it is.
+    pub async fn run<T, F>(&self, operation: CaptureOperation, capture: F) -> AppResult<T>
+    where
+        T: Send + 'static,
+        F: Fn(&dyn ScreenCaptureProvider) -> Result<T, CaptureError> + Send + 'static,
+    {
+        self.run_job(None, operation, capture, |value, _| Ok(value))
+            .await
+    }
+
+    /// Check permission, then run [`CaptureProviders::try_each`] as capture
+    /// job `job_id` on the blocking pool. `then` post-processes the capture
+    /// (saving, OCR) in the same job, after the backend search, so its
+    /// errors never make another backend capture again. Errors are mapped
+    /// for IPC.
+    pub async fn run_job<T, U, F, G>(
+        &self,
+        job_id: Option<String>,
+        operation: CaptureOperation,
+        capture: F,
+        then: G,
+    ) -> AppResult<U>
+    where
+        U: Send + 'static,
+        F: Fn(&dyn ScreenCaptureProvider) -> Result<T, CaptureError> + Send + 'static,
+        G: FnOnce(T, &JobContext) -> AppResult<U> + Send + 'static,
+    {
+        check_and_activate_permission()?;
+
+        let providers = self.clone();
+        self.jobs
+            .run(job_id, JobKind::Capture, move |job| {
+                let value = providers.try_each(operation, capture)?;
+                job.check_cancelled()?;
+                then(value, job)
+            })
+            .await
+            .map_err(to_ipc_error)
+    }
+}
+
+/// List the backends that failed before `error` as `backend: message`.
+fn with_attempts(error: CaptureError, attempts: &[CaptureError]) -> CaptureError {
+    if attempts.is_empty() {
+        return error;
+    }
+    let attempts = attempts
+        .iter()
+        .map(|attempt| {
+            format!(
+                "{}: {}",
+                attempt.detail("backend").unwrap_or("unknown"),
+                attempt.message
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("; ");
+    error.with_detail("attempts", attempts)
+}
+
+/// Record which backend produced `error`, unless it already says.
+fn tag_backend(error: CaptureError, backend: &str) -> CaptureError {
+    if error.detail("backend").is_some() {
+        return error;
+    }
+    error.with_detail("backend", backend)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct ScriptedProvider {
+        name: &'static str,
+        region: Option<CaptureErrorKind>,
+        calls: AtomicUsize,
+    }
+
+    impl ScriptedProvider {
+        fn new(name: &'static str, region: Option<CaptureErrorKind>) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                region,
+                calls: AtomicUsize::new(0),
+            })
+        }
+    }
+
+    impl ScreenCaptureProvider for ScriptedProvider {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn supports(&self, operation: CaptureOperation) -> bool {
+            operation == CaptureOperation::Region
+        }
+
+        fn capture_region(&self, rect: CaptureRect) -> Result<RgbaImage, CaptureError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            match self.region {
+                Some(kind) => Err(CaptureError::new(kind, self.name)),
+                None => Ok(RgbaImage::new(rect.width, rect.height)),
+            }
+        }
+    }
+
+    fn rect() -> CaptureRect {
+        CaptureRect {
+            x: 0,
+            y: 0,
+            width: 20,
+            height: 10,
+        }
+    }
+
+    #[test]
+    fn permission_error_recognizes_display_creation_failure() {
+        assert!(is_permission_error("could not create image from display"));
+    }
+
+    #[test]
+    fn test_failed_backend_falls_through_to_next() {
+        let failing = ScriptedProvider::new("first", Some(CaptureErrorKind::CaptureFailed));
+        let working = ScriptedProvider::new("second", None);
+        let providers = CaptureProviders::new(vec![failing.clone(), working.clone()]);
+
+        let image = providers
+            .try_each(CaptureOperation::Region, |p| p.capture_region(rect()))
+            .unwrap();
+
+        assert_eq!(image.dimensions(), (20, 10));
+        assert_eq!(failing.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(working.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_earlier_failures_are_listed_in_the_error() {
+        let providers = CaptureProviders::new(vec![
+            ScriptedProvider::new("first", Some(CaptureErrorKind::CaptureFailed)),
+            ScriptedProvider::new("second", Some(CaptureErrorKind::CommandFailed)),
+            ScriptedProvider::new("third", Some(CaptureErrorKind::Permission)),
+        ]);
+
+        let error = providers
+            .try_each(CaptureOperation::Region, |p| p.capture_region(rect()))
+            .unwrap_err();
+
+        assert_eq!(error.kind, CaptureErrorKind::Permission);
+        assert_eq!(error.detail("backend"), Some("third"));
+        assert_eq!(
+            error.detail("attempts"),
+            Some("first: first; second: second")
+        );
+    }
+
+    #[test]
+    fn test_permission_cancel_and_busy_stop_the_search() {
+        for kind in [
+            CaptureErrorKind::Permission,
+            CaptureErrorKind::Cancelled,
+            CaptureErrorKind::Busy,
+        ] {
+            let stopping = ScriptedProvider::new("first", Some(kind));
+            let working = ScriptedProvider::new("second", None);
+            let providers = CaptureProviders::new(vec![stopping, working.clone()]);
+
+            let error = providers
+                .try_each(CaptureOperation::Region, |p| p.capture_region(rect()))
+                .unwrap_err();
+
+            assert_eq!(error.kind, kind);
+            assert_eq!(error.detail("backend"), Some("first"));
+            assert_eq!(working.calls.load(Ordering::SeqCst), 0);
+        }
+    }
+
+    struct FileProvider;
+
+    impl ScreenCaptureProvider for FileProvider {
+        fn name(&self) -> &'static str {
+            "file"
+        }
+
+        fn supports(&self, operation: CaptureOperation) -> bool {
+            operation == CaptureOperation::Region
+        }
+
+        fn captures_via_file(&self) -> bool {
+            true
+        }
+
+        fn capture_region(&self, rect: CaptureRect) -> Result<RgbaImage, CaptureError> {
+            Ok(RgbaImage::new(rect.width, rect.height))
+        }
+    }
+
+    #[test]
+    fn test_polling_prefers_in_memory_backends() {
+        let memory = ScriptedProvider::new("memory", None);
+        let providers = CaptureProviders::new(vec![Arc::new(FileProvider), memory.clone()]);
+
+        let (backend, _) = providers
+            .in_memory_first()
+            .try_each(CaptureOperation::Region, |p| {
+                p.capture_region(rect()).map(|image| (p.name(), image))
+            })
+            .unwrap();
+
+        assert_eq!(backend, "memory");
+        assert_eq!(memory.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_unsupported_operations_skip_backends() {
+        let provider = ScriptedProvider::new("only", None);
+        let providers = CaptureProviders::new(vec![provider.clone()]);
+
+        let error = providers
+            .try_each(CaptureOperation::FullScreen, |p| p.capture_full_screen())
+            .unwrap_err();
+
+        assert!(error.message.contains("FullScreen"));
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_ipc_errors_keep_kind_and_explain_permission() {
+        let error = CaptureError::new(CaptureErrorKind::Cancelled, "Screenshot was cancelled");
+        assert_eq!(to_ipc_error(error.clone()), error);
+
+        let error =
+            CaptureError::new(CaptureErrorKind::Permission, "denied").with_detail("backend", "cli");
+        let mapped = to_ipc_error(error);
+        assert_eq!(mapped.kind, CaptureErrorKind::Permission);
+        assert_eq!(mapped.message, permission_required_error().message);
+        assert_eq!(mapped.detail("cause"), Some("denied"));
+        assert_eq!(mapped.detail("backend"), Some("cli"));
+    }
+
+    #[test]
+    fn test_xcap_preferred_off_macos() {
+        #[cfg(not(target_os = "macos"))]
+        assert_eq!(preferred_backends()[0], CaptureBackend::Xcap);
+    }
+
+    // Run under X11 with: xvfb-run -s "-screen 0 1280x800x24" cargo test -- --ignored
+    #[cfg(target_os = "linux")]
+    #[test]
+    #[ignore = "needs an X11 display"]
+    fn test_xcap_captures_monitor_and_region_under_x11() {
+        assert!(linux_display_available());
+        let provider = XcapProvider;
+
+        let monitors = provider.monitors().unwrap();
+        let primary = monitors
+            .iter()
+            .find(|monitor| monitor.is_primary)
+            .unwrap_or(&monitors[0]);
+        let full = provider.capture_full_screen().unwrap();
+        assert!(full.width() >= primary.width && full.height() >= primary.height);
+
+        let rect = CaptureRect::new(primary.x + 10, primary.y + 20, 120, 80);
+        let region = provider.capture_region(rect).unwrap();
+        let expected = primary.to_local_physical(rect);
+        assert_eq!(region.dimensions(), (expected.width, expected.height));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    #[ignore = "needs an X11 display"]
+    fn test_xcap_lists_windows_under_x11() {
+        let windows = XcapProvider.windows().unwrap();
+        for window in windows.iter().filter(|window| !window.is_minimized) {
+            if window.width >= 10 && window.height >= 10 {
+                XcapProvider.capture_window(window.id).unwrap();
+            }
+        }
+    }
+}
@@ -1,6 +1,7 @@
 //! Clipboard operations module
 
 use crate::utils::AppResult;
+use capture_core::CaptureError;
 use std::process::Command;
 
 /// Copy an image file to the system clipboard using macOS native APIs
@@ -15,11 +16,14 @@ pub fn copy_image_to_clipboard(image_path: &str) -> AppResult<()> {
         .arg("-e")
         .arg(&script)
         .output()
-        .map_err(|e| format!("Failed to execute osascript: {}", e))?;
+        .map_err(|e| CaptureError::command(format!("Failed to execute osascript: {}", e)))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Failed to copy image to clipboard: {}", stderr));
+        return Err(CaptureError::command(format!(
+            "Failed to copy image to clipboard: {}",
+            stderr
+        )));
     }
 
     Ok(())
@@ -34,11 +38,14 @@ pub fn copy_text_to_clipboard(text: &str) -> AppResult<()> {
         .arg("-e")
         .arg(&script)
         .output()
-        .map_err(|e| format!("Failed to execute osascript: {}", e))?;
+        .map_err(|e| CaptureError::command(format!("Failed to execute osascript: {}", e)))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Failed to copy text to clipboard: {}", stderr));
+        return Err(CaptureError::command(format!(
+            "Failed to copy text to clipboard: {}",
+            stderr
+        )));
     }
 
     Ok(())
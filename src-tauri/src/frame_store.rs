@@ -0,0 +1,284 @@
+//! In-memory frame store
+//!
+//! Captured frames and previews are kept as decoded buffers in `FrameStore`,
+//! registered as Tauri managed state, instead of being written to temp PNGs
+//! for the webview to load back. The webview reads them through the
+//! `frame://` URI scheme (`http://frame.localhost/` on Windows), which
+//! encodes a frame only when it is actually requested.
+//!
+//! Frames can belong to a group, such as a scroll session, so they can be
+//! released together. The store keeps at most `MAX_FRAME_STORE_BYTES` of
+//! pixels and evicts the oldest frames beyond that.
+
+use capture_core::CaptureError;
+use image::codecs::png::{CompressionType, FilterType, PngEncoder};
+use image::{ExtendedColorType, ImageEncoder, RgbaImage};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tauri::http::{header, Response, StatusCode};
+
+use crate::utils::AppResult;
+
+/// URI scheme the webview uses to load stored frames.
+pub const FRAME_SCHEME: &str = "frame";
+/// Pixel memory kept before the oldest frames are evicted.
+const MAX_FRAME_STORE_BYTES: usize = 512 * 1024 * 1024;
+
+/// A stored frame, as returned to the frontend.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameHandle {
+    pub id: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+struct StoredFrame {
+    image: Arc<RgbaImage>,
+    group: Option<String>,
+}
+
+#[derive(Default)]
+struct Frames {
+    frames: HashMap<String, StoredFrame>,
+    /// Ids in insertion order, oldest first.
+    order: VecDeque<String>,
+    bytes: usize,
+}
+
+impl Frames {
+    fn remove(&mut self, id: &str) -> Option<StoredFrame> {
+        let frame = self.frames.remove(id)?;
+        self.order.retain(|entry| entry != id);
+        self.bytes -= frame.image.as_raw().len();
+        Some(frame)
+    }
+
+    /// Evict the oldest frames until `incoming` more bytes fit the budget.
+    fn make_room(&mut self, incoming: usize, budget: usize) {
+        while self.bytes + incoming > budget {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+            if let Some(frame) = self.frames.remove(&oldest) {
+                self.bytes -= frame.image.as_raw().len();
+            }
+        }
+    }
+}
+
+/// Decoded frames shared with the webview, keyed by id.
+pub struct FrameStore {
+    frames: Mutex<Frames>,
+    next_id: AtomicU64,
+    budget: usize,
+}
+
+impl Default for FrameStore {
+    fn default() -> Self {
+        Self::with_budget(MAX_FRAME_STORE_BYTES)
+    }
+}
+
+impl FrameStore {
+    fn with_budget(budget: usize) -> Self {
+        Self {
+            frames: Mutex::default(),
+            next_id: AtomicU64::new(1),
+            budget,
+        }
+    }
+
+    /// Store `image` under a fresh id, optionally as part of `group`. An
+    /// `Arc` is stored as is, sharing the pixels with the caller.
+    pub fn insert(
+        &self,
+        group: Option<&str>,
+        image: impl Into<Arc<RgbaImage>>,
+    ) -> AppResult<FrameHandle> {
+        let image = image.into();
+        let handle = FrameHandle {
+            id: format!("frame-{}", self.next_id.fetch_add(1, Ordering::Relaxed)),
+            width: image.width(),
+            height: image.height(),
+        };
+        let size = image.as_raw().len();
+
+        let mut frames = self.lock()?;
+        frames.make_room(size, self.budget);
+        frames.bytes += size;
+        frames.order.push_back(handle.id.clone());
+        frames.frames.insert(
+            handle.id.clone(),
+            StoredFrame {
+                image,
+                group: group.map(str::to_string),
+            },
+        );
+        Ok(handle)
+    }
+
+    /// Store `image` in place of `previous`, which is released. Fresh ids
+    /// keep the webview from showing a cached copy of the old frame.
+    pub fn replace(
+        &self,
+        previous: Option<&str>,
+        group: Option<&str>,
+        image: impl Into<Arc<RgbaImage>>,
+    ) -> AppResult<FrameHandle> {
+        if let Some(previous) = previous {
+            self.remove(previous)?;
+        }
+        self.insert(group, image)
+    }
+
+    pub fn get(&self, id: &str) -> AppResult<Option<Arc<RgbaImage>>> {
+        Ok(self
+            .lock()?
+            .frames
+            .get(id)
+            .map(|frame| Arc::clone(&frame.image)))
+    }
+
+    /// Release a frame. Unknown ids are ignored.
+    pub fn remove(&self, id: &str) -> AppResult<()> {
+        self.lock()?.remove(id);
+        Ok(())
+    }
+
+    /// Release every frame in `group`. Returns how many were removed.
+    pub fn remove_group(&self, group: &str) -> AppResult<usize> {
+        let mut frames = self.lock()?;
+        let ids: Vec<String> = frames
+            .frames
+            .iter()
+            .filter(|(_, frame)| frame.group.as_deref() == Some(group))
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in &ids {
+            frames.remove(id);
+        }
+        Ok(ids.len())
+    }
+
+    fn lock(&self) -> AppResult<MutexGuard<'_, Frames>> {
+        self.frames.lock().map_err(|e| {
+            CaptureError::command(format!("Failed to acquire frame store lock: {}", e))
+        })
+    }
+}
+
+/// Encode `image` as PNG, favouring speed over size. Used for frames that
+/// are read back soon and thrown away.
+pub fn encode_png_fast(image: &RgbaImage) -> AppResult<Vec<u8>> {
+    let mut png = Vec::new();
+    PngEncoder::new_with_quality(&mut png, CompressionType::Fast, FilterType::Sub)
+        .write_image(
+            image.as_raw(),
+            image.width(),
+            image.height(),
+            ExtendedColorType::Rgba8,
+        )
+        .map_err(|e| CaptureError::io(format!("Failed to encode frame: {}", e)))?;
+    Ok(png)
+}
+
+/// Answer a `frame://` request; the path is the frame id. Encodes the
+/// frame, so call it on the blocking pool rather than the main thread.
+pub fn serve_frame(store: &FrameStore, path: &str) -> Response<Vec<u8>> {
+    let id = path.trim_start_matches('/');
+    let frame = match store.get(id) {
+        Ok(Some(frame)) => frame,
+        Ok(None) => return error_response(StatusCode::NOT_FOUND, "Frame not found"),
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, &e.message),
+    };
+    match encode_png_fast(&frame) {
+        Ok(png) => Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "image/png")
+            .header(header::CACHE_CONTROL, "no-store")
+            .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+            .body(png)
+            .unwrap_or_else(|_| Response::new(Vec::new())),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, &e.message),
+    }
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain")
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(message.as_bytes().to_vec())
+        .unwrap_or_else(|_| Response::new(Vec::new()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn frame(width: u32, height: u32, value: u8) -> RgbaImage {
+        RgbaImage::from_pixel(width, height, Rgba([value, 0, 0, 255]))
+    }
+
+    #[test]
+    fn test_frames_are_served_as_png() {
+        let store = FrameStore::default();
+        let handle = store.insert(None, frame(8, 4, 7)).unwrap();
+        assert_eq!((handle.width, handle.height), (8, 4));
+
+        let response = serve_frame(&store, &format!("/{}", handle.id));
+        assert_eq!(response.status(), StatusCode::OK);
+        let decoded = image::load_from_memory(response.body()).unwrap().to_rgba8();
+        assert_eq!(decoded, frame(8, 4, 7));
+
+        store.remove(&handle.id).unwrap();
+        let response = serve_frame(&store, &format!("/{}", handle.id));
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_groups_are_released_together() {
+        let store = FrameStore::default();
+        let a = store.insert(Some("session-a"), frame(2, 2, 1)).unwrap();
+        let b = store.insert(Some("session-a"), frame(2, 2, 2)).unwrap();
+        let c = store.insert(Some("session-b"), frame(2, 2, 3)).unwrap();
+
+        assert_eq!(store.remove_group("session-a").unwrap(), 2);
+        assert!(store.get(&a.id).unwrap().is_none());
+        assert!(store.get(&b.id).unwrap().is_none());
+        assert!(store.get(&c.id).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_replace_issues_a_new_id() {
+        let store = FrameStore::default();
+        let first = store.insert(None, frame(2, 2, 1)).unwrap();
+        let second = store
+            .replace(Some(&first.id), None, frame(2, 2, 2))
+            .unwrap();
+
+        assert_ne!(first.id, second.id);
+        assert!(store.get(&first.id).unwrap().is_none());
+        assert_eq!(
+            store.get(&second.id).unwrap().unwrap().get_pixel(0, 0)[0],
+            2
+        );
+    }
+
+    #[test]
+    fn test_oldest_frames_are_evicted_over_budget() {
+        // Room for two 4x4 RGBA frames.
+        let store = FrameStore::with_budget(2 * 4 * 4 * 4);
+        let first = store.insert(None, frame(4, 4, 1)).unwrap();
+        let second = store.insert(None, frame(4, 4, 2)).unwrap();
+        let third = store.insert(None, frame(4, 4, 3)).unwrap();
+
+        assert!(store.get(&first.id).unwrap().is_none());
+        assert!(store.get(&second.id).unwrap().is_some());
+        assert!(store.get(&third.id).unwrap().is_some());
+    }
+}
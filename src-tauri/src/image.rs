@@ -1,76 +1,47 @@
 //! Image processing module
 
 use base64::{engine::general_purpose, Engine as _};
-use image::{DynamicImage, ImageFormat, Rgba, RgbaImage};
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::imageops::{replace, resize, FilterType};
+use image::{
+    DynamicImage, ExtendedColorType, GrayImage, ImageEncoder, ImageFormat, Luma, Rgb, RgbImage,
+    Rgba, RgbaImage,
+};
 use imageproc::filter::gaussian_blur_f32;
 use std::fs;
 use std::path::PathBuf;
 
-use crate::utils::{ensure_dir, generate_filename, AppResult};
-
-/// Region coordinates for cropping
-#[derive(Debug, Clone, Copy)]
-pub struct CropRegion {
-    pub x: u32,
-    pub y: u32,
-    pub width: u32,
-    pub height: u32,
-}
-
-impl CropRegion {
-    /// Create a new crop region, clamping to image bounds
-    pub fn clamped(
-        x: u32,
-        y: u32,
-        width: u32,
-        height: u32,
-        img_width: u32,
-        img_height: u32,
-    ) -> Self {
-        let crop_x = x.min(img_width.saturating_sub(1));
-        let crop_y = y.min(img_height.saturating_sub(1));
-        let crop_width = width.min(img_width.saturating_sub(crop_x));
-        let crop_height = height.min(img_height.saturating_sub(crop_y));
+use capture_core::{CaptureError, CaptureErrorKind, PhysicalRect};
 
-        Self {
-            x: crop_x,
-            y: crop_y,
-            width: crop_width,
-            height: crop_height,
-        }
-    }
-
-    /// Check if the region is valid (non-zero dimensions)
-    pub fn is_valid(&self) -> bool {
-        self.width > 0 && self.height > 0
-    }
-}
+use crate::annotations::{draw_annotations, Annotation};
+use crate::utils::{ensure_dir, generate_filename, AppResult};
 
 /// Crop an image file and save to a new location
-pub fn crop_image(source_path: &str, region: CropRegion, save_dir: &str) -> AppResult<String> {
-    let img = image::open(source_path).map_err(|e| format!("Failed to open screenshot: {}", e))?;
+pub fn crop_image(source_path: &str, region: PhysicalRect, save_dir: &str) -> AppResult<String> {
+    let img = image::open(source_path)
+        .map_err(|e| CaptureError::io(format!("Failed to open screenshot: {}", e)))?;
 
     let img_width = img.width();
     let img_height = img.height();
 
     // Clamp region to image bounds
-    let region = CropRegion::clamped(
-        region.x,
-        region.y,
-        region.width,
-        region.height,
-        img_width,
-        img_height,
-    );
+    let region = region.clamp_to_size(img_width, img_height);
 
-    if !region.is_valid() {
-        return Err(format!(
+    if region.is_empty() {
+        return Err(CaptureError::validation(format!(
             "Invalid crop region: x={}, y={}, w={}, h={} (image: {}x{})",
             region.x, region.y, region.width, region.height, img_width, img_height
-        ));
+        )));
     }
 
-    let cropped = img.crop_imm(region.x, region.y, region.width, region.height);
+    let cropped = img.crop_imm(
+        region.x as u32,
+        region.y as u32,
+        region.width,
+        region.height,
+    );
 
     save_image(&cropped, save_dir, "region")
 }
@@ -84,52 +55,173 @@ pub fn save_image(img: &DynamicImage, save_dir: &str, prefix: &str) -> AppResult
     let file_path = dest_path.join(&filename);
 
     img.save(&file_path)
-        .map_err(|e| format!("Failed to save image: {}", e))?;
+        .map_err(|e| CaptureError::io(format!("Failed to save image: {}", e)))?;
 
     Ok(file_path.to_string_lossy().into_owned())
 }
 
-/// Save base64-encoded image data to a file
-pub fn save_base64_image(image_data: &str, save_dir: &str, prefix: &str) -> AppResult<String> {
-    let base64_data = image_data
-        .strip_prefix("data:image/png;base64,")
-        .ok_or("Invalid image data format: expected data:image/png;base64, prefix")?;
+/// Encoded image formats exchanged with the webview
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Png,
+    Jpeg,
+    Webp,
+}
 
-    let image_bytes = general_purpose::STANDARD
-        .decode(base64_data)
-        .map_err(|e| format!("Failed to decode base64: {}", e))?;
+/// JPEG quality used when the caller does not ask for one
+const DEFAULT_JPEG_QUALITY: u8 = 90;
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 3] = [ExportFormat::Png, ExportFormat::Jpeg, ExportFormat::Webp];
+
+    /// Parse a MIME type such as `image/webp`. Unknown types are reported as
+    /// unsupported, listing the types that are, so the caller can pick again.
+    pub fn from_mime(mime: &str) -> AppResult<Self> {
+        let essence = mime.split(';').next().unwrap_or_default().trim();
+        Self::ALL
+            .into_iter()
+            .find(|format| format.mime().eq_ignore_ascii_case(essence))
+            .ok_or_else(|| {
+                let supported: Vec<&str> = Self::ALL.iter().map(|format| format.mime()).collect();
+                CaptureError::new(
+                    CaptureErrorKind::Unsupported,
+                    format!("Image format '{}' is not supported", mime),
+                )
+                .with_detail("supported", supported.join(","))
+            })
+    }
+
+    pub fn mime(self) -> &'static str {
+        match self {
+            ExportFormat::Png => "image/png",
+            ExportFormat::Jpeg => "image/jpeg",
+            ExportFormat::Webp => "image/webp",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Png => "png",
+            ExportFormat::Jpeg => "jpg",
+            ExportFormat::Webp => "webp",
+        }
+    }
+
+    fn image_format(self) -> ImageFormat {
+        match self {
+            ExportFormat::Png => ImageFormat::Png,
+            ExportFormat::Jpeg => ImageFormat::Jpeg,
+            ExportFormat::Webp => ImageFormat::WebP,
+        }
+    }
+}
+
+/// Encode `img` as `format`. `quality` (1-100) only applies to JPEG, which
+/// has no alpha channel, so transparent areas are flattened onto white.
+/// WebP is written lossless.
+pub fn encode_image(
+    img: &RgbaImage,
+    format: ExportFormat,
+    quality: Option<u8>,
+) -> AppResult<Vec<u8>> {
+    let mut buffer = Vec::new();
+    let (width, height) = img.dimensions();
+    let result = match format {
+        ExportFormat::Png => PngEncoder::new(&mut buffer).write_image(
+            img.as_raw(),
+            width,
+            height,
+            ExtendedColorType::Rgba8,
+        ),
+        ExportFormat::Jpeg => {
+            let quality = quality.unwrap_or(DEFAULT_JPEG_QUALITY).clamp(1, 100);
+            JpegEncoder::new_with_quality(&mut buffer, quality).write_image(
+                flatten_onto_white(img).as_raw(),
+                width,
+                height,
+                ExtendedColorType::Rgb8,
+            )
+        }
+        ExportFormat::Webp => WebPEncoder::new_lossless(&mut buffer).write_image(
+            img.as_raw(),
+            width,
+            height,
+            ExtendedColorType::Rgba8,
+        ),
+    };
+    result.map_err(|e| CaptureError::io(format!("Failed to encode image: {}", e)))?;
+    Ok(buffer)
+}
+
+fn flatten_onto_white(img: &RgbaImage) -> RgbImage {
+    RgbImage::from_fn(img.width(), img.height(), |x, y| {
+        let Rgba([r, g, b, a]) = *img.get_pixel(x, y);
+        let blend = |channel: u8| {
+            let alpha = u32::from(a);
+            ((u32::from(channel) * alpha + 255 * (255 - alpha) + 127) / 255) as u8
+        };
+        Rgb([blend(r), blend(g), blend(b)])
+    })
+}
+
+/// Save already encoded image bytes to a file named after `format`. The
+/// bytes must actually be in that format.
+pub fn save_image_bytes(
+    bytes: &[u8],
+    format: ExportFormat,
+    save_dir: &str,
+    prefix: &str,
+) -> AppResult<String> {
+    match image::guess_format(bytes) {
+        Ok(found) if found == format.image_format() => {}
+        _ => {
+            return Err(CaptureError::validation(format!(
+                "Image data is not {}",
+                format.mime()
+            )))
+        }
+    }
 
     let dest_path = PathBuf::from(save_dir);
     ensure_dir(&dest_path)?;
 
-    let filename = generate_filename(prefix, "png")?;
+    let filename = generate_filename(prefix, format.extension())?;
     let file_path = dest_path.join(&filename);
 
-    fs::write(&file_path, image_bytes).map_err(|e| format!("Failed to save image: {}", e))?;
+    fs::write(&file_path, bytes)
+        .map_err(|e| CaptureError::io(format!("Failed to save image: {}", e)))?;
 
     Ok(file_path.to_string_lossy().into_owned())
 }
 
-/// Copy a screenshot file to a destination directory
-pub fn copy_screenshot_to_dir(source_path: &str, save_dir: &str) -> AppResult<String> {
-    let src_path = PathBuf::from(source_path);
-    if !src_path.exists() {
-        return Err(format!("Screenshot file not found: {}", source_path));
-    }
+/// Save base64-encoded image data to a file. Fallback for webviews that
+/// cannot send binary IPC payloads; prefer `save_image_bytes`.
+pub fn save_base64_image(image_data: &str, save_dir: &str, prefix: &str) -> AppResult<String> {
+    let base64_data = image_data
+        .strip_prefix("data:image/png;base64,")
+        .ok_or_else(|| {
+            CaptureError::validation(
+                "Invalid image data format: expected data:image/png;base64, prefix",
+            )
+        })?;
+
+    let image_bytes = general_purpose::STANDARD
+        .decode(base64_data)
+        .map_err(|e| CaptureError::validation(format!("Failed to decode base64: {}", e)))?;
 
     let dest_path = PathBuf::from(save_dir);
     ensure_dir(&dest_path)?;
 
-    let filename = generate_filename("shot", "png")?;
+    let filename = generate_filename(prefix, "png")?;
     let file_path = dest_path.join(&filename);
 
-    fs::copy(&src_path, &file_path).map_err(|e| format!("Failed to copy screenshot: {}", e))?;
+    fs::write(&file_path, image_bytes)
+        .map_err(|e| CaptureError::io(format!("Failed to save image: {}", e)))?;
 
     Ok(file_path.to_string_lossy().into_owned())
 }
 
-#[derive(Debug, Clone, serde::Deserialize)]
-#[allow(dead_code)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct RenderSettings {
     pub background_type: String,
     pub custom_color: String,
@@ -144,64 +236,365 @@ pub struct RenderSettings {
     pub shadow_offset_x: f32,
     pub shadow_offset_y: f32,
     pub shadow_opacity: f32,
+    /// Used when `background_type` is `gradient`.
+    #[serde(default)]
+    pub gradient: Option<GradientSpec>,
+    /// Image file, or base64 `data:` URL, used when `background_type` is
+    /// `image`.
+    #[serde(default)]
+    pub background_image: Option<String>,
+    #[serde(default)]
+    pub background_fit: BackgroundFit,
+    /// Grow the padding so the shadow is never cut off. Callers that place
+    /// things in the padded image by the requested padding turn this off.
+    #[serde(default = "default_shadow_auto_padding")]
+    pub shadow_auto_padding: bool,
+    /// Per-corner radii, used instead of `border_radius` when set.
+    #[serde(default)]
+    pub corner_radii: Option<CornerRadii>,
+    /// Stroke drawn just inside the screenshot's rounded edge.
+    #[serde(default)]
+    pub border: Option<BorderStroke>,
+    /// Editor annotations, positioned in the image padded by the requested
+    /// padding.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<Annotation>,
+}
+
+fn default_shadow_auto_padding() -> bool {
+    true
 }
 
-fn hex_to_rgba(hex: &str) -> Result<Rgba<u8>, String> {
-    let hex = hex.trim_start_matches('#');
-    if hex.len() != 6 {
-        return Err("Invalid hex color".to_string());
+/// Corner radii in pixels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CornerRadii {
+    pub top_left: f32,
+    pub top_right: f32,
+    pub bottom_right: f32,
+    pub bottom_left: f32,
+}
+
+impl CornerRadii {
+    pub fn uniform(radius: f32) -> Self {
+        Self {
+            top_left: radius,
+            top_right: radius,
+            bottom_right: radius,
+            bottom_left: radius,
+        }
     }
 
-    let r = u8::from_str_radix(&hex[0..2], 16).map_err(|_| "Invalid hex color")?;
-    let g = u8::from_str_radix(&hex[2..4], 16).map_err(|_| "Invalid hex color")?;
-    let b = u8::from_str_radix(&hex[4..6], 16).map_err(|_| "Invalid hex color")?;
+    /// Radii scaled down together where adjacent corners would overlap on
+    /// a `width`x`height` rectangle, as CSS and canvas `roundRect` do.
+    fn fitted(self, width: f32, height: f32) -> Self {
+        let radii = Self {
+            top_left: self.top_left.max(0.0),
+            top_right: self.top_right.max(0.0),
+            bottom_right: self.bottom_right.max(0.0),
+            bottom_left: self.bottom_left.max(0.0),
+        };
+        let ratio = |side: f32, a: f32, b: f32| if a + b > side { side / (a + b) } else { 1.0 };
+        let scale = ratio(width, radii.top_left, radii.top_right)
+            .min(ratio(width, radii.bottom_left, radii.bottom_right))
+            .min(ratio(height, radii.top_left, radii.bottom_left))
+            .min(ratio(height, radii.top_right, radii.bottom_right));
+        Self {
+            top_left: radii.top_left * scale,
+            top_right: radii.top_right * scale,
+            bottom_right: radii.bottom_right * scale,
+            bottom_left: radii.bottom_left * scale,
+        }
+    }
+}
 
-    Ok(Rgba([r, g, b, 255]))
+/// An inner border around the screenshot.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct BorderStroke {
+    pub width: f32,
+    pub color: String,
 }
 
-fn create_background(
-    width: u32,
-    height: u32,
-    background_type: &str,
-    custom_color: &str,
-) -> RgbaImage {
-    let mut img = RgbaImage::new(width, height);
-
-    match background_type {
-        "transparent" => {
-            for pixel in img.pixels_mut() {
-                *pixel = Rgba([0, 0, 0, 0]);
-            }
+/// A color stop; `offset` runs from 0 to 1 along the gradient.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct GradientStop {
+    pub offset: f32,
+    pub color: String,
+}
+
+/// A mesh gradient control point, positioned in fractions of the size.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MeshPoint {
+    pub x: f32,
+    pub y: f32,
+    pub color: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GradientSpec {
+    /// `angle` in degrees as in CSS `linear-gradient`: 0 runs bottom to
+    /// top, 90 left to right. Without one it runs from the top left to the
+    /// bottom right corner, like the editor's `createLinearGradient(0, 0, w, h)`.
+    Linear {
+        #[serde(default)]
+        angle: Option<f32>,
+        stops: Vec<GradientStop>,
+    },
+    /// `center` is in fractions of the size. The last stop is reached at
+    /// `radius` times the distance to the farthest corner.
+    Radial {
+        #[serde(default = "default_radial_center")]
+        center: [f32; 2],
+        #[serde(default = "default_radial_radius")]
+        radius: f32,
+        stops: Vec<GradientStop>,
+    },
+    /// Colors pinned to points and blended by inverse squared distance,
+    /// approximating the editor's mesh wallpapers.
+    Mesh { points: Vec<MeshPoint> },
+}
+
+fn default_radial_center() -> [f32; 2] {
+    [0.5, 0.5]
+}
+
+fn default_radial_radius() -> f32 {
+    1.0
+}
+
+/// How a background image is fitted to the padded canvas.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackgroundFit {
+    /// Scaled to the canvas, ignoring aspect ratio, as the editor draws it.
+    #[default]
+    Stretch,
+    /// Scaled to fill the canvas and cropped around the center.
+    Cover,
+    /// Scaled to fit inside the canvas, centered on transparency.
+    Contain,
+    /// Repeated at its own size from the top left.
+    Tile,
+}
+
+const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+fn hex_to_rgba(color: &str) -> AppResult<Rgba<u8>> {
+    let invalid = || CaptureError::validation(format!("Invalid hex color '{}'", color));
+    let hex = color.trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return Err(invalid());
+    }
+
+    let channel = |range| u8::from_str_radix(&hex[range], 16).map_err(|_| invalid());
+    Ok(Rgba([channel(0..2)?, channel(2..4)?, channel(4..6)?, 255]))
+}
+
+fn parse_color(hex: &str) -> AppResult<[f32; 4]> {
+    let Rgba(channels) = hex_to_rgba(hex)
+        .map_err(|_| CaptureError::validation(format!("Invalid gradient color '{}'", hex)))?;
+    Ok(channels.map(f32::from))
+}
+
+fn to_pixel(color: [f32; 4]) -> Rgba<u8> {
+    Rgba(color.map(|channel| channel.round().clamp(0.0, 255.0) as u8))
+}
+
+fn mix(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
+    std::array::from_fn(|i| a[i] + (b[i] - a[i]) * t)
+}
+
+/// Stops sorted by offset, with offsets clamped to 0..=1.
+fn parse_stops(stops: &[GradientStop]) -> AppResult<Vec<(f32, [f32; 4])>> {
+    if stops.is_empty() {
+        return Err(CaptureError::validation("Gradient needs at least one stop"));
+    }
+    let mut parsed = stops
+        .iter()
+        .map(|stop| Ok((stop.offset.clamp(0.0, 1.0), parse_color(&stop.color)?)))
+        .collect::<AppResult<Vec<_>>>()?;
+    parsed.sort_by(|a, b| a.0.total_cmp(&b.0));
+    Ok(parsed)
+}
+
+/// Color at `t` along sorted `stops`, held flat before the first and after
+/// the last stop.
+fn color_at(stops: &[(f32, [f32; 4])], t: f32) -> [f32; 4] {
+    let (first, last) = (stops[0], stops[stops.len() - 1]);
+    if t <= first.0 {
+        return first.1;
+    }
+    if t >= last.0 {
+        return last.1;
+    }
+    for pair in stops.windows(2) {
+        let ((start, from), (end, to)) = (pair[0], pair[1]);
+        if t <= end {
+            let span = end - start;
+            return if span <= f32::EPSILON {
+                to
+            } else {
+                mix(from, to, (t - start) / span)
+            };
         }
-        "white" => {
-            for pixel in img.pixels_mut() {
-                *pixel = Rgba([255, 255, 255, 255]);
-            }
+    }
+    last.1
+}
+
+fn render_gradient(width: u32, height: u32, spec: &GradientSpec) -> AppResult<RgbaImage> {
+    let (w, h) = (width as f32, height as f32);
+    match spec {
+        GradientSpec::Linear { angle, stops } => {
+            let stops = parse_stops(stops)?;
+            let diagonal = w.hypot(h).max(f32::EPSILON);
+            let (sin, cos) = match angle {
+                Some(angle) => angle.to_radians().sin_cos(),
+                None => (w / diagonal, -h / diagonal),
+            };
+            // Gradient line through the center, long enough that the
+            // corners land on the first and last stop, as in CSS.
+            let length = (w * sin).abs() + (h * cos).abs();
+            Ok(RgbaImage::from_fn(width, height, |x, y| {
+                let dx = x as f32 + 0.5 - w / 2.0;
+                let dy = y as f32 + 0.5 - h / 2.0;
+                let along = dx * sin - dy * cos;
+                let t = if length > 0.0 {
+                    along / length + 0.5
+                } else {
+                    0.0
+                };
+                to_pixel(color_at(&stops, t))
+            }))
         }
-        "black" => {
-            for pixel in img.pixels_mut() {
-                *pixel = Rgba([0, 0, 0, 255]);
-            }
+        GradientSpec::Radial {
+            center,
+            radius,
+            stops,
+        } => {
+            let stops = parse_stops(stops)?;
+            let (cx, cy) = (center[0] * w, center[1] * h);
+            let farthest = (cx.max(w - cx).powi(2) + cy.max(h - cy).powi(2)).sqrt();
+            let reach = farthest * radius.max(0.0);
+            Ok(RgbaImage::from_fn(width, height, |x, y| {
+                let distance = (x as f32 + 0.5 - cx).hypot(y as f32 + 0.5 - cy);
+                let t = if reach > 0.0 { distance / reach } else { 1.0 };
+                to_pixel(color_at(&stops, t))
+            }))
         }
-        "gray" => {
-            for pixel in img.pixels_mut() {
-                *pixel = Rgba([245, 245, 245, 255]);
+        GradientSpec::Mesh { points } => {
+            if points.is_empty() {
+                return Err(CaptureError::validation(
+                    "Mesh gradient needs at least one point",
+                ));
             }
+            let points = points
+                .iter()
+                .map(|point| Ok((point.x, point.y, parse_color(&point.color)?)))
+                .collect::<AppResult<Vec<_>>>()?;
+            Ok(RgbaImage::from_fn(width, height, |x, y| {
+                let u = (x as f32 + 0.5) / w;
+                let v = (y as f32 + 0.5) / h;
+                let mut sum = [0.0; 4];
+                let mut total = 0.0;
+                for (px, py, color) in &points {
+                    let weight = 1.0 / ((u - px).powi(2) + (v - py).powi(2) + 1e-4);
+                    for (acc, channel) in sum.iter_mut().zip(color) {
+                        *acc += channel * weight;
+                    }
+                    total += weight;
+                }
+                to_pixel(sum.map(|channel| channel / total))
+            }))
         }
-        "custom" => {
-            let color = hex_to_rgba(custom_color).unwrap_or(Rgba([255, 255, 255, 255]));
-            for pixel in img.pixels_mut() {
-                *pixel = color;
-            }
+    }
+}
+
+/// A background image from a file path or a base64 `data:` URL, which is how
+/// the webview hands over its bundled wallpapers.
+fn load_background_image(source: &str) -> AppResult<RgbaImage> {
+    let Some(data_url) = source.strip_prefix("data:") else {
+        return Ok(image::open(source)
+            .map_err(|e| CaptureError::io(format!("Failed to open background image: {}", e)))?
+            .to_rgba8());
+    };
+    let data = data_url
+        .split_once(";base64,")
+        .map(|(_, data)| data)
+        .ok_or_else(|| CaptureError::validation("Background image data URL is not base64"))?;
+    let bytes = general_purpose::STANDARD
+        .decode(data)
+        .map_err(|e| CaptureError::validation(format!("Failed to decode base64: {}", e)))?;
+    Ok(image::load_from_memory(&bytes)
+        .map_err(|e| CaptureError::validation(format!("Invalid background image: {}", e)))?
+        .to_rgba8())
+}
+
+fn render_background_image(
+    width: u32,
+    height: u32,
+    path: &str,
+    fit: BackgroundFit,
+) -> AppResult<RgbaImage> {
+    let source = load_background_image(path)?;
+    let (src_width, src_height) = source.dimensions();
+    if src_width == 0 || src_height == 0 {
+        return Err(CaptureError::validation("Background image is empty"));
+    }
+
+    let scale_x = width as f32 / src_width as f32;
+    let scale_y = height as f32 / src_height as f32;
+    let scaled = |scale: f32| {
+        let w = ((src_width as f32 * scale).round() as u32).max(1);
+        let h = ((src_height as f32 * scale).round() as u32).max(1);
+        resize(&source, w, h, FilterType::Triangle)
+    };
+
+    let fitted = match fit {
+        BackgroundFit::Stretch => resize(&source, width, height, FilterType::Triangle),
+        BackgroundFit::Cover => {
+            let covered = scaled(scale_x.max(scale_y));
+            let x = covered.width().saturating_sub(width) / 2;
+            let y = covered.height().saturating_sub(height) / 2;
+            let mut canvas = RgbaImage::new(width, height);
+            replace(&mut canvas, &covered, -i64::from(x), -i64::from(y));
+            canvas
         }
-        _ => {
-            for pixel in img.pixels_mut() {
-                *pixel = Rgba([255, 255, 255, 255]);
-            }
+        BackgroundFit::Contain => {
+            let contained = scaled(scale_x.min(scale_y));
+            let x = width.saturating_sub(contained.width()) / 2;
+            let y = height.saturating_sub(contained.height()) / 2;
+            let mut canvas = RgbaImage::new(width, height);
+            replace(&mut canvas, &contained, i64::from(x), i64::from(y));
+            canvas
         }
-    }
+        BackgroundFit::Tile => RgbaImage::from_fn(width, height, |x, y| {
+            *source.get_pixel(x % src_width, y % src_height)
+        }),
+    };
+    Ok(fitted)
+}
 
-    img
+fn create_background(width: u32, height: u32, settings: &RenderSettings) -> AppResult<RgbaImage> {
+    let fill = |color: Rgba<u8>| RgbaImage::from_pixel(width, height, color);
+
+    let background = match settings.background_type.as_str() {
+        "transparent" => fill(Rgba([0, 0, 0, 0])),
+        "white" => fill(WHITE),
+        "black" => fill(Rgba([0, 0, 0, 255])),
+        "gray" => fill(Rgba([245, 245, 245, 255])),
+        "custom" => fill(hex_to_rgba(&settings.custom_color).unwrap_or(WHITE)),
+        "gradient" => match &settings.gradient {
+            Some(spec) => render_gradient(width, height, spec)?,
+            None => fill(WHITE),
+        },
+        "image" => match &settings.background_image {
+            Some(path) => render_background_image(width, height, path, settings.background_fit)?,
+            None => fill(WHITE),
+        },
+        _ => fill(WHITE),
+    };
+
+    Ok(background)
 }
 
 fn apply_noise(img: &mut RgbaImage, amount: f32) {
@@ -222,90 +615,275 @@ fn apply_noise(img: &mut RgbaImage, amount: f32) {
     }
 }
 
-pub fn render_image_with_effects(image_path: &str, settings: RenderSettings) -> AppResult<String> {
-    let img = image::open(image_path).map_err(|e| format!("Failed to open image: {}", e))?;
+/// Space around the screenshot in the rendered image, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Padding {
+    pub top: u32,
+    pub bottom: u32,
+    pub left: u32,
+    pub right: u32,
+}
 
-    let img_width = img.width();
-    let img_height = img.height();
-    let bg_width = img_width + settings.padding_left + settings.padding_right;
-    let bg_height = img_height + settings.padding_top + settings.padding_bottom;
-
-    let mut background = create_background(
-        bg_width,
-        bg_height,
-        &settings.background_type,
-        &settings.custom_color,
+impl Padding {
+    fn is_zero(&self) -> bool {
+        self.top == 0 && self.bottom == 0 && self.left == 0 && self.right == 0
+    }
+}
+
+impl RenderSettings {
+    /// A plain white background with no padding or effects, for tests to
+    /// adjust.
+    #[cfg(test)]
+    pub fn test_default() -> Self {
+        Self {
+            background_type: "white".to_string(),
+            custom_color: "#ffffff".to_string(),
+            blur_amount: 0.0,
+            noise_amount: 0.0,
+            border_radius: 0.0,
+            padding_top: 0,
+            padding_bottom: 0,
+            padding_left: 0,
+            padding_right: 0,
+            shadow_blur: 0.0,
+            shadow_offset_x: 0.0,
+            shadow_offset_y: 0.0,
+            shadow_opacity: 0.0,
+            gradient: None,
+            background_image: None,
+            background_fit: BackgroundFit::default(),
+            shadow_auto_padding: default_shadow_auto_padding(),
+            corner_radii: None,
+            border: None,
+            annotations: Vec::new(),
+        }
+    }
+
+    fn requested_padding(&self) -> Padding {
+        Padding {
+            top: self.padding_top,
+            bottom: self.padding_bottom,
+            left: self.padding_left,
+            right: self.padding_right,
+        }
+    }
+
+    /// Whether a shadow is drawn. Like the editor, there is none without
+    /// padding.
+    fn has_shadow(&self) -> bool {
+        self.shadow_opacity > 0.0 && !self.requested_padding().is_zero()
+    }
+
+    /// Padding used for the render: the requested padding, grown on each
+    /// side the shadow would otherwise reach past, unless
+    /// `shadow_auto_padding` is off.
+    pub fn padding(&self) -> Padding {
+        let requested = self.requested_padding();
+        if !self.shadow_auto_padding || !self.has_shadow() {
+            return requested;
+        }
+
+        let reach = shadow_reach(self.shadow_blur);
+        let needed = |offset: f32| (reach + offset).ceil().max(0.0) as u32;
+        Padding {
+            top: requested.top.max(needed(-self.shadow_offset_y)),
+            bottom: requested.bottom.max(needed(self.shadow_offset_y)),
+            left: requested.left.max(needed(-self.shadow_offset_x)),
+            right: requested.right.max(needed(self.shadow_offset_x)),
+        }
+    }
+}
+
+/// Canvas `shadowBlur` is twice the Gaussian sigma.
+fn shadow_sigma(blur: f32) -> f32 {
+    blur.max(0.0) / 2.0
+}
+
+/// How far past the shape a shadow stays visible.
+fn shadow_reach(blur: f32) -> f32 {
+    (3.0 * shadow_sigma(blur)).ceil()
+}
+
+/// The screenshot's outline: a rounded rectangle the size of the image.
+struct RoundedRect {
+    width: f32,
+    height: f32,
+    radii: CornerRadii,
+}
+
+impl RoundedRect {
+    fn new(width: u32, height: u32, radii: CornerRadii) -> Self {
+        let (width, height) = (width as f32, height as f32);
+        Self {
+            width,
+            height,
+            radii: radii.fitted(width, height),
+        }
+    }
+
+    /// Signed distance from the center of pixel `(x, y)` to the outline,
+    /// negative inside.
+    fn distance(&self, x: u32, y: u32) -> f32 {
+        let dx = x as f32 + 0.5 - self.width / 2.0;
+        let dy = y as f32 + 0.5 - self.height / 2.0;
+        let radius = match (dx < 0.0, dy < 0.0) {
+            (true, true) => self.radii.top_left,
+            (false, true) => self.radii.top_right,
+            (false, false) => self.radii.bottom_right,
+            (true, false) => self.radii.bottom_left,
+        };
+        let qx = dx.abs() - self.width / 2.0 + radius;
+        let qy = dy.abs() - self.height / 2.0 + radius;
+        qx.max(qy).min(0.0) + qx.max(0.0).hypot(qy.max(0.0)) - radius
+    }
+}
+
+/// How much of a pixel lies inside an outline `distance` away from its
+/// center, approximating the pixel as one unit wide across the edge.
+fn coverage(distance: f32) -> f32 {
+    (0.5 - distance).clamp(0.0, 1.0)
+}
+
+fn to_alpha(coverage: f32) -> u8 {
+    (coverage * 255.0).round() as u8
+}
+
+/// Blend `src` over `dst`, scaling the source alpha by `coverage` (0-255).
+fn blend_over(dst: &mut Rgba<u8>, src: Rgba<u8>, coverage: u8) {
+    let src_alpha = f32::from(src[3]) * f32::from(coverage) / (255.0 * 255.0);
+    if src_alpha <= 0.0 {
+        return;
+    }
+    let dst_alpha = f32::from(dst[3]) / 255.0;
+    let out_alpha = src_alpha + dst_alpha * (1.0 - src_alpha);
+    for i in 0..3 {
+        let color = (f32::from(src[i]) * src_alpha
+            + f32::from(dst[i]) * dst_alpha * (1.0 - src_alpha))
+            / out_alpha;
+        dst[i] = color.round().clamp(0.0, 255.0) as u8;
+    }
+    dst[3] = (out_alpha * 255.0).round().clamp(0.0, 255.0) as u8;
+}
+
+/// Draw the shadow of `shape`, an alpha mask placed at `(x, y)`, onto
+/// `canvas`.
+fn draw_shadow(
+    canvas: &mut RgbaImage,
+    shape: &GrayImage,
+    x: i64,
+    y: i64,
+    settings: &RenderSettings,
+) {
+    let mut layer = GrayImage::new(canvas.width(), canvas.height());
+    replace(
+        &mut layer,
+        shape,
+        x + settings.shadow_offset_x.round() as i64,
+        y + settings.shadow_offset_y.round() as i64,
     );
+    let sigma = shadow_sigma(settings.shadow_blur);
+    if sigma > 0.0 {
+        layer = gaussian_blur_f32(&layer, sigma);
+    }
+
+    let opacity = (settings.shadow_opacity / 100.0).clamp(0.0, 1.0);
+    let color = Rgba([0, 0, 0, (opacity * 255.0).round() as u8]);
+    for (pixel, coverage) in canvas.pixels_mut().zip(layer.pixels()) {
+        blend_over(pixel, color, coverage[0]);
+    }
+}
+
+/// The screenshot clipped to its rounded outline, with anti-aliased edges
+/// and the inner border drawn on top.
+fn render_card(img: &RgbaImage, settings: &RenderSettings) -> AppResult<RgbaImage> {
+    let (width, height) = img.dimensions();
+    let radii = settings
+        .corner_radii
+        .unwrap_or_else(|| CornerRadii::uniform(settings.border_radius));
+    let outline = RoundedRect::new(width, height, radii);
+    let border = match &settings.border {
+        Some(border) if border.width > 0.0 => {
+            let color = hex_to_rgba(&border.color).map_err(|_| {
+                CaptureError::validation(format!("Invalid border color '{}'", border.color))
+            })?;
+            Some((border.width, color))
+        }
+        _ => None,
+    };
+
+    let mut card = RgbaImage::new(width, height);
+    for (x, y, pixel) in img.enumerate_pixels() {
+        let distance = outline.distance(x, y);
+        let inside = coverage(distance);
+        if inside <= 0.0 {
+            continue;
+        }
+        let target = card.get_pixel_mut(x, y);
+        blend_over(target, *pixel, to_alpha(inside));
+        if let Some((stroke_width, color)) = border {
+            let stroke = inside - coverage(distance + stroke_width);
+            blend_over(target, color, to_alpha(stroke));
+        }
+    }
+    Ok(card)
+}
+
+/// Render `image_path` with padding, background, shadow, rounded corners
+/// and annotations.
+pub fn render_image(image_path: &str, settings: &RenderSettings) -> AppResult<RgbaImage> {
+    let img = image::open(image_path)
+        .map_err(|e| CaptureError::io(format!("Failed to open image: {}", e)))?
+        .to_rgba8();
+    render_screenshot(&img, settings)
+}
+
+/// `render_image` for an already decoded screenshot.
+pub fn render_screenshot(img: &RgbaImage, settings: &RenderSettings) -> AppResult<RgbaImage> {
+    let (img_width, img_height) = img.dimensions();
+    let padding = settings.padding();
+    let bg_width = img_width + padding.left + padding.right;
+    let bg_height = img_height + padding.top + padding.bottom;
+
+    let mut canvas = create_background(bg_width, bg_height, settings)?;
 
     if settings.blur_amount > 0.0 {
-        background = gaussian_blur_f32(&background, settings.blur_amount);
+        canvas = gaussian_blur_f32(&canvas, settings.blur_amount);
     }
 
     if settings.noise_amount > 0.0 {
-        apply_noise(&mut background, settings.noise_amount);
-    }
-
-    let img_rgba = img.to_rgba8();
-    let mut final_img = RgbaImage::new(bg_width, bg_height);
-
-    for y in 0..bg_height {
-        for x in 0..bg_width {
-            if x >= settings.padding_left
-                && x < settings.padding_left + img_width
-                && y >= settings.padding_top
-                && y < settings.padding_top + img_height
-            {
-                let img_x = x - settings.padding_left;
-                let img_y = y - settings.padding_top;
-
-                let corner_x = if img_x < settings.border_radius as u32 {
-                    img_x
-                } else if img_x >= img_width.saturating_sub(settings.border_radius as u32) {
-                    img_width - img_x - 1
-                } else {
-                    u32::MAX
-                };
-
-                let corner_y = if img_y < settings.border_radius as u32 {
-                    img_y
-                } else if img_y >= img_height.saturating_sub(settings.border_radius as u32) {
-                    img_height - img_y - 1
-                } else {
-                    u32::MAX
-                };
+        apply_noise(&mut canvas, settings.noise_amount);
+    }
 
-                let in_corner = corner_x < settings.border_radius as u32
-                    && corner_y < settings.border_radius as u32;
+    let card = render_card(img, settings)?;
+    let (left, top) = (i64::from(padding.left), i64::from(padding.top));
 
-                if in_corner {
-                    let dist_x = corner_x as f32;
-                    let dist_y = corner_y as f32;
-                    let corner_dist = (dist_x * dist_x + dist_y * dist_y).sqrt();
+    if settings.has_shadow() {
+        let shape = GrayImage::from_fn(img_width, img_height, |x, y| {
+            Luma([card.get_pixel(x, y)[3]])
+        });
+        draw_shadow(&mut canvas, &shape, left, top, settings);
+    }
 
-                    if corner_dist <= settings.border_radius {
-                        let pixel = img_rgba.get_pixel(img_x, img_y);
-                        final_img.put_pixel(x, y, *pixel);
-                    } else {
-                        let bg_pixel = background.get_pixel(x, y);
-                        final_img.put_pixel(x, y, *bg_pixel);
-                    }
-                } else {
-                    let pixel = img_rgba.get_pixel(img_x, img_y);
-                    final_img.put_pixel(x, y, *pixel);
-                }
-            } else {
-                let bg_pixel = background.get_pixel(x, y);
-                final_img.put_pixel(x, y, *bg_pixel);
-            }
-        }
+    for (x, y, pixel) in card.enumerate_pixels() {
+        let target = canvas.get_pixel_mut(x + padding.left, y + padding.top);
+        blend_over(target, *pixel, 255);
     }
 
-    let mut buffer = Vec::new();
-    let mut cursor = std::io::Cursor::new(&mut buffer);
-    DynamicImage::ImageRgba8(final_img)
-        .write_to(&mut cursor, ImageFormat::Png)
-        .map_err(|e| format!("Failed to encode image: {}", e))?;
+    // Annotations stay on the screenshot when the shadow grew the padding.
+    let requested = settings.requested_padding();
+    let offset = (
+        (padding.left - requested.left) as f32,
+        (padding.top - requested.top) as f32,
+    );
+    draw_annotations(&mut canvas, &settings.annotations, offset)?;
+
+    Ok(canvas)
+}
 
+/// `render_image` as a PNG data URL, for the base64 IPC fallback.
+pub fn render_image_with_effects(image_path: &str, settings: RenderSettings) -> AppResult<String> {
+    let rendered = render_image(image_path, &settings)?;
+    let buffer = encode_image(&rendered, ExportFormat::Png, None)?;
     let base64_data = general_purpose::STANDARD.encode(&buffer);
     Ok(format!("data:image/png;base64,{}", base64_data))
 }
@@ -314,91 +892,509 @@ pub fn render_image_with_effects(image_path: &str, settings: RenderSettings) ->
 mod tests {
     use super::*;
 
-    mod crop_region {
-        use super::*;
+    fn checker() -> RgbaImage {
+        RgbaImage::from_fn(6, 4, |x, y| {
+            if (x + y) % 2 == 0 {
+                Rgba([255, 0, 0, 255])
+            } else {
+                Rgba([0, 0, 255, 0])
+            }
+        })
+    }
 
-        #[test]
-        fn test_crop_region_clamped_within_bounds() {
-            let region = CropRegion::clamped(100, 100, 200, 200, 1920, 1080);
+    #[test]
+    fn test_formats_are_negotiated_by_mime() {
+        assert_eq!(
+            ExportFormat::from_mime("image/webp").unwrap(),
+            ExportFormat::Webp
+        );
+        assert_eq!(
+            ExportFormat::from_mime("IMAGE/JPEG; q=0.9").unwrap(),
+            ExportFormat::Jpeg
+        );
+
+        let error = ExportFormat::from_mime("image/avif").unwrap_err();
+        assert_eq!(error.kind, CaptureErrorKind::Unsupported);
+        assert_eq!(
+            error.detail("supported"),
+            Some("image/png,image/jpeg,image/webp")
+        );
+    }
 
-            assert_eq!(region.x, 100);
-            assert_eq!(region.y, 100);
-            assert_eq!(region.width, 200);
-            assert_eq!(region.height, 200);
+    #[test]
+    fn test_lossless_formats_round_trip() {
+        let img = checker();
+        for format in [ExportFormat::Png, ExportFormat::Webp] {
+            let bytes = encode_image(&img, format, None).unwrap();
+            assert_eq!(image::guess_format(&bytes).unwrap(), format.image_format());
+            let decoded = image::load_from_memory(&bytes).unwrap().to_rgba8();
+            assert_eq!(decoded, img, "{:?}", format);
         }
+    }
 
-        #[test]
-        fn test_crop_region_clamped_exceeds_bounds() {
-            // Region that exceeds image bounds
-            let region = CropRegion::clamped(1800, 1000, 500, 500, 1920, 1080);
-
-            assert_eq!(region.x, 1800);
-            assert_eq!(region.y, 1000);
-            assert_eq!(region.width, 120); // 1920 - 1800 = 120
-            assert_eq!(region.height, 80); // 1080 - 1000 = 80
-        }
+    #[test]
+    fn test_jpeg_flattens_transparency_onto_white() {
+        let img = RgbaImage::from_pixel(16, 16, Rgba([0, 0, 0, 0]));
+        let bytes = encode_image(&img, ExportFormat::Jpeg, Some(95)).unwrap();
+        let decoded = image::load_from_memory(&bytes).unwrap().to_rgb8();
+        assert!(decoded
+            .pixels()
+            .all(|pixel| pixel.0.iter().all(|c| *c > 250)));
+    }
 
-        #[test]
-        fn test_crop_region_clamped_x_y_exceed_bounds() {
-            // X and Y exceed image dimensions
-            let region = CropRegion::clamped(2000, 2000, 100, 100, 1920, 1080);
-
-            assert_eq!(region.x, 1919); // Clamped to img_width - 1
-            assert_eq!(region.y, 1079); // Clamped to img_height - 1
-            assert_eq!(region.width, 1); // Only 1 pixel available
-            assert_eq!(region.height, 1); // Only 1 pixel available
-        }
+    #[test]
+    fn test_save_image_bytes_checks_the_declared_format() {
+        let dir = std::env::temp_dir().join(format!("ashot-image-bytes-{}", std::process::id()));
+        let dir_str = dir.to_string_lossy().into_owned();
+        let png = encode_image(&checker(), ExportFormat::Png, None).unwrap();
 
-        #[test]
-        fn test_crop_region_is_valid() {
-            let valid_region = CropRegion {
-                x: 0,
-                y: 0,
-                width: 100,
-                height: 100,
-            };
-            assert!(valid_region.is_valid());
+        let path = save_image_bytes(&png, ExportFormat::Png, &dir_str, "test").unwrap();
+        assert!(path.ends_with(".png"));
+        assert_eq!(fs::read(&path).unwrap(), png);
 
-            let invalid_region_zero_width = CropRegion {
-                x: 0,
-                y: 0,
-                width: 0,
-                height: 100,
-            };
-            assert!(!invalid_region_zero_width.is_valid());
+        let error = save_image_bytes(&png, ExportFormat::Webp, &dir_str, "test").unwrap_err();
+        assert_eq!(error.kind, CaptureErrorKind::ValidationFailed);
 
-            let invalid_region_zero_height = CropRegion {
-                x: 0,
-                y: 0,
-                width: 100,
-                height: 0,
-            };
-            assert!(!invalid_region_zero_height.is_valid());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    /// `RenderSettings::test_default` with the fields in `background`
+    /// replaced.
+    fn settings(background: serde_json::Value) -> RenderSettings {
+        let mut value = serde_json::to_value(RenderSettings::test_default()).unwrap();
+        value
+            .as_object_mut()
+            .unwrap()
+            .extend(background.as_object().unwrap().clone());
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_linear_gradient_follows_css_angles() {
+        let stops = serde_json::json!([
+            { "offset": 0.0, "color": "#000000" },
+            { "offset": 1.0, "color": "#ffffff" },
+        ]);
+        let to_right = settings(serde_json::json!({
+            "background_type": "gradient",
+            "gradient": { "kind": "linear", "angle": 90.0, "stops": stops },
+        }));
+        let img = create_background(100, 10, &to_right).unwrap();
+        assert!(img.get_pixel(0, 5)[0] < 5);
+        assert!(img.get_pixel(99, 5)[0] > 250);
+        assert!((img.get_pixel(50, 0)[0] as i32 - 128).abs() <= 2);
+        assert_eq!(img.get_pixel(50, 0), img.get_pixel(50, 9));
+
+        let to_top = settings(serde_json::json!({
+            "background_type": "gradient",
+            "gradient": { "kind": "linear", "angle": 0.0, "stops": stops },
+        }));
+        let img = create_background(10, 100, &to_top).unwrap();
+        assert!(img.get_pixel(5, 0)[0] > 250);
+        assert!(img.get_pixel(5, 99)[0] < 5);
+
+        // Without an angle the gradient runs corner to corner, so the top
+        // right corner of a wide canvas is nearly at the last stop.
+        let corners = settings(serde_json::json!({
+            "background_type": "gradient",
+            "gradient": { "kind": "linear", "stops": stops },
+        }));
+        let img = create_background(200, 50, &corners).unwrap();
+        assert!(img.get_pixel(0, 0)[0] < 5);
+        assert!(img.get_pixel(199, 49)[0] > 250);
+        assert!((img.get_pixel(199, 0)[0] as i32 - 240).abs() <= 3);
+        assert!((img.get_pixel(100, 25)[0] as i32 - 128).abs() <= 2);
+    }
+
+    #[test]
+    fn test_radial_gradient_holds_the_last_stop_past_its_radius() {
+        let radial = settings(serde_json::json!({
+            "background_type": "gradient",
+            "gradient": {
+                "kind": "radial",
+                "radius": 0.5,
+                "stops": [
+                    { "offset": 0.0, "color": "#ff0000" },
+                    { "offset": 1.0, "color": "#0000ff" },
+                ],
+            },
+        }));
+        let img = create_background(40, 40, &radial).unwrap();
+        assert!(img.get_pixel(20, 20)[0] > 240);
+        assert_eq!(img.get_pixel(0, 0), &Rgba([0, 0, 255, 255]));
+        assert_eq!(img.get_pixel(39, 0), &Rgba([0, 0, 255, 255]));
+    }
+
+    #[test]
+    fn test_mesh_gradient_is_pinned_at_its_points() {
+        let mesh = settings(serde_json::json!({
+            "background_type": "gradient",
+            "gradient": {
+                "kind": "mesh",
+                "points": [
+                    { "x": 0.0, "y": 0.0, "color": "#667eea" },
+                    { "x": 1.0, "y": 1.0, "color": "#764ba2" },
+                ],
+            },
+        }));
+        let img = create_background(64, 64, &mesh).unwrap();
+        let near = |pixel: &Rgba<u8>, expected: [u8; 3]| {
+            (0..3).all(|i| (pixel[i] as i32 - expected[i] as i32).abs() <= 3)
+        };
+        assert!(near(img.get_pixel(0, 0), [0x66, 0x7e, 0xea]));
+        assert!(near(img.get_pixel(63, 63), [0x76, 0x4b, 0xa2]));
+    }
+
+    #[test]
+    fn test_invalid_gradients_are_rejected() {
+        let no_stops = settings(serde_json::json!({
+            "background_type": "gradient",
+            "gradient": { "kind": "linear", "angle": 45.0, "stops": [] },
+        }));
+        let error = create_background(4, 4, &no_stops).unwrap_err();
+        assert_eq!(error.kind, CaptureErrorKind::ValidationFailed);
+
+        let bad_color = settings(serde_json::json!({
+            "background_type": "gradient",
+            "gradient": { "kind": "mesh", "points": [{ "x": 0.5, "y": 0.5, "color": "teal" }] },
+        }));
+        let error = create_background(4, 4, &bad_color).unwrap_err();
+        assert_eq!(error.kind, CaptureErrorKind::ValidationFailed);
+
+        // Without a spec the editor's white fallback is kept.
+        let missing = settings(serde_json::json!({ "background_type": "gradient" }));
+        let img = create_background(4, 4, &missing).unwrap();
+        assert!(img.pixels().all(|pixel| *pixel == WHITE));
+    }
+
+    #[test]
+    fn test_background_image_fits() {
+        let dir = std::env::temp_dir().join(format!("ashot-background-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        // 4x2: left half red, right half blue.
+        let path = dir.join("wallpaper.png");
+        RgbaImage::from_fn(4, 2, |x, _| {
+            if x < 2 {
+                Rgba([255, 0, 0, 255])
+            } else {
+                Rgba([0, 0, 255, 255])
+            }
+        })
+        .save(&path)
+        .unwrap();
+        let with_fit = |fit: &str| {
+            settings(serde_json::json!({
+                "background_type": "image",
+                "background_image": path.to_string_lossy(),
+                "background_fit": fit,
+            }))
+        };
+
+        let tiled = create_background(10, 3, &with_fit("tile")).unwrap();
+        assert_eq!(tiled.get_pixel(4, 2), &Rgba([255, 0, 0, 255]));
+        assert_eq!(tiled.get_pixel(7, 0), &Rgba([0, 0, 255, 255]));
+
+        // A square canvas crops the sides away for cover and letterboxes
+        // the top and bottom for contain.
+        let covered = create_background(8, 8, &with_fit("cover")).unwrap();
+        assert!(covered.pixels().all(|pixel| pixel[3] == 255));
+        assert_eq!(covered.get_pixel(0, 4)[0], 255);
+        assert_eq!(covered.get_pixel(7, 4)[2], 255);
+
+        let contained = create_background(8, 8, &with_fit("contain")).unwrap();
+        assert_eq!(contained.get_pixel(4, 0)[3], 0);
+        assert_eq!(contained.get_pixel(0, 4), &Rgba([255, 0, 0, 255]));
+        assert_eq!(contained.get_pixel(4, 7)[3], 0);
+
+        let stretched = create_background(8, 8, &with_fit("stretch")).unwrap();
+        assert!(stretched.pixels().all(|pixel| pixel[3] == 255));
+
+        let data_url = format!(
+            "data:image/png;base64,{}",
+            general_purpose::STANDARD.encode(fs::read(&path).unwrap())
+        );
+        let inline = settings(serde_json::json!({
+            "background_type": "image",
+            "background_image": data_url,
+        }));
+        assert_eq!(create_background(8, 8, &inline).unwrap(), stretched);
+
+        let missing = settings(serde_json::json!({
+            "background_type": "image",
+            "background_image": dir.join("missing.png").to_string_lossy(),
+        }));
+        let error = create_background(8, 8, &missing).unwrap_err();
+        assert_eq!(error.kind, CaptureErrorKind::Io);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    fn render_solid(width: u32, height: u32, settings: &RenderSettings) -> RgbaImage {
+        let shot = RgbaImage::from_pixel(width, height, Rgba([255, 0, 0, 255]));
+        render_screenshot(&shot, settings).unwrap()
+    }
+
+    fn shadowed(padding: u32, blur: f32, offset: (f32, f32), radius: f32) -> RenderSettings {
+        settings(serde_json::json!({
+            "padding_top": padding,
+            "padding_bottom": padding,
+            "padding_left": padding,
+            "padding_right": padding,
+            "border_radius": radius,
+            "shadow_blur": blur,
+            "shadow_offset_x": offset.0,
+            "shadow_offset_y": offset.1,
+            "shadow_opacity": 50.0,
+        }))
+    }
+
+    #[test]
+    fn test_shadow_is_offset_and_darkens_the_background() {
+        let settings = shadowed(20, 0.0, (6.0, 6.0), 0.0);
+        let img = render_solid(20, 20, &settings);
+        assert_eq!(img.dimensions(), (60, 60));
+
+        // Just past the bottom right of the screenshot, inside the offset.
+        let shadow = img.get_pixel(42, 42);
+        assert!((shadow[0] as i32 - 128).abs() <= 1, "{:?}", shadow);
+        assert_eq!(shadow[3], 255);
+        // Above and left of the screenshot there is no shadow.
+        assert_eq!(img.get_pixel(18, 18), &WHITE);
+        // The screenshot covers its own shadow.
+        assert_eq!(img.get_pixel(30, 30), &Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_shadow_follows_rounded_corners() {
+        let settings = shadowed(20, 0.0, (8.0, 8.0), 10.0);
+        let img = render_solid(40, 40, &settings);
+
+        // The bottom right corner of the offset shadow is rounded off, its
+        // edges are not.
+        assert_eq!(img.get_pixel(20 + 8 + 39, 20 + 8 + 39), &WHITE);
+        assert!(img.get_pixel(20 + 8 + 20, 20 + 8 + 39)[0] < 200);
+        // So is the screenshot: its corner shows the shadow underneath.
+        assert_ne!(img.get_pixel(20, 20), &Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_padding_grows_so_shadows_never_clip() {
+        let settings = shadowed(4, 20.0, (10.0, -5.0), 0.0);
+        // Sigma 10, so the shadow reaches 30px past the shape.
+        assert_eq!(
+            settings.padding(),
+            Padding {
+                top: 35,
+                bottom: 25,
+                left: 20,
+                right: 40,
+            }
+        );
+        let img = render_solid(10, 10, &settings);
+        assert_eq!(img.dimensions(), (10 + 20 + 40, 10 + 35 + 25));
+        // The edges stay background.
+        assert_eq!(img.get_pixel(img.width() - 1, 40), &WHITE);
+        assert_eq!(img.get_pixel(25, 0), &WHITE);
+
+        let mut fixed = settings.clone();
+        fixed.shadow_auto_padding = false;
+        assert_eq!(fixed.padding(), fixed.requested_padding());
+
+        // Without padding there is no shadow, as in the editor.
+        let unpadded = shadowed(0, 20.0, (10.0, 10.0), 0.0);
+        assert_eq!(unpadded.padding(), unpadded.requested_padding());
+        assert_eq!(render_solid(10, 10, &unpadded).dimensions(), (10, 10));
+    }
+
+    #[test]
+    fn test_outline_distance_is_measured_from_the_corner_circle() {
+        let outline = RoundedRect::new(40, 20, CornerRadii::uniform(8.0));
+        // Pixel centers half a pixel inside the straight edges.
+        assert_eq!(outline.distance(20, 0), -0.5);
+        assert_eq!(outline.distance(0, 10), -0.5);
+        // The very corner pixel sits outside the arc: its center is
+        // (7.5, 7.5) from the arc center, 8 * (sqrt(2) - 1) - 0.5 out.
+        let corner = outline.distance(0, 0);
+        assert!((corner - (7.5 * 2f32.sqrt() - 8.0)).abs() < 1e-4);
+        assert_eq!(coverage(corner), 0.0);
+        // Deep inside, the distance is to the nearest edge.
+        assert_eq!(outline.distance(20, 10), -9.5);
+    }
+
+    #[test]
+    fn test_oversized_radii_are_scaled_together() {
+        let radii = CornerRadii {
+            top_left: 30.0,
+            top_right: 10.0,
+            bottom_right: 0.0,
+            bottom_left: -5.0,
         }
+        .fitted(20.0, 100.0);
+        assert_eq!(radii.top_left, 15.0);
+        assert_eq!(radii.top_right, 5.0);
+        assert_eq!(radii.bottom_left, 0.0);
+    }
 
-        #[test]
-        fn test_crop_region_at_origin() {
-            let region = CropRegion::clamped(0, 0, 100, 100, 1920, 1080);
-
-            assert_eq!(region.x, 0);
-            assert_eq!(region.y, 0);
-            assert_eq!(region.width, 100);
-            assert_eq!(region.height, 100);
-            assert!(region.is_valid());
+    #[test]
+    fn test_corner_edges_are_anti_aliased() {
+        let card = render_card(
+            &RgbaImage::from_pixel(32, 32, Rgba([0, 0, 0, 255])),
+            &settings(serde_json::json!({ "border_radius": 12.0 })),
+        )
+        .unwrap();
+        let partial = card.pixels().filter(|pixel| pixel[3] > 0 && pixel[3] < 255);
+        assert!(partial.count() >= 4 * 6);
+        assert_eq!(card.get_pixel(0, 0)[3], 0);
+        assert_eq!(card.get_pixel(16, 0)[3], 255);
+    }
+
+    fn golden_screenshot() -> RgbaImage {
+        RgbaImage::from_fn(48, 32, |x, y| {
+            Rgba([(x * 5) as u8, (y * 7) as u8, 160, 255])
+        })
+    }
+
+    /// Compare with `tests/golden/<name>.png`, allowing for rounding
+    /// differences. Run with `UPDATE_GOLDEN=1` to write the images instead.
+    fn assert_matches_golden(name: &str, actual: &RgbaImage) {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/golden")
+            .join(format!("{}.png", name));
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            actual.save(&path).unwrap();
+            return;
         }
 
-        #[test]
-        fn test_crop_region_full_image() {
-            let region = CropRegion::clamped(0, 0, 1920, 1080, 1920, 1080);
-
-            assert_eq!(region.x, 0);
-            assert_eq!(region.y, 0);
-            assert_eq!(region.width, 1920);
-            assert_eq!(region.height, 1080);
-            assert!(region.is_valid());
+        let expected = image::open(&path)
+            .unwrap_or_else(|e| panic!("Failed to open {}: {}", path.display(), e))
+            .to_rgba8();
+        assert_eq!(actual.dimensions(), expected.dimensions(), "{}", name);
+        for (x, y, pixel) in actual.enumerate_pixels() {
+            let wanted = expected.get_pixel(x, y);
+            let close = (0..4).all(|i| (i32::from(pixel[i]) - i32::from(wanted[i])).abs() <= 1);
+            assert!(
+                close,
+                "{} differs at ({}, {}): {:?} != {:?}",
+                name, x, y, pixel, wanted
+            );
         }
     }
 
+    fn render_golden(overrides: serde_json::Value) -> RgbaImage {
+        let mut options = serde_json::json!({
+            "background_type": "custom",
+            "custom_color": "#dfe4ea",
+            "padding_top": 8,
+            "padding_bottom": 8,
+            "padding_left": 8,
+            "padding_right": 8,
+        });
+        options
+            .as_object_mut()
+            .unwrap()
+            .extend(overrides.as_object().unwrap().clone());
+        render_screenshot(&golden_screenshot(), &settings(options)).unwrap()
+    }
+
+    #[test]
+    fn test_golden_uniform_corners() {
+        let img = render_golden(serde_json::json!({ "border_radius": 10.0 }));
+        assert_matches_golden("corners_uniform", &img);
+    }
+
+    #[test]
+    fn test_golden_per_corner_radii() {
+        let img = render_golden(serde_json::json!({
+            "border_radius": 4.0,
+            "corner_radii": {
+                "top_left": 0.0,
+                "top_right": 6.0,
+                "bottom_right": 16.0,
+                "bottom_left": 10.0,
+            },
+        }));
+        assert_matches_golden("corners_per_corner", &img);
+    }
+
+    #[test]
+    fn test_golden_inner_border() {
+        let img = render_golden(serde_json::json!({
+            "border_radius": 10.0,
+            "border": { "width": 2.5, "color": "#1e90ff" },
+        }));
+        assert_matches_golden("corners_border", &img);
+    }
+
+    #[test]
+    fn test_golden_rounded_shadow() {
+        let img = render_golden(serde_json::json!({
+            "border_radius": 10.0,
+            "shadow_blur": 6.0,
+            "shadow_offset_x": 3.0,
+            "shadow_offset_y": 4.0,
+            "shadow_opacity": 45.0,
+        }));
+        assert_matches_golden("corners_shadow", &img);
+    }
+
+    #[test]
+    fn test_golden_annotations() {
+        let color = serde_json::json!({ "hex": "#e11d48", "opacity": 100 });
+        let base = |id: &str, x: f32, y: f32| {
+            serde_json::json!({
+                "id": id,
+                "x": x,
+                "y": y,
+                "fill": color,
+                "border": { "width": 2, "color": color },
+            })
+        };
+        let with = |mut value: serde_json::Value, extra: serde_json::Value| {
+            value
+                .as_object_mut()
+                .unwrap()
+                .extend(extra.as_object().unwrap().clone());
+            value
+        };
+        let annotations = serde_json::json!([
+            with(
+                base("blur", 30.0, 22.0),
+                serde_json::json!({
+                    "type": "blur", "width": 16, "height": 14, "blurAmount": 3,
+                })
+            ),
+            with(
+                base("rect", 10.0, 10.0),
+                serde_json::json!({
+                    "type": "rectangle", "width": 18, "height": 12,
+                })
+            ),
+            with(
+                base("arrow", 12.0, 36.0),
+                serde_json::json!({
+                    "type": "arrow", "endX": 46.0, "endY": 14.0, "lineType": "curved",
+                    "arrowType": "thin", "controlPoints": [{ "x": 40.0, "y": 40.0 }],
+                })
+            ),
+            with(
+                base("badge", 52.0, 36.0),
+                serde_json::json!({
+                    "type": "number", "number": 2, "radius": 7,
+                })
+            ),
+        ]);
+        // Shadowed, so the padding grows and annotations have to follow.
+        let img = render_golden(serde_json::json!({
+            "border_radius": 6.0,
+            "shadow_blur": 4.0,
+            "shadow_offset_y": 6.0,
+            "shadow_opacity": 30.0,
+            "annotations": annotations,
+        }));
+        assert_matches_golden("annotations", &img);
+    }
+
     mod base64_validation {
         #[test]
         fn test_base64_prefix_validation() {
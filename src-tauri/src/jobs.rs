@@ -0,0 +1,375 @@
+//! Background jobs
+//!
+//! Blocking work such as stitching, rendering, encoding and capture CLI
+//! runs goes through `Jobs`, registered as Tauri managed state, so it runs
+//! on the blocking pool instead of an async runtime worker. Every job has an
+//! id, which the frontend may choose up front so it can call `cancel_job`
+//! before the command returns, and reports `job-progress` events.
+//!
+//! Cancellation is cooperative: work polls `JobContext::check_cancelled`
+//! between steps. Code that does not get the context, like capture
+//! backends, can ask `current_job_cancelled` about the job running on the
+//! current thread.
+
+use capture_core::{CaptureError, CaptureErrorKind};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tauri::{AppHandle, Emitter};
+
+use crate::utils::AppResult;
+
+/// Event carrying a `JobProgress` payload.
+pub const JOB_PROGRESS_EVENT: &str = "job-progress";
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    Capture,
+    Stitch,
+    Render,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct JobProgress {
+    pub job_id: String,
+    pub kind: JobKind,
+    /// What the job is doing, e.g. "stitching" or "encoding".
+    pub stage: String,
+    pub done: u64,
+    pub total: u64,
+}
+
+type Reporter = Arc<dyn Fn(JobProgress) + Send + Sync>;
+
+thread_local! {
+    static CURRENT_JOB: RefCell<Option<Arc<AtomicBool>>> = const { RefCell::new(None) };
+}
+
+/// Whether the job running on this thread has been cancelled. False
+/// outside of jobs.
+pub fn current_job_cancelled() -> bool {
+    CURRENT_JOB.with(|job| {
+        job.borrow()
+            .as_ref()
+            .is_some_and(|cancelled| cancelled.load(Ordering::Relaxed))
+    })
+}
+
+fn cancelled_error(job_id: &str) -> CaptureError {
+    CaptureError::new(CaptureErrorKind::Cancelled, "Job was cancelled")
+        .with_detail("job_id", job_id)
+}
+
+/// Handed to a job's work for progress and cancellation.
+pub struct JobContext {
+    id: String,
+    kind: JobKind,
+    cancelled: Arc<AtomicBool>,
+    reporter: Reporter,
+}
+
+impl JobContext {
+    #[cfg(test)]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// A `cancelled` error once `cancel_job` was called for this job.
+    pub fn check_cancelled(&self) -> AppResult<()> {
+        if self.is_cancelled() {
+            return Err(cancelled_error(&self.id));
+        }
+        Ok(())
+    }
+
+    pub fn progress(&self, stage: &str, done: u64, total: u64) {
+        (self.reporter)(JobProgress {
+            job_id: self.id.clone(),
+            kind: self.kind,
+            stage: stage.to_string(),
+            done,
+            total,
+        });
+    }
+
+    /// Run `work` with this job as the current one on this thread.
+    fn enter<T>(&self, work: impl FnOnce(&JobContext) -> T) -> T {
+        struct Exit;
+        impl Drop for Exit {
+            fn drop(&mut self) {
+                CURRENT_JOB.with(|job| job.borrow_mut().take());
+            }
+        }
+
+        CURRENT_JOB.with(|job| *job.borrow_mut() = Some(Arc::clone(&self.cancelled)));
+        let _exit = Exit;
+        work(self)
+    }
+}
+
+#[derive(Default)]
+struct JobsInner {
+    running: Mutex<HashMap<String, Arc<AtomicBool>>>,
+    next_id: AtomicU64,
+    app: Option<AppHandle>,
+}
+
+/// Running jobs, keyed by id.
+#[derive(Clone, Default)]
+pub struct Jobs {
+    inner: Arc<JobsInner>,
+}
+
+impl Jobs {
+    /// Jobs reporting progress as events on `app`.
+    pub fn new(app: AppHandle) -> Self {
+        Self {
+            inner: Arc::new(JobsInner {
+                app: Some(app),
+                ..JobsInner::default()
+            }),
+        }
+    }
+
+    /// Run `work` on the blocking pool as job `job_id`, or under a generated
+    /// id. Fails with `busy` if a job with that id is already running.
+    pub async fn run<T, F>(&self, job_id: Option<String>, kind: JobKind, work: F) -> AppResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&JobContext) -> AppResult<T> + Send + 'static,
+    {
+        let context = self.start(job_id, kind, self.reporter())?;
+        let finish = FinishOnDrop {
+            jobs: self.clone(),
+            id: context.id.clone(),
+        };
+        let _cancel = CancelOnDrop(Arc::clone(&context.cancelled));
+        tauri::async_runtime::spawn_blocking(move || {
+            let _finish = finish;
+            context.enter(work)
+        })
+        .await
+        .map_err(|e| CaptureError::command(format!("Job failed: {}", e)))?
+    }
+
+    /// Cancel a running job. Returns whether it was running.
+    pub fn cancel(&self, job_id: &str) -> AppResult<bool> {
+        let running = self.lock()?;
+        let Some(cancelled) = running.get(job_id) else {
+            return Ok(false);
+        };
+        cancelled.store(true, Ordering::Relaxed);
+        Ok(true)
+    }
+
+    fn start(
+        &self,
+        job_id: Option<String>,
+        kind: JobKind,
+        reporter: Reporter,
+    ) -> AppResult<JobContext> {
+        let id = job_id.unwrap_or_else(|| {
+            format!("job-{}", self.inner.next_id.fetch_add(1, Ordering::Relaxed))
+        });
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        let mut running = self.lock()?;
+        if running.contains_key(&id) {
+            return Err(CaptureError::new(
+                CaptureErrorKind::Busy,
+                format!("Job '{}' is already running", id),
+            )
+            .with_detail("job_id", &id));
+        }
+        running.insert(id.clone(), Arc::clone(&cancelled));
+
+        Ok(JobContext {
+            id,
+            kind,
+            cancelled,
+            reporter,
+        })
+    }
+
+    fn finish(&self, job_id: &str) {
+        if let Ok(mut running) = self.lock() {
+            running.remove(job_id);
+        }
+    }
+
+    fn reporter(&self) -> Reporter {
+        let app = self.inner.app.clone();
+        Arc::new(move |progress| {
+            if let Some(app) = &app {
+                let _ = app.emit(JOB_PROGRESS_EVENT, progress);
+            }
+        })
+    }
+
+    fn lock(&self) -> AppResult<MutexGuard<'_, HashMap<String, Arc<AtomicBool>>>> {
+        self.inner
+            .running
+            .lock()
+            .map_err(|e| CaptureError::command(format!("Failed to acquire jobs lock: {}", e)))
+    }
+}
+
+/// Unregisters a job once its work returns, however it returns. It lives in
+/// the blocking closure, so the id stays registered, and busy, for as long
+/// as the work actually runs.
+struct FinishOnDrop {
+    jobs: Jobs,
+    id: String,
+}
+
+impl Drop for FinishOnDrop {
+    fn drop(&mut self) {
+        self.jobs.finish(&self.id);
+    }
+}
+
+/// Cancels a job when its command future is dropped, e.g. when the webview
+/// drops the request while the work is still running on the blocking pool.
+/// Once the work has returned the flag is no longer read.
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collecting_reporter() -> (Reporter, Arc<Mutex<Vec<JobProgress>>>) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let reporter: Reporter = Arc::new(move |progress| sink.lock().unwrap().push(progress));
+        (reporter, events)
+    }
+
+    #[test]
+    fn test_progress_is_reported_with_the_job_id() {
+        let jobs = Jobs::default();
+        let (reporter, events) = collecting_reporter();
+        let context = jobs
+            .start(Some("stitch-1".to_string()), JobKind::Stitch, reporter)
+            .unwrap();
+
+        context.progress("encoding", 1, 3);
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].job_id, "stitch-1");
+        assert_eq!(events[0].kind, JobKind::Stitch);
+        assert_eq!((events[0].done, events[0].total), (1, 3));
+    }
+
+    #[test]
+    fn test_cancel_reaches_the_running_job() {
+        let jobs = Jobs::default();
+        let (reporter, _) = collecting_reporter();
+        let context = jobs.start(None, JobKind::Render, reporter).unwrap();
+        assert!(context.check_cancelled().is_ok());
+
+        assert!(jobs.cancel(context.id()).unwrap());
+        let error = context.check_cancelled().unwrap_err();
+        assert_eq!(error.kind, CaptureErrorKind::Cancelled);
+        assert_eq!(error.detail("job_id"), Some(context.id()));
+
+        jobs.finish(context.id());
+        assert!(!jobs.cancel(context.id()).unwrap());
+    }
+
+    #[test]
+    fn test_duplicate_job_ids_are_busy() {
+        let jobs = Jobs::default();
+        let (reporter, _) = collecting_reporter();
+        let _first = jobs
+            .start(
+                Some("capture".to_string()),
+                JobKind::Capture,
+                Arc::clone(&reporter),
+            )
+            .unwrap();
+
+        let error = jobs
+            .start(Some("capture".to_string()), JobKind::Capture, reporter)
+            .err()
+            .unwrap();
+        assert_eq!(error.kind, CaptureErrorKind::Busy);
+    }
+
+    #[test]
+    fn test_current_job_is_scoped_to_the_work() {
+        let jobs = Jobs::default();
+        let (reporter, _) = collecting_reporter();
+        let context = jobs.start(None, JobKind::Capture, reporter).unwrap();
+        jobs.cancel(context.id()).unwrap();
+
+        assert!(!current_job_cancelled());
+        assert!(context.enter(|_| current_job_cancelled()));
+        assert!(!current_job_cancelled());
+    }
+
+    #[test]
+    fn test_dropped_job_is_cancelled_but_stays_busy_until_its_work_returns() {
+        let jobs = Jobs::default();
+        let (reporter, _) = collecting_reporter();
+        let context = jobs
+            .start(
+                Some("stitch".to_string()),
+                JobKind::Stitch,
+                Arc::clone(&reporter),
+            )
+            .unwrap();
+        let finish = FinishOnDrop {
+            jobs: jobs.clone(),
+            id: context.id().to_string(),
+        };
+
+        drop(CancelOnDrop(Arc::clone(&context.cancelled)));
+        assert!(context.is_cancelled());
+        let error = jobs
+            .start(
+                Some("stitch".to_string()),
+                JobKind::Stitch,
+                Arc::clone(&reporter),
+            )
+            .err()
+            .unwrap();
+        assert_eq!(error.kind, CaptureErrorKind::Busy);
+
+        drop(finish);
+        assert!(jobs
+            .start(Some("stitch".to_string()), JobKind::Stitch, reporter)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_run_executes_on_the_blocking_pool_and_unregisters() {
+        let jobs = Jobs::default();
+        let result = tauri::async_runtime::block_on(jobs.run(
+            Some("render".to_string()),
+            JobKind::Render,
+            |context| {
+                context.check_cancelled()?;
+                Ok(context.id().to_string())
+            },
+        ))
+        .unwrap();
+
+        assert_eq!(result, "render");
+        assert!(!jobs.cancel("render").unwrap());
+    }
+}
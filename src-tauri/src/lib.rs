@@ -4,27 +4,40 @@
 //! and saving screenshots with various features like region selection
 //! and background customization.
 
+mod annotations;
+mod capture_provider;
 mod clipboard;
 mod commands;
+mod frame_store;
 mod image;
+mod jobs;
 mod ocr;
-mod screencapturekit;
+mod project;
 mod screenshot;
+mod scroll_session;
 mod utils;
 
+use capture_provider::CaptureProviders;
 use commands::{
-    capture_all_monitors, capture_once, capture_rect_frame, capture_rect_ocr, capture_region,
-    check_screen_permission, cleanup_scroll_temp, copy_image_file_to_clipboard,
-    get_desktop_directory, get_mouse_position, get_temp_directory, list_capture_windows,
-    move_window_to_active_space, native_capture_fullscreen, native_capture_interactive,
-    native_capture_ocr_region, native_capture_window, open_screen_recording_settings,
-    play_screenshot_sound, poll_scroll_region, render_image_with_effects_rust,
-    request_screen_permission, reset_scroll_monitor, save_edited_image,
-    set_main_window_mouse_passthrough, stitch_scroll_frames, stitch_scroll_frames_preview,
+    cancel_job, capture_all_monitors, capture_once, capture_rect_frame, capture_rect_ocr,
+    capture_region, capture_virtual_desktop, check_screen_permission, cleanup_scroll_temp,
+    copy_image_file_to_clipboard, flatten_project, gc_scroll_sessions, get_desktop_directory,
+    get_mouse_position, get_temp_directory, list_capture_windows, list_scroll_sessions,
+    load_project, move_window_to_active_space, native_capture_fullscreen,
+    native_capture_interactive, native_capture_ocr_region, native_capture_window,
+    open_screen_recording_settings, play_screenshot_sound, poll_scroll_region, render_image_bytes,
+    render_image_with_effects_rust, request_screen_permission, resume_scroll_session,
+    save_edited_image, save_edited_image_bytes, save_project, set_main_window_mouse_passthrough,
+    start_scroll_session, stitch_scroll_frames, stitch_scroll_frames_preview,
     validate_save_directory,
 };
+use frame_store::{serve_frame, FrameStore, FRAME_SCHEME};
+use jobs::Jobs;
+use scroll_session::ScrollSessions;
 
-use tauri::{Emitter, Manager, RunEvent, WebviewUrl, WebviewWindowBuilder};
+#[cfg(target_os = "macos")]
+use tauri::RunEvent;
+use tauri::{Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
 
 fn is_hidden_launch() -> bool {
     std::env::args().any(|arg| arg == "--hidden")
@@ -82,9 +95,23 @@ pub fn run() {
             tauri_plugin_autostart::MacosLauncher::LaunchAgent,
             Some(vec!["--hidden"]),
         ))
+        .manage(ScrollSessions::default())
+        .manage(FrameStore::default())
+        .register_asynchronous_uri_scheme_protocol(FRAME_SCHEME, |ctx, request, responder| {
+            // Encoding a large canvas takes a while; keep it off the main thread.
+            let app = ctx.app_handle().clone();
+            let path = request.uri().path().to_string();
+            tauri::async_runtime::spawn_blocking(move || {
+                responder.respond(serve_frame(&app.state::<FrameStore>(), &path));
+            });
+        })
         .setup(|app| {
             use tauri::menu::{MenuBuilder, MenuItemBuilder, PredefinedMenuItem};
 
+            let jobs = Jobs::new(app.handle().clone());
+            app.manage(CaptureProviders::detect(app.handle()).with_jobs(jobs.clone()));
+            app.manage(jobs);
+
             // Enable autostart by default only for release builds.
             // In dev/debug this can create hidden background agents that keep
             // global shortcuts active after closing the terminal session.
@@ -129,6 +156,9 @@ pub fn run() {
             let capture_screen_item =
                 MenuItemBuilder::with_id("capture_screen", "Capture Screen").build(app)?;
 
+            let capture_desktop_item =
+                MenuItemBuilder::with_id("capture_desktop", "Capture All Displays").build(app)?;
+
             let capture_window_item =
                 MenuItemBuilder::with_id("capture_window", "Capture Window").build(app)?;
 
@@ -149,6 +179,7 @@ pub fn run() {
                     &PredefinedMenuItem::separator(app)?,
                     &capture_region_item,
                     &capture_screen_item,
+                    &capture_desktop_item,
                     &capture_window_item,
                     &capture_ocr_item,
                     &PredefinedMenuItem::separator(app)?,
@@ -173,6 +204,9 @@ pub fn run() {
                     "capture_screen" => {
                         let _ = app.emit("capture-fullscreen", ());
                     }
+                    "capture_desktop" => {
+                        let _ = app.emit("capture-desktop", ());
+                    }
                     "capture_window" => {
                         let _ = app.emit("capture-window", ());
                     }
@@ -198,6 +232,7 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             capture_once,
             capture_all_monitors,
+            capture_virtual_desktop,
             capture_rect_frame,
             capture_rect_ocr,
             capture_region,
@@ -205,14 +240,23 @@ pub fn run() {
             request_screen_permission,
             open_screen_recording_settings,
             list_capture_windows,
+            start_scroll_session,
             poll_scroll_region,
-            reset_scroll_monitor,
             save_edited_image,
+            save_edited_image_bytes,
             stitch_scroll_frames,
             stitch_scroll_frames_preview,
             cleanup_scroll_temp,
+            cancel_job,
+            list_scroll_sessions,
+            resume_scroll_session,
+            gc_scroll_sessions,
             validate_save_directory,
             render_image_with_effects_rust,
+            render_image_bytes,
+            save_project,
+            load_project,
+            flatten_project,
             get_desktop_directory,
             get_temp_directory,
             native_capture_interactive,
@@ -228,12 +272,15 @@ pub fn run() {
         .build(tauri::generate_context!())
         .expect("error while building tauri application");
 
-    app.run(|app_handle, event| match event {
-        RunEvent::Reopen { .. } | RunEvent::Opened { .. } => {
+    // Reopen and Opened are only sent on macOS.
+    app.run(|app_handle, event| {
+        #[cfg(target_os = "macos")]
+        if let RunEvent::Reopen { .. } | RunEvent::Opened { .. } = event {
             if let Err(e) = show_main_window(app_handle) {
                 eprintln!("Failed to show window on app activation: {}", e);
             }
         }
-        _ => {}
+        #[cfg(not(target_os = "macos"))]
+        let _ = (app_handle, event);
     });
 }
@@ -1,6 +1,7 @@
 //! OCR module using macOS Vision framework
 
 use crate::utils::AppResult;
+use capture_core::{CaptureError, CaptureErrorKind};
 
 #[cfg(target_os = "macos")]
 pub fn recognize_text_from_image(image_path: &str) -> AppResult<String> {
@@ -17,7 +18,10 @@ pub fn recognize_text_from_image(image_path: &str) -> AppResult<String> {
 
     let path = Path::new(image_path);
     if !path.exists() {
-        return Err(format!("Image file does not exist: {}", image_path));
+        return Err(CaptureError::io(format!(
+            "Image file does not exist: {}",
+            image_path
+        )));
     }
 
     autoreleasepool(|_| unsafe {
@@ -60,7 +64,7 @@ pub fn recognize_text_from_image(image_path: &str) -> AppResult<String> {
 
         handler
             .performRequests_error(&requests)
-            .map_err(|e| format!("Vision request failed: {:?}", e))?;
+            .map_err(|e| CaptureError::command(format!("Vision request failed: {:?}", e)))?;
 
         let observations = text_request.results();
         let mut recognized_texts = Vec::new();
@@ -88,7 +92,10 @@ pub fn recognize_text_from_image(image_path: &str) -> AppResult<String> {
         }
 
         if recognized_texts.is_empty() {
-            return Err("No text recognized in image".to_string());
+            return Err(CaptureError::new(
+                CaptureErrorKind::OcrEmpty,
+                "No text recognized in image",
+            ));
         }
 
         Ok(recognized_texts.join("\n"))
@@ -97,5 +104,8 @@ pub fn recognize_text_from_image(image_path: &str) -> AppResult<String> {
 
 #[cfg(not(target_os = "macos"))]
 pub fn recognize_text_from_image(_image_path: &str) -> AppResult<String> {
-    Err("OCR is only supported on macOS".to_string())
+    Err(CaptureError::new(
+        CaptureErrorKind::Unsupported,
+        "OCR is only supported on macOS",
+    ))
 }
@@ -0,0 +1,545 @@
+//! `.ashot` project documents
+//!
+//! A project keeps an edit re-editable: a zip holding the original capture,
+//! the annotations and the `RenderSettings`, so it can be reopened and
+//! flattened again later instead of only keeping the rendered image.
+//!
+//! | Entry              | Contents                                         |
+//! |--------------------|--------------------------------------------------|
+//! | `manifest.json`    | `ProjectManifest`: format, schema version, times |
+//! | `capture.png`      | The original capture, always PNG                 |
+//! | `annotations.json` | The editor's `Annotation` list                   |
+//! | `settings.json`    | `RenderSettings`, without annotations            |
+//! | `editor.json`      | Optional frontend state, stored as given         |
+//!
+//! `schemaVersion` is bumped whenever an entry changes incompatibly. Only
+//! the current version is read for now; upgrades from older ones go in
+//! `check_manifest` once there are any. Newer versions are refused with an
+//! `unsupported` error rather than misread.
+
+use capture_core::{CaptureError, CaptureErrorKind};
+use image::{ImageFormat, ImageReader, RgbaImage};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{Cursor, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use zip::result::ZipError;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::annotations::Annotation;
+use crate::image::{encode_image, render_screenshot, ExportFormat, RenderSettings};
+use crate::utils::{ensure_dir, generate_filename, get_timestamp, AppResult};
+
+/// Extension of project files.
+pub const PROJECT_EXTENSION: &str = "ashot";
+/// Schema version written by this build.
+pub const PROJECT_SCHEMA_VERSION: u32 = 1;
+const PROJECT_FORMAT: &str = "ashot";
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const CAPTURE_ENTRY: &str = "capture.png";
+const ANNOTATIONS_ENTRY: &str = "annotations.json";
+const SETTINGS_ENTRY: &str = "settings.json";
+const EDITOR_ENTRY: &str = "editor.json";
+/// Largest entry read back, so a damaged file cannot exhaust memory.
+const MAX_ENTRY_BYTES: u64 = 512 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectManifest {
+    pub format: String,
+    pub schema_version: u32,
+    /// Milliseconds since the Unix epoch.
+    pub created_at: u64,
+    pub modified_at: u64,
+    /// Version of the app that last saved the project.
+    pub app_version: String,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+/// A project in memory.
+#[derive(Debug, Clone)]
+pub struct ProjectDocument {
+    pub manifest: ProjectManifest,
+    /// The original capture, PNG encoded.
+    pub capture_png: Vec<u8>,
+    pub annotations: Vec<Annotation>,
+    pub settings: RenderSettings,
+    pub editor_state: Option<serde_json::Value>,
+}
+
+impl ProjectDocument {
+    /// A new project for the capture at `capture_path`. Captures in other
+    /// formats are converted to PNG. Annotations left in `settings` are
+    /// moved to the project's own list.
+    pub fn new(
+        capture_path: &Path,
+        mut annotations: Vec<Annotation>,
+        mut settings: RenderSettings,
+        editor_state: Option<serde_json::Value>,
+        title: Option<String>,
+    ) -> AppResult<Self> {
+        let bytes = fs::read(capture_path)
+            .map_err(|e| CaptureError::io(format!("Failed to read capture: {}", e)))?;
+        let capture_png = match image::guess_format(&bytes) {
+            Ok(ImageFormat::Png) => bytes,
+            _ => {
+                let decoded = image::load_from_memory(&bytes)
+                    .map_err(|e| CaptureError::io(format!("Failed to decode capture: {}", e)))?;
+                encode_image(&decoded.to_rgba8(), ExportFormat::Png, None)?
+            }
+        };
+        let (width, height) = ImageReader::with_format(Cursor::new(&capture_png), ImageFormat::Png)
+            .into_dimensions()
+            .map_err(|e| CaptureError::io(format!("Failed to read capture size: {}", e)))?;
+
+        annotations.append(&mut settings.annotations);
+        let now = get_timestamp()?;
+        Ok(Self {
+            manifest: ProjectManifest {
+                format: PROJECT_FORMAT.to_string(),
+                schema_version: PROJECT_SCHEMA_VERSION,
+                created_at: now,
+                modified_at: now,
+                app_version: env!("CARGO_PKG_VERSION").to_string(),
+                width,
+                height,
+                title,
+            },
+            capture_png,
+            annotations,
+            settings,
+            editor_state,
+        })
+    }
+
+    /// Read the project at `path`.
+    pub fn read(path: &Path) -> AppResult<Self> {
+        let mut archive = open_archive(path)?;
+        let manifest = read_manifest_entry(&mut archive)?;
+        let settings: RenderSettings = read_json(&mut archive, SETTINGS_ENTRY)?;
+        let annotations: Vec<Annotation> = read_json(&mut archive, ANNOTATIONS_ENTRY)?;
+        let editor_state = read_optional_entry(&mut archive, EDITOR_ENTRY)?
+            .map(|bytes| parse_json(EDITOR_ENTRY, &bytes))
+            .transpose()?;
+        let capture_png = read_entry(&mut archive, CAPTURE_ENTRY)?;
+
+        Ok(Self {
+            manifest,
+            capture_png,
+            annotations,
+            settings,
+            editor_state,
+        })
+    }
+
+    /// Write the project to `path`, replacing it only once fully written.
+    pub fn write(&self, path: &Path) -> AppResult<()> {
+        let partial = path.with_extension(format!("{}.partial", PROJECT_EXTENSION));
+        let result = File::create(&partial)
+            .map_err(|e| CaptureError::io(format!("Failed to create project: {}", e)))
+            .and_then(|file| self.write_to(file))
+            .and_then(|_| {
+                fs::rename(&partial, path)
+                    .map_err(|e| CaptureError::io(format!("Failed to save project: {}", e)))
+            });
+        if result.is_err() {
+            let _ = fs::remove_file(&partial);
+        }
+        result
+    }
+
+    fn write_to<W: Write + Seek>(&self, writer: W) -> AppResult<()> {
+        let mut zip = ZipWriter::new(writer);
+        let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        // PNG is compressed already.
+        let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+        let mut settings = self.settings.clone();
+        settings.annotations.clear();
+
+        write_entry(
+            &mut zip,
+            MANIFEST_ENTRY,
+            deflated,
+            &to_json(&self.manifest)?,
+        )?;
+        write_entry(&mut zip, CAPTURE_ENTRY, stored, &self.capture_png)?;
+        write_entry(
+            &mut zip,
+            ANNOTATIONS_ENTRY,
+            deflated,
+            &to_json(&self.annotations)?,
+        )?;
+        write_entry(&mut zip, SETTINGS_ENTRY, deflated, &to_json(&settings)?)?;
+        if let Some(editor_state) = &self.editor_state {
+            write_entry(&mut zip, EDITOR_ENTRY, deflated, &to_json(editor_state)?)?;
+        }
+        zip.finish()
+            .map_err(|e| CaptureError::io(format!("Failed to write project: {}", e)))?;
+        Ok(())
+    }
+
+    /// Render the capture with its settings and annotations.
+    pub fn flatten(&self) -> AppResult<RgbaImage> {
+        let capture = image::load_from_memory_with_format(&self.capture_png, ImageFormat::Png)
+            .map_err(|e| CaptureError::io(format!("Failed to decode capture: {}", e)))?
+            .to_rgba8();
+        let mut settings = self.settings.clone();
+        settings.annotations = self.annotations.clone();
+        render_screenshot(&capture, &settings)
+    }
+
+    /// Write the capture to `dir` so the editor can open it by path.
+    pub fn extract_capture(&self, dir: &Path) -> AppResult<PathBuf> {
+        let dir = dir.to_path_buf();
+        ensure_dir(&dir)?;
+        let path = dir.join(generate_filename("project_capture", "png")?);
+        fs::write(&path, &self.capture_png)
+            .map_err(|e| CaptureError::io(format!("Failed to extract capture: {}", e)))?;
+        Ok(path)
+    }
+}
+
+/// Save `document` to `path`, or to a new file in `save_dir` when `path` is
+/// `None`. Saving over an existing project keeps its creation time.
+pub fn save_project(
+    mut document: ProjectDocument,
+    path: Option<&Path>,
+    save_dir: &Path,
+) -> AppResult<PathBuf> {
+    let path = match path {
+        Some(path) => {
+            if let Ok(existing) = read_manifest(path) {
+                document.manifest.created_at = existing.created_at;
+            }
+            path.to_path_buf()
+        }
+        None => {
+            let save_dir = save_dir.to_path_buf();
+            ensure_dir(&save_dir)?;
+            save_dir.join(generate_filename("bettershot", PROJECT_EXTENSION)?)
+        }
+    };
+    document.manifest.modified_at = get_timestamp()?;
+    document.write(&path)?;
+    Ok(path)
+}
+
+/// Read only the manifest of the project at `path`.
+fn read_manifest(path: &Path) -> AppResult<ProjectManifest> {
+    read_manifest_entry(&mut open_archive(path)?)
+}
+
+fn open_archive(path: &Path) -> AppResult<ZipArchive<File>> {
+    let file =
+        File::open(path).map_err(|e| CaptureError::io(format!("Failed to open project: {}", e)))?;
+    ZipArchive::new(file).map_err(invalid_archive)
+}
+
+fn read_manifest_entry<R: Read + Seek>(archive: &mut ZipArchive<R>) -> AppResult<ProjectManifest> {
+    let manifest: ProjectManifest = read_json(archive, MANIFEST_ENTRY)?;
+    check_manifest(&manifest)?;
+    Ok(manifest)
+}
+
+fn check_manifest(manifest: &ProjectManifest) -> AppResult<()> {
+    if manifest.format != PROJECT_FORMAT {
+        return Err(CaptureError::validation(format!(
+            "Not a project file (format '{}')",
+            manifest.format
+        )));
+    }
+    match manifest.schema_version {
+        // Upgrades from older versions go here as the schema evolves.
+        PROJECT_SCHEMA_VERSION => Ok(()),
+        version if version > PROJECT_SCHEMA_VERSION => Err(CaptureError::new(
+            CaptureErrorKind::Unsupported,
+            "Project was saved by a newer version of the app",
+        )
+        .with_detail("schema_version", version.to_string())
+        .with_detail("supported", PROJECT_SCHEMA_VERSION.to_string())),
+        version => Err(CaptureError::validation(format!(
+            "Unknown project schema version {}",
+            version
+        ))),
+    }
+}
+
+fn invalid_archive(error: ZipError) -> CaptureError {
+    match error {
+        ZipError::Io(e) => CaptureError::io(format!("Failed to read project: {}", e)),
+        e => CaptureError::validation(format!("Project file is damaged: {}", e)),
+    }
+}
+
+/// Read an entry; missing entries are a validation error.
+fn read_entry<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str) -> AppResult<Vec<u8>> {
+    read_optional_entry(archive, name)?.ok_or_else(|| {
+        CaptureError::validation(format!("Project is missing {}", name)).with_detail("entry", name)
+    })
+}
+
+/// Read an entry, or `None` if the project has no entry called `name`.
+fn read_optional_entry<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+) -> AppResult<Option<Vec<u8>>> {
+    let entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(invalid_archive(e)),
+    };
+    if entry.size() > MAX_ENTRY_BYTES {
+        return Err(
+            CaptureError::validation(format!("Project entry {} is too large", name))
+                .with_detail("entry", name),
+        );
+    }
+
+    let mut bytes = Vec::with_capacity(entry.size() as usize);
+    entry
+        .take(MAX_ENTRY_BYTES)
+        .read_to_end(&mut bytes)
+        .map_err(|e| CaptureError::io(format!("Failed to read {}: {}", name, e)))?;
+    Ok(Some(bytes))
+}
+
+fn read_json<R: Read + Seek, T: DeserializeOwned>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+) -> AppResult<T> {
+    parse_json(name, &read_entry(archive, name)?)
+}
+
+fn parse_json<T: DeserializeOwned>(name: &str, bytes: &[u8]) -> AppResult<T> {
+    serde_json::from_slice(bytes).map_err(|e| {
+        CaptureError::validation(format!("Invalid {} in project: {}", name, e))
+            .with_detail("entry", name)
+    })
+}
+
+fn to_json<T: Serialize>(value: &T) -> AppResult<Vec<u8>> {
+    serde_json::to_vec_pretty(value)
+        .map_err(|e| CaptureError::io(format!("Failed to serialize project: {}", e)))
+}
+
+fn write_entry<W: Write + Seek>(
+    zip: &mut ZipWriter<W>,
+    name: &str,
+    options: SimpleFileOptions,
+    bytes: &[u8],
+) -> AppResult<()> {
+    zip.start_file(name, options)
+        .and_then(|_| zip.write_all(bytes).map_err(ZipError::Io))
+        .map_err(|e| CaptureError::io(format!("Failed to write {}: {}", name, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage, Rgba};
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("ashot-project-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn settings() -> RenderSettings {
+        RenderSettings {
+            background_type: "custom".to_string(),
+            custom_color: "#334455".to_string(),
+            border_radius: 4.0,
+            padding_top: 10,
+            padding_bottom: 10,
+            padding_left: 10,
+            padding_right: 10,
+            ..RenderSettings::test_default()
+        }
+    }
+
+    fn rectangle() -> Annotation {
+        serde_json::from_value(serde_json::json!({
+            "type": "rectangle",
+            "id": "r1",
+            "x": 12.0,
+            "y": 12.0,
+            "width": 10.0,
+            "height": 8.0,
+            "fill": { "hex": "#ff0000", "opacity": 100 },
+            "border": { "width": 2, "color": { "hex": "#ff0000", "opacity": 100 } },
+            "alignment": { "horizontal": "left", "vertical": "top" },
+        }))
+        .unwrap()
+    }
+
+    fn capture(dir: &TempDir, format: ImageFormat) -> PathBuf {
+        let path = dir
+            .0
+            .join(format!("capture.{}", format.extensions_str()[0]));
+        // JPEG has no alpha channel.
+        RgbImage::from_pixel(30, 20, Rgb([0, 128, 255]))
+            .save_with_format(&path, format)
+            .unwrap();
+        path
+    }
+
+    fn document(dir: &TempDir) -> ProjectDocument {
+        ProjectDocument::new(
+            &capture(dir, ImageFormat::Png),
+            vec![rectangle()],
+            settings(),
+            Some(serde_json::json!({ "gradientId": "mesh-3" })),
+            Some("Docs hero".to_string()),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_projects_round_trip() {
+        let dir = TempDir::new("round-trip");
+        let document = document(&dir);
+        let path = save_project(document.clone(), None, &dir.0).unwrap();
+        assert_eq!(path.extension().unwrap(), PROJECT_EXTENSION);
+
+        let loaded = ProjectDocument::read(&path).unwrap();
+        assert_eq!(loaded.manifest.schema_version, PROJECT_SCHEMA_VERSION);
+        assert_eq!(loaded.manifest.title.as_deref(), Some("Docs hero"));
+        assert_eq!((loaded.manifest.width, loaded.manifest.height), (30, 20));
+        assert_eq!(loaded.capture_png, document.capture_png);
+        assert_eq!(loaded.annotations, vec![rectangle()]);
+        assert_eq!(loaded.settings.custom_color, "#334455");
+        assert_eq!(loaded.editor_state, document.editor_state);
+
+        let extracted = loaded.extract_capture(&dir.0).unwrap();
+        assert_eq!(fs::read(extracted).unwrap(), document.capture_png);
+    }
+
+    #[test]
+    fn test_resaving_keeps_the_creation_time() {
+        let dir = TempDir::new("resave");
+        let path = save_project(document(&dir), None, &dir.0).unwrap();
+        let first = ProjectDocument::read(&path).unwrap().manifest;
+
+        let mut edited = document(&dir);
+        edited.manifest.created_at = 0;
+        edited.annotations.clear();
+        save_project(edited, Some(&path), &dir.0).unwrap();
+
+        let second = ProjectDocument::read(&path).unwrap();
+        assert_eq!(second.manifest.created_at, first.created_at);
+        assert!(second.manifest.modified_at >= first.modified_at);
+        assert!(second.annotations.is_empty());
+        assert!(!path.with_extension("ashot.partial").exists());
+    }
+
+    #[test]
+    fn test_captures_are_stored_as_png() {
+        let dir = TempDir::new("jpeg");
+        let document = ProjectDocument::new(
+            &capture(&dir, ImageFormat::Jpeg),
+            Vec::new(),
+            settings(),
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            image::guess_format(&document.capture_png).unwrap(),
+            ImageFormat::Png
+        );
+    }
+
+    #[test]
+    fn test_annotations_in_settings_move_to_the_project() {
+        let dir = TempDir::new("settings-annotations");
+        let mut with_annotations = settings();
+        with_annotations.annotations = vec![rectangle()];
+        let document = ProjectDocument::new(
+            &capture(&dir, ImageFormat::Png),
+            Vec::new(),
+            with_annotations,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(document.annotations.len(), 1);
+        assert!(document.settings.annotations.is_empty());
+    }
+
+    #[test]
+    fn test_flatten_renders_effects_and_annotations() {
+        let dir = TempDir::new("flatten");
+        let image = document(&dir).flatten().unwrap();
+        assert_eq!(image.dimensions(), (50, 40));
+        assert_eq!(image.get_pixel(2, 2), &Rgba([0x33, 0x44, 0x55, 255]));
+        assert_eq!(image.get_pixel(25, 25), &Rgba([0, 128, 255, 255]));
+        // The rectangle's top edge.
+        let edge = image.get_pixel(17, 12);
+        assert!(edge[0] > 200 && edge[2] < 80, "{:?}", edge);
+    }
+
+    fn write_raw(path: &Path, entries: &[(&str, Vec<u8>)]) {
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+        for (name, bytes) in entries {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(bytes).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_newer_schema_versions_are_refused() {
+        let dir = TempDir::new("newer");
+        let mut manifest = document(&dir).manifest;
+        manifest.schema_version = PROJECT_SCHEMA_VERSION + 1;
+        let path = dir.0.join("future.ashot");
+        write_raw(&path, &[(MANIFEST_ENTRY, to_json(&manifest).unwrap())]);
+
+        let error = ProjectDocument::read(&path).unwrap_err();
+        assert_eq!(error.kind, CaptureErrorKind::Unsupported);
+        assert_eq!(error.detail("schema_version"), Some("2"));
+    }
+
+    #[test]
+    fn test_damaged_projects_are_reported() {
+        let dir = TempDir::new("damaged");
+        let manifest = document(&dir).manifest;
+
+        let missing = dir.0.join("missing.ashot");
+        write_raw(&missing, &[(MANIFEST_ENTRY, to_json(&manifest).unwrap())]);
+        let error = ProjectDocument::read(&missing).unwrap_err();
+        assert_eq!(error.kind, CaptureErrorKind::ValidationFailed);
+        assert_eq!(error.detail("entry"), Some(SETTINGS_ENTRY));
+
+        let not_zip = dir.0.join("not-zip.ashot");
+        fs::write(&not_zip, b"definitely not a zip").unwrap();
+        let error = ProjectDocument::read(&not_zip).unwrap_err();
+        assert_eq!(error.kind, CaptureErrorKind::ValidationFailed);
+
+        let foreign = dir.0.join("foreign.ashot");
+        let mut other = manifest;
+        other.format = "other".to_string();
+        write_raw(&foreign, &[(MANIFEST_ENTRY, to_json(&other).unwrap())]);
+        let error = ProjectDocument::read(&foreign).unwrap_err();
+        assert_eq!(error.kind, CaptureErrorKind::ValidationFailed);
+    }
+}
@@ -1,10 +1,13 @@
 //! Screenshot capture module
 
+use capture_core::composite::DesktopLayout;
+use capture_core::provider::MonitorInfo;
+use capture_core::{CaptureError, CaptureErrorKind};
+use image::RgbaImage;
 use serde::Serialize;
-use std::path::PathBuf;
-use xcap::Monitor;
+use std::path::{Path, PathBuf};
 
-use crate::utils::{ensure_dir, generate_filename_with_id, AppResult};
+use crate::utils::{ensure_dir, generate_filename, generate_filename_with_id, AppResult};
 
 /// Represents a captured monitor screenshot with geometry info
 #[derive(Serialize, Clone, Debug)]
@@ -18,97 +21,87 @@ pub struct MonitorShot {
     pub path: String,
 }
 
-/// Capture screenshots of all available monitors
-pub fn capture_all_monitors(save_dir: &str) -> AppResult<Vec<MonitorShot>> {
-    let monitors = Monitor::all().map_err(|e| format!("Failed to get monitors: {}", e))?;
-
-    if monitors.is_empty() {
-        return Err("No monitors available".into());
-    }
+/// A composite of the whole virtual desktop and its layout map
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct VirtualDesktopShot {
+    pub path: String,
+    /// JSON copy of `layout` saved next to the image
+    pub layout_path: String,
+    pub layout: DesktopLayout,
+}
 
+/// Save a desktop composite as `desktop_{timestamp}.png` with its layout
+/// map beside it as `desktop_{timestamp}.json`
+pub fn save_virtual_desktop(
+    image: &RgbaImage,
+    layout: DesktopLayout,
+    save_dir: &str,
+) -> AppResult<VirtualDesktopShot> {
     let save_path = PathBuf::from(save_dir);
     ensure_dir(&save_path)?;
 
-    let mut shots = Vec::with_capacity(monitors.len());
-
-    for monitor in monitors {
-        let shot = capture_single_monitor(&monitor, &save_path)?;
-        shots.push(shot);
-    }
+    let image_path = save_path.join(generate_filename("desktop", "png")?);
+    let layout_path = image_path.with_extension("json");
 
-    Ok(shots)
+    image
+        .save(&image_path)
+        .map_err(|e| CaptureError::io(format!("Failed to save screenshot: {}", e)))?;
+    let json = serde_json::to_vec_pretty(&layout)
+        .map_err(|e| CaptureError::io(format!("Failed to encode desktop layout: {}", e)))?;
+    std::fs::write(&layout_path, json)
+        .map_err(|e| CaptureError::io(format!("Failed to save desktop layout: {}", e)))?;
+
+    Ok(VirtualDesktopShot {
+        path: image_path.to_string_lossy().into_owned(),
+        layout_path: layout_path.to_string_lossy().into_owned(),
+        layout,
+    })
 }
 
-/// Capture a single monitor screenshot
-fn capture_single_monitor(monitor: &Monitor, save_path: &PathBuf) -> AppResult<MonitorShot> {
-    let monitor_id = monitor
-        .id()
-        .map_err(|e| format!("Failed to get monitor id: {}", e))?;
+/// Save monitor captures to `save_dir`, one file per monitor
+pub fn save_monitor_shots(
+    captures: Vec<(MonitorInfo, RgbaImage)>,
+    save_dir: &str,
+) -> AppResult<Vec<MonitorShot>> {
+    if captures.is_empty() {
+        return Err(CaptureError::new(
+            CaptureErrorKind::CaptureFailed,
+            "No monitors available",
+        ));
+    }
+
+    let save_path = PathBuf::from(save_dir);
+    ensure_dir(&save_path)?;
 
-    // Capture the screenshot
-    let image = monitor
-        .capture_image()
-        .map_err(|e| format!("Failed to capture monitor {}: {}", monitor_id, e))?;
+    captures
+        .into_iter()
+        .map(|(monitor, image)| save_monitor_shot(&monitor, &image, &save_path))
+        .collect()
+}
 
+/// Save a single monitor screenshot
+fn save_monitor_shot(
+    monitor: &MonitorInfo,
+    image: &RgbaImage,
+    save_path: &Path,
+) -> AppResult<MonitorShot> {
     // Generate unique filename
-    let filename = generate_filename_with_id("monitor", monitor_id, "png")?;
+    let filename = generate_filename_with_id("monitor", monitor.id, "png")?;
     let screenshot_path = save_path.join(&filename);
 
     // Save the image
     image
         .save(&screenshot_path)
-        .map_err(|e| format!("Failed to save screenshot: {}", e))?;
-
-    // Get monitor geometry
-    let x = monitor
-        .x()
-        .map_err(|e| format!("Failed to get monitor x: {}", e))?;
-    let y = monitor
-        .y()
-        .map_err(|e| format!("Failed to get monitor y: {}", e))?;
-    let width = monitor
-        .width()
-        .map_err(|e| format!("Failed to get monitor width: {}", e))?;
-    let height = monitor
-        .height()
-        .map_err(|e| format!("Failed to get monitor height: {}", e))?;
-    let scale_factor = monitor
-        .scale_factor()
-        .map_err(|e| format!("Failed to get monitor scale factor: {}", e))?;
-
-    let logical_x = (x as f32 / scale_factor).round() as i32;
-    let logical_y = (y as f32 / scale_factor).round() as i32;
-    let logical_width = ((width as f32 / scale_factor).round().max(1.0)) as u32;
-    let logical_height = ((height as f32 / scale_factor).round().max(1.0)) as u32;
+        .map_err(|e| CaptureError::io(format!("Failed to save screenshot: {}", e)))?;
 
     Ok(MonitorShot {
-        id: monitor_id,
-        x: logical_x,
-        y: logical_y,
-        width: logical_width,
-        height: logical_height,
-        scale_factor,
+        id: monitor.id,
+        x: monitor.x,
+        y: monitor.y,
+        width: monitor.width,
+        height: monitor.height,
+        scale_factor: monitor.scale_factor,
         path: screenshot_path.to_string_lossy().into_owned(),
     })
 }
-
-/// Capture primary monitor using the screenshots plugin
-pub async fn capture_primary_monitor(app_handle: tauri::AppHandle) -> AppResult<PathBuf> {
-    use tauri_plugin_screenshots::{get_monitor_screenshot, get_screenshotable_monitors};
-
-    let monitors = get_screenshotable_monitors()
-        .await
-        .map_err(|e| format!("Failed to get monitors: {}", e))?;
-
-    if monitors.is_empty() {
-        return Err("No monitors available".into());
-    }
-
-    let primary_monitor = monitors.first().ok_or("No monitor found")?;
-
-    let screenshot_path = get_monitor_screenshot(app_handle, primary_monitor.id)
-        .await
-        .map_err(|e| format!("Failed to capture screenshot: {}", e))?;
-
-    Ok(screenshot_path)
-}
@@ -0,0 +1,1175 @@
+//! Scroll capture sessions
+//!
+//! A session owns its capture rect, temp directory, change-detection state
+//! and `Stitcher`. Sessions live in `ScrollSessions`, which is registered as
+//! Tauri managed state, and are addressed by the id returned from
+//! `start_scroll_session`, so several scroll captures can run side by side.
+//!
+//! Every session keeps a `session.json` manifest next to its frames (config,
+//! accepted frames with their offsets, state). If the app quits or crashes
+//! mid-capture, the next launch can list those sessions, resume one by
+//! replaying its frames, or garbage-collect stale ones. Frames and the
+//! manifest are written by a per-session writer thread, so polls never wait
+//! for PNG encoding or disk; the webview gets frames from the frame store.
+
+use capture_core::stitch::{
+    downscale_to_width, sample_frame_difference, ScrollBand, Stitcher, MIN_FRAME_DIFFERENCE,
+};
+use capture_core::{
+    AppendResult, CaptureError, CaptureErrorKind, ScrollConfig, ScrollProgress, ScrollSessionState,
+    StopReason,
+};
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::UNIX_EPOCH;
+
+use crate::commands::CaptureRect;
+use crate::frame_store::{encode_png_fast, FrameHandle, FrameStore};
+use crate::image::{encode_image, ExportFormat};
+use crate::jobs::JobContext;
+use crate::utils::{generate_filename, generate_filename_with_id, get_timestamp, AppResult};
+
+/// Maximum width of the live scroll preview image, in pixels.
+const SCROLL_PREVIEW_MAX_WIDTH: u32 = 680;
+const SESSION_DIR_PREFIX: &str = "bettershot-scroll-";
+const MANIFEST_FILE: &str = "session.json";
+const MANIFEST_VERSION: u32 = 1;
+/// Sessions left on disk for longer than this are garbage-collected.
+pub const STALE_SCROLL_SESSION_MS: u64 = 24 * 60 * 60 * 1000;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScrollPollResult {
+    /// "unchanged" | "scrolling" | "captured" | "skipped" | "stopped"
+    pub state: String,
+    /// The frame accepted by this poll. It replaces the session's previous
+    /// frame in the frame store.
+    pub frame: Option<FrameHandle>,
+    pub frame_count: usize,
+    pub progress: ScrollProgress,
+    /// Outcome of stitching the captured frame, if one was captured.
+    pub append: Option<AppendResult>,
+    /// Set once the session has stopped; the frontend should finish it.
+    pub stop_reason: Option<StopReason>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StitchResult {
+    pub path: String,
+    pub total_frames: usize,
+    pub used_frames: usize,
+    pub skipped_frames: usize,
+    pub final_height: u32,
+    pub progress: ScrollProgress,
+    pub stop_reason: Option<StopReason>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScrollSessionInfo {
+    pub session_id: String,
+    pub session_dir: String,
+    pub frames_dir: String,
+    pub progress: ScrollProgress,
+}
+
+/// A frame that added content, in stitching order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestFrame {
+    /// File name inside the session's frames directory.
+    pub file: String,
+    /// Canvas row (column for horizontal sessions) where its slice starts.
+    pub offset_px: u32,
+    /// Height of the slice it added.
+    pub dy: u32,
+    /// Match confidence, when the frame was not the one that stopped the
+    /// session.
+    pub score: Option<f64>,
+}
+
+/// On-disk record of a scroll session, rewritten after every change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScrollSessionManifest {
+    pub version: u32,
+    pub session_id: String,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub rect: CaptureRect,
+    pub config: ScrollConfig,
+    pub band: Option<ScrollBand>,
+    pub state: ScrollSessionState,
+    pub stop_reason: Option<StopReason>,
+    /// Frames stitched or skipped, including those not in `frames`.
+    #[serde(default)]
+    pub total_frames: usize,
+    #[serde(default)]
+    pub skipped_frames: usize,
+    pub frames: Vec<ManifestFrame>,
+}
+
+impl ScrollSessionManifest {
+    fn load(session_dir: &Path) -> AppResult<Self> {
+        let raw = fs::read(session_dir.join(MANIFEST_FILE)).map_err(|e| {
+            CaptureError::io(format!("Failed to read scroll session manifest: {}", e))
+        })?;
+        let manifest: Self = serde_json::from_slice(&raw).map_err(|e| {
+            CaptureError::validation(format!("Invalid scroll session manifest: {}", e))
+        })?;
+        if manifest.version > MANIFEST_VERSION {
+            return Err(CaptureError::new(
+                CaptureErrorKind::Unsupported,
+                format!(
+                    "Scroll session manifest version {} is not supported",
+                    manifest.version
+                ),
+            ));
+        }
+        Ok(manifest)
+    }
+
+    /// Write via a temp file so a crash never leaves a truncated manifest.
+    fn save(&self, session_dir: &Path) -> AppResult<()> {
+        let raw = serde_json::to_vec_pretty(self).map_err(|e| {
+            CaptureError::io(format!("Failed to encode scroll session manifest: {}", e))
+        })?;
+        let tmp_path = session_dir.join(format!("{}.tmp", MANIFEST_FILE));
+        fs::write(&tmp_path, raw).map_err(|e| {
+            CaptureError::io(format!("Failed to write scroll session manifest: {}", e))
+        })?;
+        fs::rename(&tmp_path, session_dir.join(MANIFEST_FILE)).map_err(|e| {
+            CaptureError::io(format!("Failed to write scroll session manifest: {}", e))
+        })
+    }
+}
+
+/// Work for a session's writer thread.
+enum WriteTask {
+    Frame {
+        path: PathBuf,
+        image: Arc<RgbaImage>,
+    },
+    Manifest(Box<ScrollSessionManifest>),
+    Flush(mpsc::Sender<()>),
+}
+
+/// Writes a session's frames and manifest on a background thread. Tasks run
+/// in order, so the manifest never lists a frame before its file exists.
+/// The first failure is kept and returned by the next call.
+struct SessionWriter {
+    tasks: Option<mpsc::Sender<WriteTask>>,
+    thread: Option<JoinHandle<()>>,
+    error: Arc<Mutex<Option<CaptureError>>>,
+}
+
+impl SessionWriter {
+    fn spawn(session_dir: PathBuf) -> AppResult<Self> {
+        let (tasks, receiver) = mpsc::channel();
+        let error = Arc::new(Mutex::new(None));
+        let failed = Arc::clone(&error);
+        let thread = std::thread::Builder::new()
+            .name("scroll-session-writer".to_string())
+            .spawn(move || {
+                for task in receiver {
+                    let result = match task {
+                        WriteTask::Frame { path, image } => write_frame(&path, &image),
+                        WriteTask::Manifest(manifest) => manifest.save(&session_dir),
+                        WriteTask::Flush(done) => {
+                            let _ = done.send(());
+                            continue;
+                        }
+                    };
+                    if let (Err(e), Ok(mut slot)) = (result, failed.lock()) {
+                        slot.get_or_insert(e);
+                    }
+                }
+            })
+            .map_err(|e| {
+                CaptureError::io(format!("Failed to start scroll session writer: {}", e))
+            })?;
+        Ok(Self {
+            tasks: Some(tasks),
+            thread: Some(thread),
+            error,
+        })
+    }
+
+    fn send(&self, task: WriteTask) -> AppResult<()> {
+        self.take_error()?;
+        self.tasks
+            .as_ref()
+            .and_then(|tasks| tasks.send(task).ok())
+            .ok_or_else(|| CaptureError::io("Scroll session writer has stopped"))
+    }
+
+    /// Wait until everything queued so far is on disk.
+    fn flush(&self) -> AppResult<()> {
+        let (done, written) = mpsc::channel();
+        self.send(WriteTask::Flush(done))?;
+        let _ = written.recv();
+        self.take_error()
+    }
+
+    fn take_error(&self) -> AppResult<()> {
+        let mut slot = self.error.lock().map_err(|e| {
+            CaptureError::command(format!("Failed to acquire scroll writer lock: {}", e))
+        })?;
+        slot.take().map_or(Ok(()), Err)
+    }
+}
+
+impl Drop for SessionWriter {
+    /// Let queued writes finish so the session on disk is complete.
+    fn drop(&mut self) {
+        self.tasks.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn write_frame(path: &Path, image: &RgbaImage) -> AppResult<()> {
+    fs::write(path, encode_png_fast(image)?)
+        .map_err(|e| CaptureError::io(format!("Failed to save scroll frame: {}", e)))
+}
+
+/// A session left on disk by an earlier run.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoverableScrollSession {
+    pub session_id: String,
+    pub session_dir: String,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub frame_count: usize,
+    pub captured_height_px: u32,
+    pub state: ScrollSessionState,
+    pub stop_reason: Option<StopReason>,
+}
+
+/// One scroll capture.
+/// The frontend polls at ~200ms intervals; this state determines
+/// whether content is scrolling or has stabilized.
+pub struct ScrollCaptureSession {
+    manifest: ScrollSessionManifest,
+    session_dir: PathBuf,
+    frames_dir: PathBuf,
+    /// Previous frame for comparison
+    prev_frame: Option<Arc<RgbaImage>>,
+    /// Was content scrolling last poll?
+    was_scrolling: bool,
+    /// Number of consecutive stable polls
+    stable_count: u32,
+    /// Total frames captured in this session
+    frame_count: usize,
+    /// Stitched canvas, extended with every captured frame
+    stitcher: Stitcher,
+    /// Latest preview in the frame store
+    preview_frame: Option<String>,
+    /// Latest accepted frame in the frame store
+    latest_frame: Option<String>,
+    writer: SessionWriter,
+}
+
+impl ScrollCaptureSession {
+    fn new(
+        id: String,
+        rect: CaptureRect,
+        session_dir: PathBuf,
+        config: ScrollConfig,
+    ) -> AppResult<Self> {
+        let now = get_timestamp()?;
+        let stitcher = Stitcher::new(config);
+        let manifest = ScrollSessionManifest {
+            version: MANIFEST_VERSION,
+            session_id: id,
+            created_at: now,
+            updated_at: now,
+            rect,
+            config,
+            band: None,
+            state: stitcher.progress().state,
+            stop_reason: None,
+            total_frames: 0,
+            skipped_frames: 0,
+            frames: Vec::new(),
+        };
+        Self::with_stitcher(manifest, session_dir, stitcher, None)
+    }
+
+    /// Rebuild a session from its manifest by replaying the accepted frames,
+    /// then restore its state, stop reason and frame counts.
+    /// `on_frame` is told how many of them have been read so far and can
+    /// abort by returning an error.
+    fn restore(
+        manifest: ScrollSessionManifest,
+        session_dir: PathBuf,
+        mut on_frame: impl FnMut(u64, u64) -> AppResult<()>,
+    ) -> AppResult<Self> {
+        let frames_dir = session_dir.join("frames");
+        let total = manifest.frames.len() as u64;
+        let mut frames = Vec::with_capacity(manifest.frames.len());
+        for entry in &manifest.frames {
+            if Path::new(&entry.file).file_name() != Some(OsStr::new(&entry.file)) {
+                return Err(CaptureError::validation(format!(
+                    "Invalid scroll frame name '{}'",
+                    entry.file
+                )));
+            }
+            let frame = image::open(frames_dir.join(&entry.file))
+                .map_err(|e| {
+                    CaptureError::io(format!(
+                        "Failed to read scroll frame '{}': {}",
+                        entry.file, e
+                    ))
+                })?
+                .to_rgba8();
+            frames.push((frame, entry.dy));
+            on_frame(frames.len() as u64, total)?;
+        }
+        let last_frame = frames.last().map(|(frame, _)| Arc::new(frame.clone()));
+        let mut stitcher = Stitcher::restore(manifest.config, manifest.band, frames)?;
+        stitcher.restore_status(
+            manifest.state,
+            manifest.stop_reason,
+            manifest.total_frames,
+            manifest.skipped_frames,
+        )?;
+        Self::with_stitcher(manifest, session_dir, stitcher, last_frame)
+    }
+
+    fn with_stitcher(
+        manifest: ScrollSessionManifest,
+        session_dir: PathBuf,
+        stitcher: Stitcher,
+        prev_frame: Option<Arc<RgbaImage>>,
+    ) -> AppResult<Self> {
+        let frames_dir = session_dir.join("frames");
+        let frame_count = manifest.total_frames.max(manifest.frames.len());
+        let writer = SessionWriter::spawn(session_dir.clone())?;
+        Ok(Self {
+            manifest,
+            session_dir,
+            frames_dir,
+            prev_frame,
+            was_scrolling: false,
+            stable_count: 0,
+            frame_count,
+            stitcher,
+            preview_frame: None,
+            latest_frame: None,
+            writer,
+        })
+    }
+
+    pub fn rect(&self) -> &CaptureRect {
+        &self.manifest.rect
+    }
+
+    pub fn session_dir(&self) -> &Path {
+        &self.session_dir
+    }
+
+    pub fn frames_dir(&self) -> &Path {
+        &self.frames_dir
+    }
+
+    pub fn stitcher(&self) -> &Stitcher {
+        &self.stitcher
+    }
+
+    pub fn preview_frame(&self) -> Option<&str> {
+        self.preview_frame.as_deref()
+    }
+
+    pub fn set_preview_frame(&mut self, id: String) {
+        self.preview_frame = Some(id);
+    }
+
+    pub fn info(&self) -> ScrollSessionInfo {
+        ScrollSessionInfo {
+            session_id: self.manifest.session_id.clone(),
+            session_dir: self.session_dir.to_string_lossy().into_owned(),
+            frames_dir: self.frames_dir.to_string_lossy().into_owned(),
+            progress: self.stitcher.progress(),
+        }
+    }
+
+    /// A "stopped" poll result once the session has hit a limit or timed
+    /// out, so callers can skip capturing.
+    pub fn check_stopped(&mut self) -> AppResult<Option<ScrollPollResult>> {
+        if self.stitcher.check_timeout().is_some() {
+            self.persist()?;
+        }
+        if self.stitcher.stop_reason().is_none() {
+            return Ok(None);
+        }
+        Ok(Some(self.poll_result("stopped", None, None)))
+    }
+
+    /// Wait for queued frame and manifest writes.
+    pub fn flush(&self) -> AppResult<()> {
+        self.writer.flush()
+    }
+
+    /// Stop at the user's request, keeping any earlier stop reason.
+    pub fn finish(&mut self) -> AppResult<()> {
+        self.stitcher.cancel();
+        self.persist()
+    }
+
+    /// Compare a freshly captured frame with the previous poll and stitch it
+    /// once scrolling has settled.
+    ///   "unchanged" - content has not changed since last poll
+    ///   "scrolling" - content is actively changing (user is scrolling)
+    ///   "captured"  - content was scrolling but has now stabilized → frame stitched
+    ///   "skipped"   - content stabilized, but the frame added nothing new
+    pub fn observe(
+        &mut self,
+        frame: RgbaImage,
+        frames: &FrameStore,
+    ) -> AppResult<ScrollPollResult> {
+        let Some(prev_frame) = self.prev_frame.as_ref() else {
+            // First poll - store frame as baseline, capture it as frame 0
+            return self.capture(frame, frames);
+        };
+
+        let config = self.stitcher.config();
+        let diff =
+            sample_frame_difference(prev_frame, &frame, config.match_region, config.direction);
+
+        if diff >= MIN_FRAME_DIFFERENCE {
+            // Content is changing → user is scrolling
+            self.was_scrolling = true;
+            self.stable_count = 0;
+            // Update prev_frame to latest so we detect when scrolling stops
+            self.prev_frame = Some(Arc::new(frame));
+            Ok(self.poll_result("scrolling", None, None))
+        } else if self.was_scrolling {
+            // Content was scrolling and is now stable → auto-capture
+            self.was_scrolling = false;
+            self.stable_count = 0;
+            self.capture(frame, frames)
+        } else {
+            // Content unchanged and wasn't scrolling before
+            Ok(self.poll_result("unchanged", None, None))
+        }
+    }
+
+    /// Downscaled stitched canvas, or the latest frame while only the first
+    /// frame has been stitched.
+    pub fn preview(&self) -> Option<RgbaImage> {
+        if self.stitcher.used_frames() > 1 {
+            self.stitcher.preview(SCROLL_PREVIEW_MAX_WIDTH)
+        } else {
+            self.prev_frame
+                .as_ref()
+                .map(|frame| downscale_to_width(frame, SCROLL_PREVIEW_MAX_WIDTH))
+        }
+    }
+
+    /// Stitch a settled frame and, if it was accepted, hand it to the
+    /// webview through the frame store in place of the previous one. The
+    /// stitcher, the store and the writer share the one buffer.
+    fn capture(&mut self, frame: RgbaImage, frames: &FrameStore) -> AppResult<ScrollPollResult> {
+        let frame = Arc::new(frame);
+        let before = self.stitcher.progress();
+        let append = self.stitcher.push(Arc::clone(&frame))?;
+        let accepted = self.stitcher.progress().frames > before.frames;
+
+        let handle = if accepted {
+            let handle = frames.replace(
+                self.latest_frame.as_deref(),
+                Some(&self.manifest.session_id),
+                Arc::clone(&frame),
+            )?;
+            self.latest_frame = Some(handle.id.clone());
+            self.record_frame(&frame, before, append)?;
+            Some(handle)
+        } else {
+            self.persist()?;
+            None
+        };
+        self.prev_frame = Some(frame);
+        self.frame_count += 1;
+
+        let state = if accepted { "captured" } else { "skipped" };
+        Ok(self.poll_result(state, handle, Some(append)))
+    }
+
+    /// Queue an accepted `frame` for the frames directory and add it to the
+    /// manifest, then persist.
+    fn record_frame(
+        &mut self,
+        frame: &Arc<RgbaImage>,
+        before: ScrollProgress,
+        append: AppendResult,
+    ) -> AppResult<()> {
+        let after = self.stitcher.progress();
+        let file = generate_filename_with_id("scroll_frame", self.frame_count as u32, "png")?;
+        self.writer.send(WriteTask::Frame {
+            path: self.frames_dir.join(&file),
+            image: Arc::clone(frame),
+        })?;
+        self.manifest.frames.push(ManifestFrame {
+            file,
+            offset_px: before.captured_height_px,
+            dy: after.captured_height_px - before.captured_height_px,
+            score: match append {
+                AppendResult::Accepted { score, .. } => Some(score),
+                _ => None,
+            },
+        });
+        self.persist()
+    }
+
+    fn persist(&mut self) -> AppResult<()> {
+        self.manifest.updated_at = get_timestamp()?;
+        self.manifest.band = self.stitcher.scroll_band();
+        self.manifest.state = self.stitcher.progress().state;
+        self.manifest.stop_reason = self.stitcher.stop_reason();
+        self.manifest.total_frames = self.stitcher.total_frames();
+        self.manifest.skipped_frames = self.stitcher.skipped_frames();
+        self.writer
+            .send(WriteTask::Manifest(Box::new(self.manifest.clone())))
+    }
+
+    fn poll_result(
+        &self,
+        state: &str,
+        frame: Option<FrameHandle>,
+        append: Option<AppendResult>,
+    ) -> ScrollPollResult {
+        ScrollPollResult {
+            state: state.to_string(),
+            frame,
+            frame_count: self.frame_count,
+            progress: self.stitcher.progress(),
+            append,
+            stop_reason: self.stitcher.stop_reason(),
+        }
+    }
+}
+
+pub type SharedScrollSession = Arc<Mutex<ScrollCaptureSession>>;
+
+/// All running scroll captures, keyed by session id. Clones share the
+/// same sessions.
+#[derive(Clone, Default)]
+pub struct ScrollSessions {
+    sessions: Arc<Mutex<HashMap<String, SharedScrollSession>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl ScrollSessions {
+    /// Register a new session with its own directory under `temp_dir`.
+    pub fn start(
+        &self,
+        temp_dir: &Path,
+        rect: CaptureRect,
+        config: ScrollConfig,
+    ) -> AppResult<ScrollSessionInfo> {
+        let id = format!(
+            "{}-{}",
+            get_timestamp()?,
+            self.next_id.fetch_add(1, Ordering::Relaxed)
+        );
+        let session_dir = temp_dir.join(format!("{}{}", SESSION_DIR_PREFIX, id));
+        let mut session = ScrollCaptureSession::new(id.clone(), rect, session_dir, config)?;
+        fs::create_dir_all(session.frames_dir()).map_err(|e| {
+            CaptureError::io(format!("Failed to create scroll session directory: {}", e))
+        })?;
+        session.persist()?;
+        session.flush()?;
+        let info = session.info();
+
+        self.lock()?.insert(id, Arc::new(Mutex::new(session)));
+        Ok(info)
+    }
+
+    pub fn get(&self, session_id: &str) -> AppResult<SharedScrollSession> {
+        self.lock()?
+            .get(session_id)
+            .cloned()
+            .ok_or_else(|| session_not_found(session_id))
+    }
+
+    /// Unregister a session and delete its directory. Unknown ids are ignored.
+    pub fn end(&self, session_id: &str) -> AppResult<()> {
+        let Some(session) = self.lock()?.remove(session_id) else {
+            return Ok(());
+        };
+        let session = lock_session(&session)?;
+        // Write failures no longer matter once the directory is deleted.
+        let _ = session.flush();
+        let session_dir = session.session_dir().to_path_buf();
+        if !session_dir.exists() {
+            return Ok(());
+        }
+        fs::remove_dir_all(&session_dir)
+            .map_err(|e| CaptureError::io(format!("Failed to clean scroll temp directory: {}", e)))
+    }
+
+    /// Sessions under `temp_dir` left behind by an earlier run, newest first.
+    pub fn recoverable(&self, temp_dir: &Path) -> AppResult<Vec<RecoverableScrollSession>> {
+        let live = self.live_ids()?;
+        let mut found: Vec<RecoverableScrollSession> = session_dirs(temp_dir)?
+            .into_iter()
+            .filter(|(id, _)| !live.contains(id))
+            .filter_map(|(_, dir)| {
+                let manifest = ScrollSessionManifest::load(&dir).ok()?;
+                let last = manifest.frames.last()?;
+                Some(RecoverableScrollSession {
+                    session_id: manifest.session_id.clone(),
+                    session_dir: dir.to_string_lossy().into_owned(),
+                    created_at: manifest.created_at,
+                    updated_at: manifest.updated_at,
+                    frame_count: manifest.frames.len(),
+                    captured_height_px: last.offset_px + last.dy,
+                    state: manifest.state,
+                    stop_reason: manifest.stop_reason,
+                })
+            })
+            .collect();
+        found.sort_by_key(|session| std::cmp::Reverse(session.updated_at));
+        Ok(found)
+    }
+
+    /// Register a session recovered from disk, reporting each frame read
+    /// through `on_frame` (see `ScrollCaptureSession::restore`). Already
+    /// running sessions are returned as they are.
+    pub fn resume(
+        &self,
+        temp_dir: &Path,
+        session_id: &str,
+        on_frame: impl FnMut(u64, u64) -> AppResult<()>,
+    ) -> AppResult<ScrollSessionInfo> {
+        if let Ok(session) = self.get(session_id) {
+            return Ok(lock_session(&session)?.info());
+        }
+        let session_dir = temp_dir.join(format!("{}{}", SESSION_DIR_PREFIX, session_id));
+        let manifest = ScrollSessionManifest::load(&session_dir)?;
+        if manifest.session_id != session_id {
+            return Err(session_not_found(session_id));
+        }
+        let mut session = ScrollCaptureSession::restore(manifest, session_dir, on_frame)?;
+        session.persist()?;
+        session.flush()?;
+        let info = session.info();
+
+        self.lock()?
+            .entry(session_id.to_string())
+            .or_insert_with(|| Arc::new(Mutex::new(session)));
+        Ok(info)
+    }
+
+    /// Delete session directories under `temp_dir` that are not running and
+    /// either hold nothing recoverable or were last touched `max_age_ms` ago.
+    /// Returns how many were removed.
+    pub fn collect_garbage(&self, temp_dir: &Path, max_age_ms: u64) -> AppResult<usize> {
+        let live = self.live_ids()?;
+        let now = get_timestamp()?;
+        let mut removed = 0;
+        for (id, dir) in session_dirs(temp_dir)? {
+            if live.contains(&id) {
+                continue;
+            }
+            let (updated_at, recoverable) = match ScrollSessionManifest::load(&dir) {
+                Ok(manifest) => (manifest.updated_at, !manifest.frames.is_empty()),
+                Err(_) => (modified_at(&dir).unwrap_or(0), true),
+            };
+            if recoverable && now.saturating_sub(updated_at) < max_age_ms {
+                continue;
+            }
+            if fs::remove_dir_all(&dir).is_ok() {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    fn live_ids(&self) -> AppResult<HashSet<String>> {
+        Ok(self.lock()?.keys().cloned().collect())
+    }
+
+    fn lock(&self) -> AppResult<MutexGuard<'_, HashMap<String, SharedScrollSession>>> {
+        self.sessions.lock().map_err(|e| {
+            CaptureError::command(format!("Failed to acquire scroll sessions lock: {}", e))
+        })
+    }
+}
+
+/// Session directories under `temp_dir`, with the id taken from the name.
+fn session_dirs(temp_dir: &Path) -> AppResult<Vec<(String, PathBuf)>> {
+    let entries = match fs::read_dir(temp_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(CaptureError::io(format!(
+                "Failed to read temp directory: {}",
+                e
+            )))
+        }
+    };
+    Ok(entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let id = name.strip_prefix(SESSION_DIR_PREFIX)?.to_string();
+            Some((id, entry.path()))
+        })
+        .collect())
+}
+
+fn session_not_found(session_id: &str) -> CaptureError {
+    CaptureError::validation(format!("Scroll session '{}' not found", session_id))
+        .with_detail("session_id", session_id)
+}
+
+fn modified_at(path: &Path) -> Option<u64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_millis() as u64)
+}
+
+pub fn lock_session(
+    session: &Mutex<ScrollCaptureSession>,
+) -> AppResult<MutexGuard<'_, ScrollCaptureSession>> {
+    session
+        .lock()
+        .map_err(|e| CaptureError::command(format!("Failed to acquire scroll session lock: {}", e)))
+}
+
+/// Stop `session`, if still running, and save its stitched canvas as a PNG
+/// in `dest`, as job `job`. Only the stitched copy is made under the lock;
+/// encoding and saving run after it is released so polling and previews
+/// are not held up.
+pub fn stitch_session(
+    session: &Mutex<ScrollCaptureSession>,
+    dest: &Path,
+    job: &JobContext,
+) -> AppResult<StitchResult> {
+    let (result, mut summary) = {
+        let mut guard = lock_session(session)?;
+        guard.finish()?;
+        let stitcher = guard.stitcher();
+        let result = stitcher.stitched_with_progress(|done, total| {
+            job.progress("stitching", done as u64, total as u64);
+        })?;
+        let summary = StitchResult {
+            path: String::new(),
+            total_frames: stitcher.total_frames(),
+            used_frames: stitcher.used_frames(),
+            skipped_frames: stitcher.skipped_frames(),
+            final_height: result.height(),
+            progress: stitcher.progress(),
+            stop_reason: stitcher.stop_reason(),
+        };
+        (result, summary)
+    };
+    let frames = summary.used_frames as u64;
+
+    job.check_cancelled()?;
+    job.progress("encoding", frames, frames);
+    let png = encode_image(&result, ExportFormat::Png, None)?;
+
+    job.check_cancelled()?;
+    job.progress("saving", frames, frames);
+    let output_path = dest.join(generate_filename("scrollshot", "png")?);
+    fs::write(&output_path, png)
+        .map_err(|e| CaptureError::io(format!("Failed to save stitched image: {}", e)))?;
+
+    summary.path = output_path
+        .to_str()
+        .ok_or_else(|| CaptureError::io("Failed to encode stitched file path"))?
+        .to_string();
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture_provider::CaptureProviders;
+    use crate::jobs::{JobKind, Jobs};
+    use capture_core::provider::CaptureOperation;
+    use capture_core::replay::{ReplayProvider, ReplayScene, ReplayTimeline};
+
+    fn test_rect() -> CaptureRect {
+        CaptureRect {
+            x: 0,
+            y: 0,
+            width: 120,
+            height: 160,
+        }
+    }
+
+    fn test_config() -> ScrollConfig {
+        ScrollConfig {
+            throttle_ms: 0,
+            ..ScrollConfig::default()
+        }
+    }
+
+    fn temp_root(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "ashot-scroll-test-{}-{}",
+            name,
+            get_timestamp().unwrap()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn page_frame(offset: u32) -> RgbaImage {
+        RgbaImage::from_fn(120, 160, |x, y| {
+            let row = (y + offset).wrapping_mul(2_654_435_761);
+            image::Rgba([(row >> 8) as u8, (row >> 16) as u8, (x * 2) as u8, 255])
+        })
+    }
+
+    #[test]
+    fn test_sessions_have_unique_ids_and_dirs() {
+        let root = temp_root("ids");
+        let sessions = ScrollSessions::default();
+        let first = sessions.start(&root, test_rect(), test_config()).unwrap();
+        let second = sessions.start(&root, test_rect(), test_config()).unwrap();
+
+        assert_ne!(first.session_id, second.session_id);
+        assert_ne!(first.session_dir, second.session_dir);
+        assert!(Path::new(&first.frames_dir).is_dir());
+        assert!(Path::new(&second.frames_dir).is_dir());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_ending_one_session_leaves_others_running() {
+        let root = temp_root("end");
+        let sessions = ScrollSessions::default();
+        let first = sessions.start(&root, test_rect(), test_config()).unwrap();
+        let second = sessions.start(&root, test_rect(), test_config()).unwrap();
+
+        sessions.end(&first.session_id).unwrap();
+        let error = sessions.get(&first.session_id).err().unwrap();
+        assert_eq!(error.kind, CaptureErrorKind::ValidationFailed);
+        assert_eq!(error.detail("session_id"), Some(first.session_id.as_str()));
+        assert!(!Path::new(&first.session_dir).exists());
+        assert!(sessions.get(&second.session_id).is_ok());
+        assert!(Path::new(&second.session_dir).exists());
+        assert!(sessions.end(&first.session_id).is_ok());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_observe_stitches_frames_per_session() {
+        let root = temp_root("observe");
+        let sessions = ScrollSessions::default();
+        let info = sessions.start(&root, test_rect(), test_config()).unwrap();
+        let other = sessions.start(&root, test_rect(), test_config()).unwrap();
+        let shared = sessions.get(&info.session_id).unwrap();
+        let mut session = lock_session(&shared).unwrap();
+        let frames = FrameStore::default();
+
+        let observe = |session: &mut ScrollCaptureSession, offset| {
+            session.observe(page_frame(offset), &frames).unwrap()
+        };
+        let first = observe(&mut session, 0);
+        assert_eq!(first.state, "captured");
+        assert_eq!(observe(&mut session, 0).state, "unchanged");
+        assert_eq!(observe(&mut session, 60).state, "scrolling");
+        let captured = observe(&mut session, 60);
+        assert_eq!(captured.state, "captured");
+        assert_eq!(captured.frame_count, 2);
+        assert!(matches!(
+            captured.append,
+            Some(AppendResult::Accepted { dy: 60, .. })
+        ));
+        let handle = captured.frame.unwrap();
+        assert_eq!(*frames.get(&handle.id).unwrap().unwrap(), page_frame(60));
+        // Each accepted frame replaces the previous one in the store.
+        assert!(frames.get(&first.frame.unwrap().id).unwrap().is_none());
+        assert_eq!(session.stitcher().progress().captured_height_px, 220);
+
+        // Scrolling away and back settles on the frame already stitched.
+        assert_eq!(observe(&mut session, 120).state, "scrolling");
+        assert_eq!(observe(&mut session, 60).state, "scrolling");
+        let skipped = observe(&mut session, 60);
+        assert_eq!(skipped.state, "skipped");
+        assert!(skipped.frame.is_none());
+        assert!(matches!(skipped.append, Some(AppendResult::Skipped(_))));
+        assert_eq!(frames.remove_group(&info.session_id).unwrap(), 1);
+
+        let other = sessions.get(&other.session_id).unwrap();
+        assert_eq!(lock_session(&other).unwrap().stitcher().total_frames(), 0);
+        session.flush().unwrap();
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    /// Run a session through three captured frames without ending it.
+    fn capture_three_frames(sessions: &ScrollSessions, root: &Path) -> ScrollSessionInfo {
+        let info = sessions.start(root, test_rect(), test_config()).unwrap();
+        let shared = sessions.get(&info.session_id).unwrap();
+        let mut session = lock_session(&shared).unwrap();
+        let frames = FrameStore::default();
+        session.observe(page_frame(0), &frames).unwrap();
+        for offset in [60, 130] {
+            session.observe(page_frame(offset), &frames).unwrap();
+            session.observe(page_frame(offset), &frames).unwrap();
+        }
+        session.flush().unwrap();
+        info
+    }
+
+    #[test]
+    fn test_manifest_tracks_accepted_frames() {
+        let root = temp_root("manifest");
+        let sessions = ScrollSessions::default();
+        let info = capture_three_frames(&sessions, &root);
+
+        let manifest = ScrollSessionManifest::load(Path::new(&info.session_dir)).unwrap();
+        assert_eq!(manifest.session_id, info.session_id);
+        assert_eq!(manifest.state, ScrollSessionState::Capturing);
+        assert!(manifest.band.is_some());
+        let layout: Vec<(u32, u32)> = manifest
+            .frames
+            .iter()
+            .map(|frame| (frame.offset_px, frame.dy))
+            .collect();
+        assert_eq!(layout, vec![(0, 160), (160, 60), (220, 70)]);
+        for frame in &manifest.frames {
+            assert!(Path::new(&info.frames_dir).join(&frame.file).is_file());
+        }
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_resume_rebuilds_session_after_restart() {
+        let root = temp_root("resume");
+        let expected = {
+            let crashed = ScrollSessions::default();
+            let info = capture_three_frames(&crashed, &root);
+            let shared = crashed.get(&info.session_id).unwrap();
+            let stitched = lock_session(&shared).unwrap().stitcher().stitched();
+            (info.session_id, stitched.unwrap())
+        };
+
+        let sessions = ScrollSessions::default();
+        let recoverable = sessions.recoverable(&root).unwrap();
+        assert_eq!(recoverable.len(), 1);
+        assert_eq!(recoverable[0].session_id, expected.0);
+        assert_eq!(recoverable[0].frame_count, 3);
+        assert_eq!(recoverable[0].captured_height_px, 290);
+
+        let mut reported = Vec::new();
+        let info = sessions
+            .resume(&root, &expected.0, |done, total| {
+                reported.push((done, total));
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(reported, vec![(1, 3), (2, 3), (3, 3)]);
+        assert_eq!(info.progress.frames, 3);
+        assert!(sessions.recoverable(&root).unwrap().is_empty());
+        let shared = sessions.get(&expected.0).unwrap();
+        let session = lock_session(&shared).unwrap();
+        assert_eq!(session.stitcher().stitched().unwrap(), expected.1);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_resume_keeps_a_stopped_session_stopped() {
+        let root = temp_root("stopped");
+        let (session_id, expected) = {
+            let previous = ScrollSessions::default();
+            let info = capture_three_frames(&previous, &root);
+            let shared = previous.get(&info.session_id).unwrap();
+            let mut session = lock_session(&shared).unwrap();
+            let frames = FrameStore::default();
+            // Scroll away and back, so the settled frame repeats the last one.
+            session.observe(page_frame(200), &frames).unwrap();
+            session.observe(page_frame(130), &frames).unwrap();
+            let repeated = session.observe(page_frame(130), &frames).unwrap();
+            assert!(matches!(repeated.append, Some(AppendResult::Skipped(_))));
+            session.finish().unwrap();
+            let stitcher = session.stitcher();
+            let expected = (
+                stitcher.progress(),
+                stitcher.stop_reason(),
+                stitcher.total_frames(),
+                stitcher.skipped_frames(),
+            );
+            (info.session_id, expected)
+        };
+        assert_eq!(expected.1, Some(StopReason::User));
+        assert_eq!((expected.2, expected.3), (4, 1));
+
+        let sessions = ScrollSessions::default();
+        let info = sessions.resume(&root, &session_id, |_, _| Ok(())).unwrap();
+        assert_eq!(info.progress, expected.0);
+        let shared = sessions.get(&session_id).unwrap();
+        let mut session = lock_session(&shared).unwrap();
+        let stitcher = session.stitcher();
+        let restored = (
+            stitcher.progress(),
+            stitcher.stop_reason(),
+            stitcher.total_frames(),
+            stitcher.skipped_frames(),
+        );
+        assert_eq!(restored, expected);
+        let stopped = session.check_stopped().unwrap().unwrap();
+        assert_eq!(stopped.state, "stopped");
+        assert_eq!(stopped.frame_count, 4);
+        session.flush().unwrap();
+        drop(session);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_gc_removes_stale_and_empty_sessions() {
+        let root = temp_root("gc");
+        let (recent, stale, empty) = {
+            let previous = ScrollSessions::default();
+            let recent = capture_three_frames(&previous, &root);
+            let stale = capture_three_frames(&previous, &root);
+            let empty = previous.start(&root, test_rect(), test_config()).unwrap();
+            (recent, stale, empty)
+        };
+        let stale_dir = Path::new(&stale.session_dir);
+        let mut manifest = ScrollSessionManifest::load(stale_dir).unwrap();
+        manifest.updated_at = 0;
+        manifest.save(stale_dir).unwrap();
+
+        let sessions = ScrollSessions::default();
+        let live = sessions.start(&root, test_rect(), test_config()).unwrap();
+        let removed = sessions
+            .collect_garbage(&root, STALE_SCROLL_SESSION_MS)
+            .unwrap();
+
+        assert_eq!(removed, 2);
+        assert!(Path::new(&recent.session_dir).exists());
+        assert!(!stale_dir.exists());
+        assert!(!Path::new(&empty.session_dir).exists());
+        assert!(Path::new(&live.session_dir).exists());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    /// Poll `provider` until its timeline ends, the same way as
+    /// `poll_scroll_region` minus the async hop, and return the states.
+    fn replay_polls(
+        provider: &Arc<ReplayProvider>,
+        shared: &SharedScrollSession,
+        frames: &FrameStore,
+    ) -> Vec<String> {
+        let providers = CaptureProviders::new(vec![provider.clone()]);
+        let mut states = Vec::new();
+        while !provider.is_finished() {
+            let rect = capture_core::CaptureRect::from(lock_session(shared).unwrap().rect());
+            let frame = providers
+                .try_each(CaptureOperation::Region, |p| p.capture_region(rect))
+                .unwrap();
+            let mut session = lock_session(shared).unwrap();
+            states.push(session.observe(frame, frames).unwrap().state);
+        }
+        states
+    }
+
+    /// Run `stitch_session` as a job, like `stitch_scroll_frames`.
+    fn replay_stitch(shared: &SharedScrollSession, dest: &Path) -> StitchResult {
+        let shared = Arc::clone(shared);
+        let dest = dest.to_path_buf();
+        tauri::async_runtime::block_on(Jobs::default().run(None, JobKind::Stitch, move |job| {
+            stitch_session(&shared, &dest, job)
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_replayed_polls_stitch_the_source_page() {
+        // Each offset is polled twice: once mid-scroll, once settled.
+        let mut timeline = ReplayTimeline::starting_at(0);
+        for offset in [90, 180, 270, 360, 450] {
+            timeline = timeline.then(200, offset).then(200, offset);
+        }
+        let provider = Arc::new(ReplayProvider::new(
+            ReplayScene::synthetic(120, 640, 160).with_header(20),
+            timeline,
+        ));
+
+        let root = temp_root("replay");
+        let sessions = ScrollSessions::default();
+        let info = sessions.start(&root, test_rect(), test_config()).unwrap();
+        let shared = sessions.get(&info.session_id).unwrap();
+        let frames = FrameStore::default();
+
+        let states = replay_polls(&provider, &shared, &frames);
+
+        assert_eq!(
+            states.iter().filter(|state| *state == "captured").count(),
+            6
+        );
+        let result = replay_stitch(&shared, &root);
+        assert_eq!(result.used_frames, 6);
+        assert_eq!(result.stop_reason, Some(StopReason::User));
+        let saved = image::open(&result.path).unwrap().to_rgba8();
+        assert!(saved == provider.expected_stitch());
+        assert_eq!(result.final_height, saved.height());
+        lock_session(&shared).unwrap().flush().unwrap();
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_replayed_bounces_save_sticky_bars_once() {
+        // Scrolls overshoot and bounce back before they settle; the last one
+        // stops dead.
+        let mut timeline = ReplayTimeline::starting_at(0);
+        for offset in [80, 160, 240, 320] {
+            timeline = timeline
+                .then(150, offset + 12)
+                .then(150, offset)
+                .then(150, offset);
+        }
+        timeline = timeline.then(150, 400).then(150, 400);
+        let provider = Arc::new(ReplayProvider::new(
+            ReplayScene::synthetic(120, 560, 160)
+                .with_header(20)
+                .with_footer(16),
+            timeline,
+        ));
+
+        let root = temp_root("replay-bounce");
+        let sessions = ScrollSessions::default();
+        let info = sessions.start(&root, test_rect(), test_config()).unwrap();
+        let shared = sessions.get(&info.session_id).unwrap();
+        let frames = FrameStore::default();
+
+        let states = replay_polls(&provider, &shared, &frames);
+        assert_eq!(
+            states.iter().filter(|state| *state == "captured").count(),
+            6
+        );
+
+        let result = replay_stitch(&shared, &root);
+        let expected = provider.expected_stitch();
+        assert_eq!(result.final_height, 20 + 400 + 124 + 16);
+        assert!(image::open(&result.path).unwrap().to_rgba8() == expected);
+        lock_session(&shared).unwrap().flush().unwrap();
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
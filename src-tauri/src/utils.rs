@@ -4,12 +4,16 @@ use std::fs;
 use std::path::PathBuf;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-/// Custom error type for better error handling
-pub type AppResult<T> = Result<T, String>;
+use capture_core::CaptureError;
+
+/// Result type for commands; the error serializes to a typed
+/// `{ kind, message, details? }` object on the frontend.
+pub type AppResult<T> = Result<T, CaptureError>;
 
 /// Get the user's Desktop directory path (cross-platform)
 pub fn get_desktop_path() -> AppResult<String> {
-    let desktop = dirs::desktop_dir().ok_or("Failed to get Desktop directory")?;
+    let desktop =
+        dirs::desktop_dir().ok_or_else(|| CaptureError::io("Failed to get Desktop directory"))?;
     Ok(desktop.to_string_lossy().into_owned())
 }
 
@@ -17,13 +21,14 @@ pub fn get_desktop_path() -> AppResult<String> {
 pub fn get_timestamp() -> AppResult<u64> {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
-        .map_err(|e| format!("Failed to get timestamp: {}", e))
+        .map_err(|e| CaptureError::io(format!("Failed to get timestamp: {}", e)))
         .map(|d| d.as_millis() as u64)
 }
 
 /// Ensure a directory exists, creating it if necessary
 pub fn ensure_dir(path: &PathBuf) -> AppResult<()> {
-    fs::create_dir_all(path).map_err(|e| format!("Failed to create directory: {}", e))
+    fs::create_dir_all(path)
+        .map_err(|e| CaptureError::io(format!("Failed to create directory: {}", e)))
 }
 
 /// Generate a unique filename with a prefix and timestamp
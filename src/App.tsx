@@ -3,8 +3,11 @@ import { editorActions } from "@/stores/editorStore";
 import { Button } from "@/components/ui/button";
 import { Card, CardContent } from "@/components/ui/card";
 import { Switch } from "@/components/ui/switch";
-import { classifyCaptureError } from "@/lib/capture-errors";
-import { processScreenshotWithDefaultBackground } from "@/lib/auto-process";
+import { classifyCaptureError, errorMessage } from "@/lib/capture-errors";
+import type { FrameHandle } from "@/lib/frame-store";
+import { renderScreenshotWithDefaultBackground } from "@/lib/auto-process";
+import { saveImage } from "@/lib/image-ipc";
+import { cancelJob, createJobId, onJobProgress, type JobProgress } from "@/lib/jobs";
 import {
   ensureScreenPermission as ensureScreenPermissionFlow,
   type PermissionEnsureResult,
@@ -24,8 +27,13 @@ import {
 } from "@/lib/shortcuts";
 import {
   SCROLL_SESSION_TIMEOUT_MS,
+  scrollStopMessageKey,
   shouldAutoCancelScrollSession,
+  type RecoverableScrollSession,
+  type ScrollDirection,
+  type ScrollMatchRegion,
   type ScrollPollResult,
+  type ScrollSessionInfo,
   type ScrollSessionState,
   type StitchResult,
 } from "@/lib/scroll-session";
@@ -63,6 +71,7 @@ import type {
   CaptureRect,
   CaptureWindowInfo,
   MonitorShot,
+  VirtualDesktopShot,
 } from "@/ui-workflows/capture-shell/types";
 
 const OnboardingFlow = lazy(() =>
@@ -74,15 +83,12 @@ const PreferencesPage = lazy(() =>
 
 type AppView = "launcher" | "preferences" | "editing" | "capture";
 
-type SaveImageResponse = {
-  path: string;
-  copy_warning?: string | null;
-};
-
 type ScrollSession = {
+  sessionId: string;
   sessionDir: string;
   framesDir: string;
   rect: CaptureRect;
+  /** Frame store ids of the captured frames. */
   frames: string[];
 };
 
@@ -267,7 +273,8 @@ function App() {
   const [scrollSessionState, setScrollSessionState] = useState<ScrollSessionState>("idle");
   const [scrollFrameCount, setScrollFrameCount] = useState(0);
   const [scrollIsScrolling, setScrollIsScrolling] = useState(false);
-  const [scrollPreviewPath, setScrollPreviewPath] = useState<string | null>(null);
+  const [scrollPreviewFrame, setScrollPreviewFrame] = useState<string | null>(null);
+  const [scrollStitchProgress, setScrollStitchProgress] = useState<JobProgress | null>(null);
 
   const [hotkeyHealth, setHotkeyHealth] = useState<HotkeyHealthSnapshot>({
     state: "no_enabled_shortcuts",
@@ -285,10 +292,12 @@ function App() {
   const isScrollSessionActiveRef = useRef(false);
   const scrollSessionRef = useRef<ScrollSession | null>(null);
   const scrollLastActivityRef = useRef(0);
+  const stitchJobRef = useRef<string | null>(null);
   const finishScrollCaptureRef = useRef<(intent?: ScrollFinishIntent) => Promise<void>>(async () => undefined);
   const cancelScrollCaptureRef = useRef<(reason?: "user" | "timeout") => Promise<void>>(
     async () => undefined,
   );
+  const offerScrollRecoveryRef = useRef<(dir: string) => Promise<void>>(async () => undefined);
 
   const transitionCaptureState = useCallback((event: CaptureEvent) => {
     setCaptureState((prev) => reduceCaptureState(prev, event));
@@ -318,16 +327,20 @@ function App() {
     setScrollSessionState("idle");
     setScrollFrameCount(0);
     setScrollIsScrolling(false);
-    setScrollPreviewPath(null);
+    setScrollPreviewFrame(null);
     setCaptureState({ kind: "Idle" });
   }, []);
 
   const resetOverlayFlow = useCallback(
     async (restoreMainWindow: boolean, nextView: AppView = "launcher") => {
       clearOverlayUiState();
+      const scrollSession = scrollSessionRef.current;
       isScrollSessionActiveRef.current = false;
       scrollSessionRef.current = null;
       scrollLastActivityRef.current = 0;
+      if (scrollSession) {
+        await invoke("cleanup_scroll_temp", { sessionId: scrollSession.sessionId }).catch(() => undefined);
+      }
       await invoke("set_main_window_mouse_passthrough", { enabled: false }).catch(() => undefined);
       setAppView(nextView);
 
@@ -388,7 +401,7 @@ function App() {
     try {
       await invoke("open_screen_recording_settings");
     } catch (openError) {
-      const message = openError instanceof Error ? openError.message : String(openError);
+      const message = errorMessage(openError);
       toast.error(t("app.toast.openSettingsFailed"), {
         description: message,
         duration: 4500,
@@ -528,6 +541,7 @@ function App() {
       try {
         const systemTempDir = await invoke<string>("get_temp_directory");
         setTempDir(systemTempDir);
+        void offerScrollRecoveryRef.current(systemTempDir);
       } catch (tempError) {
         console.error("Failed to get temp directory, using fallback:", tempError);
       }
@@ -615,13 +629,13 @@ function App() {
       scrollSessionRef.current = null;
 
       if (session) {
-        await invoke("cleanup_scroll_temp", { sessionDir: session.sessionDir }).catch(() => undefined);
+        await invoke("cleanup_scroll_temp", { sessionId: session.sessionId }).catch(() => undefined);
       }
 
       setScrollSessionState("idle");
       setScrollFrameCount(0);
       setScrollIsScrolling(false);
-      setScrollPreviewPath(null);
+      setScrollPreviewFrame(null);
 
       clearOverlayUiState();
       if (options?.nextImagePath) {
@@ -667,9 +681,17 @@ function App() {
           await invoke("validate_save_directory", { path: targetDir });
         }
 
+        const jobId = createJobId("stitch");
+        stitchJobRef.current = jobId;
+        const unlistenProgress = await onJobProgress(jobId, setScrollStitchProgress);
         const result = await invoke<StitchResult>("stitch_scroll_frames", {
-          framePaths: session.frames,
+          sessionId: session.sessionId,
           saveDir: targetDir,
+          jobId,
+        }).finally(() => {
+          stitchJobRef.current = null;
+          unlistenProgress();
+          setScrollStitchProgress(null);
         });
 
         if (intent === "copy_only") {
@@ -711,7 +733,9 @@ function App() {
           session.rect.y + session.rect.height / 2,
         );
       } catch (finishError) {
-        const message = finishError instanceof Error ? finishError.message : String(finishError);
+        // Cancelling mid-stitch already cleaned up the session.
+        if (classifyCaptureError(finishError) === "cancelled") return;
+        const message = errorMessage(finishError);
         transitionCaptureState({ type: "StitchFail", reason: message });
         setScrollSessionState("failed");
         setError(message);
@@ -724,6 +748,9 @@ function App() {
 
   const cancelScrollCapture = useCallback(
     async (reason: "user" | "timeout" = "user") => {
+      if (stitchJobRef.current) {
+        await cancelJob(stitchJobRef.current);
+      }
       transitionCaptureState({ type: "Cancel" });
       if (reason === "timeout") {
         toast.error(t("app.toast.scrollTimeout"), { duration: 2600 });
@@ -743,33 +770,113 @@ function App() {
     cancelScrollCaptureRef.current = cancelScrollCapture;
   }, [cancelScrollCapture]);
 
-  const startScrollSession = useCallback(
-    async (rect: CaptureRect) => {
-      const sessionDir = `${settingsRef.current.tempDir}/bettershot-scroll-${Date.now()}`;
-      const framesDir = `${sessionDir}/frames`;
+  const recoverScrollSession = useCallback(
+    async (dir: string, sessionId: string) => {
+      const jobId = createJobId("stitch");
+      const unlistenProgress = await onJobProgress(jobId, (progress) => {
+        toast.loading(
+          t("app.toast.scrollRecovering", {
+            done: String(progress.done),
+            total: String(progress.total),
+          }),
+          { id: jobId },
+        );
+      });
+      try {
+        await invoke<ScrollSessionInfo>("resume_scroll_session", {
+          tempDir: dir,
+          sessionId,
+          jobId,
+        });
+        const result = await invoke<StitchResult>("stitch_scroll_frames", {
+          sessionId,
+          saveDir: dir,
+        });
+        await invoke("cleanup_scroll_temp", { sessionId }).catch(() => undefined);
+        setTempScreenshotPath(result.path);
+        setAppView("editing");
+        await restoreWindow();
+      } catch (recoverError) {
+        const message = errorMessage(recoverError);
+        console.error("Failed to recover scroll session:", recoverError);
+        toast.error(t("app.toast.scrollRecoverFailed"), { description: message });
+      } finally {
+        unlistenProgress();
+        toast.dismiss(jobId);
+      }
+    },
+    [t],
+  );
 
-      scrollSessionRef.current = {
-        sessionDir,
-        framesDir,
-        rect,
-        frames: [],
-      };
+  const offerScrollRecovery = useCallback(
+    async (dir: string) => {
+      let sessions: RecoverableScrollSession[] = [];
+      try {
+        await invoke<number>("gc_scroll_sessions", { tempDir: dir });
+        sessions = await invoke<RecoverableScrollSession[]>("list_scroll_sessions", {
+          tempDir: dir,
+        });
+      } catch (listError) {
+        console.error("Failed to list interrupted scroll sessions:", listError);
+        return;
+      }
 
+      const latest = sessions.find((session) => session.frameCount >= 2);
+      if (!latest) return;
+
+      toast(t("app.toast.scrollRecoverable"), {
+        description: t("app.toast.scrollRecoverableFrames", {
+          count: String(latest.frameCount),
+        }),
+        duration: 10000,
+        action: {
+          label: t("app.toast.scrollRecoverAction"),
+          onClick: () => {
+            void recoverScrollSession(dir, latest.sessionId);
+          },
+        },
+      });
+    },
+    [recoverScrollSession, t],
+  );
+
+  useEffect(() => {
+    offerScrollRecoveryRef.current = offerScrollRecovery;
+  }, [offerScrollRecovery]);
+
+  const startScrollSession = useCallback(
+    async (rect: CaptureRect) => {
       try {
+        const store = await Store.load("settings.json");
+        const direction = await store.get<ScrollDirection>("scrollDirection");
+        const matchRegion = await store.get<ScrollMatchRegion>("scrollMatchRegion");
+        const info = await invoke<ScrollSessionInfo>("start_scroll_session", {
+          tempDir: settingsRef.current.tempDir,
+          rect,
+          direction: direction ?? null,
+          matchRegion: matchRegion ?? null,
+        });
+        scrollSessionRef.current = {
+          sessionId: info.sessionId,
+          sessionDir: info.sessionDir,
+          framesDir: info.framesDir,
+          rect,
+          frames: [],
+        };
+
         transitionCaptureState({ type: "StartScroll" });
         isScrollSessionActiveRef.current = true;
         setScrollSessionState("capturing");
         setScrollFrameCount(0);
         setScrollIsScrolling(false);
-        setScrollPreviewPath(null);
+        setScrollPreviewFrame(null);
         scrollLastActivityRef.current = Date.now();
 
-        await invoke("reset_scroll_monitor");
         await unregisterSet(registeredShortcutsRef);
         await registerScrollHotkeys();
         await invoke("set_main_window_mouse_passthrough", { enabled: true });
       } catch (startError) {
-        const message = startError instanceof Error ? startError.message : String(startError);
+        const message = errorMessage(startError);
         setError(message);
         setScrollSessionState("failed");
         await cleanupScrollSession({ restoreMainWindow: true, nextView: "launcher" });
@@ -810,8 +917,7 @@ function App() {
 
       try {
         const result = await invoke<ScrollPollResult>("poll_scroll_region", {
-          rect: currentSession.rect,
-          framesDir: currentSession.framesDir,
+          sessionId: currentSession.sessionId,
         });
 
         if (cancelled) return;
@@ -823,20 +929,34 @@ function App() {
           setScrollIsScrolling(false);
         }
 
-        if (result.state === "captured" && result.framePath) {
-          currentSession.frames.push(result.framePath);
+        if (result.state === "captured" && result.frame) {
+          currentSession.frames.push(result.frame.id);
           setScrollFrameCount(currentSession.frames.length);
           scrollLastActivityRef.current = Date.now();
 
-          const previewPath = await invoke<string>("stitch_scroll_frames_preview", {
-            framePaths: currentSession.frames,
-            sessionDir: currentSession.sessionDir,
+          const preview = await invoke<FrameHandle>("stitch_scroll_frames_preview", {
+            sessionId: currentSession.sessionId,
           }).catch(() => null);
 
-          if (previewPath) {
-            setScrollPreviewPath(previewPath);
+          if (preview) {
+            setScrollPreviewFrame(preview.id);
           }
         }
+
+        if (result.stopReason) {
+          const messageKey = scrollStopMessageKey(result.stopReason);
+          if (result.stopReason === "Timeout") {
+            void cancelScrollCaptureRef.current("timeout");
+          } else if (currentSession.frames.length < 2) {
+            void cancelScrollCaptureRef.current("user");
+          } else {
+            if (messageKey) {
+              toast(t(messageKey), { duration: 2600 });
+            }
+            void finishScrollCaptureRef.current("edit");
+          }
+          return;
+        }
       } catch (pollError) {
         console.error("Scroll poll error:", pollError);
       }
@@ -852,7 +972,7 @@ function App() {
       cancelled = true;
       clearTimeout(pollTimeout);
     };
-  }, [scrollSessionState, transitionCaptureState]);
+  }, [scrollSessionState, t, transitionCaptureState]);
 
   useEffect(() => {
     if (!isScrollSessionActiveRef.current) return;
@@ -930,7 +1050,7 @@ function App() {
       transitionCaptureState({ type: "TriggerCapture", mode: "window" });
       toast(t("app.toast.monitorLocked", { display: monitor.label }), { duration: 2000 });
     } catch (overlayError) {
-      const message = overlayError instanceof Error ? overlayError.message : String(overlayError);
+      const message = errorMessage(overlayError);
       setError(message);
       setAppView("launcher");
       await resetOverlayFlow(true);
@@ -988,9 +1108,8 @@ function App() {
         if (settingsRef.current.autoApplyBackground) {
           await invoke("validate_save_directory", { path: settingsRef.current.saveDir });
 
-          const processedImageData = await processScreenshotWithDefaultBackground(screenshotPath);
-          const saved = await invoke<SaveImageResponse>("save_edited_image", {
-            imageData: processedImageData,
+          const processedImage = await renderScreenshotWithDefaultBackground(screenshotPath);
+          const saved = await saveImage(processedImage, {
             saveDir: settingsRef.current.saveDir,
             copyToClip: true,
           });
@@ -1011,8 +1130,8 @@ function App() {
         setAppView("editing");
         await restoreWindowOnScreen(centerX, centerY);
       } catch (finishError) {
-        const errorMessage = finishError instanceof Error ? finishError.message : String(finishError);
-        const errorKind = classifyCaptureError(errorMessage);
+        const message = errorMessage(finishError);
+        const errorKind = classifyCaptureError(finishError);
 
         if (errorKind === "cancelled") {
           await resetOverlayFlow(true);
@@ -1021,9 +1140,9 @@ function App() {
           setIsPermissionError(true);
           await resetOverlayFlow(true);
         } else {
-          setError(errorMessage);
+          setError(message);
           toast.error(t("app.toast.saveFailed"), {
-            description: errorMessage,
+            description: message,
             duration: 5000,
           });
           await resetOverlayFlow(true);
@@ -1065,8 +1184,8 @@ function App() {
 
         await resetOverlayFlow(true);
       } catch (ocrError) {
-        const errorMessage = ocrError instanceof Error ? ocrError.message : String(ocrError);
-        const errorKind = classifyCaptureError(errorMessage);
+        const message = errorMessage(ocrError);
+        const errorKind = classifyCaptureError(ocrError);
 
         if (errorKind === "cancelled") {
           await resetOverlayFlow(true);
@@ -1081,9 +1200,9 @@ function App() {
           });
           await resetOverlayFlow(true);
         } else {
-          setError(errorMessage);
+          setError(message);
           toast.error(t("app.toast.ocrFailed"), {
-            description: errorMessage,
+            description: message,
             duration: 5000,
           });
           await resetOverlayFlow(true);
@@ -1197,7 +1316,7 @@ function App() {
               registeredShortcutsRef.current.add(captureShortcut.shortcut);
               registeredShortcutCount += 1;
             } catch (registerError) {
-              const reason = registerError instanceof Error ? registerError.message : String(registerError);
+              const reason = errorMessage(registerError);
               registrationIssues.push({
                 kind: "register_failed",
                 message: t("app.error.shortcutRegisterOne", {
@@ -1231,7 +1350,7 @@ function App() {
           return;
         }
       } catch (hotkeyError) {
-        const reason = hotkeyError instanceof Error ? hotkeyError.message : String(hotkeyError);
+        const reason = errorMessage(hotkeyError);
         const issue: ShortcutRegistrationIssue = {
           kind: "register_failed",
           message: reason,
@@ -1274,6 +1393,7 @@ function App() {
     let unlisten5: (() => void) | null = null;
     let unlisten6: (() => void) | null = null;
     let unlisten7: (() => void) | null = null;
+    let unlisten8: (() => void) | null = null;
     let mounted = true;
 
     const setupListeners = async () => {
@@ -1308,6 +1428,20 @@ function App() {
         }
         await restoreWindow();
       });
+      unlisten8 = await listen("capture-desktop", async () => {
+        if (!mounted) return;
+
+        try {
+          const shot = await invoke<VirtualDesktopShot>("capture_virtual_desktop", {
+            saveDir: settingsRef.current.tempDir,
+          });
+          setTempScreenshotPath(shot.path);
+          setAppView("editing");
+          await restoreWindow();
+        } catch (e) {
+          toast.error(errorMessage(e));
+        }
+      });
     };
 
     void setupListeners();
@@ -1321,6 +1455,7 @@ function App() {
       unlisten5?.();
       unlisten6?.();
       unlisten7?.();
+      unlisten8?.();
     };
   }, []);
 
@@ -1351,11 +1486,10 @@ function App() {
   }, [loadSettings]);
 
   const handleEditorSave = useCallback(
-    async (editedImageData: string) => {
+    async (editedImage: Blob) => {
       try {
         await invoke("validate_save_directory", { path: saveDir });
-        const saved = await invoke<SaveImageResponse>("save_edited_image", {
-          imageData: editedImageData,
+        const saved = await saveImage(editedImage, {
           saveDir,
           copyToClip: true,
         });
@@ -1371,10 +1505,10 @@ function App() {
         setAppView("launcher");
         transitionCaptureState({ type: "ExportDone" });
       } catch (saveError) {
-        const errorMessage = saveError instanceof Error ? saveError.message : String(saveError);
-        setError(errorMessage);
+        const message = errorMessage(saveError);
+        setError(message);
         toast.error(t("app.toast.saveFailed"), {
-          description: errorMessage,
+          description: message,
           duration: 5000,
         });
         editorActions.reset();
@@ -1402,7 +1536,12 @@ function App() {
   if (appView === "editing" && tempScreenshotPath) {
     return (
       <Suspense fallback={<LoadingFallback />}>
-        <EditorShell imagePath={tempScreenshotPath} onSave={handleEditorSave} onCancel={handleEditorCancel} />
+        <EditorShell
+          imagePath={tempScreenshotPath}
+          saveDir={saveDir}
+          onSave={handleEditorSave}
+          onCancel={handleEditorCancel}
+        />
       </Suspense>
     );
   }
@@ -1485,9 +1624,10 @@ function App() {
               <ScrollPreviewPanel
                 monitor={activeMonitor}
                 rect={selectedRect}
-                previewPath={scrollPreviewPath}
+                previewFrameId={scrollPreviewFrame}
                 frameCount={scrollFrameCount}
                 isScrolling={scrollIsScrolling}
+                stitchProgress={scrollStitchProgress}
               />
             </>
         )}
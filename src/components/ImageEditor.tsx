@@ -1,8 +1,9 @@
 import { useState, useRef, useEffect, useCallback } from "react";
 import { convertFileSrc, invoke } from "@tauri-apps/api/core";
 import { getCurrentWindow } from "@tauri-apps/api/window";
+import { open } from "@tauri-apps/plugin-dialog";
 import { toast } from "sonner";
-import { Copy, ImageDown, Loader2, Redo2, Undo2 } from "lucide-react";
+import { Copy, FolderOpen, ImageDown, Loader2, Redo2, Save, Undo2 } from "lucide-react";
 import { Button } from "@/components/ui/button";
 import { Card, CardContent } from "@/components/ui/card";
 import { Tooltip, TooltipContent, TooltipProvider, TooltipTrigger } from "@/components/ui/tooltip";
@@ -17,26 +18,29 @@ import { Annotation, ToolType } from "@/types/annotations";
 import { usePreviewGenerator } from "@/hooks/usePreviewGenerator";
 import { assetCategories } from "@/hooks/useEditorSettings";
 import { useI18n } from "@/i18n/useI18n";
+import { errorMessage } from "@/lib/capture-errors";
+import { resolveRustRenderSettings, saveImage } from "@/lib/image-ipc";
+import { createJobId } from "@/lib/jobs";
+import { loadProject, saveProject, PROJECT_EXTENSION, type LoadedProject } from "@/lib/project";
 import {
+  useEditorStore,
   useSettings,
   useAnnotations,
   useCanUndo,
   useCanRedo,
   editorActions,
+  type EditorSettings,
 } from "@/stores";
 
 interface ImageEditorProps {
   imagePath: string;
-  onSave: (editedImageData: string) => void;
+  /** Directory new projects are saved to. */
+  saveDir: string;
+  onSave: (editedImage: Blob) => void;
   onCancel: () => void;
 }
 
-type SaveImageResponse = {
-  path: string;
-  copy_warning?: string | null;
-};
-
-export function ImageEditor({ imagePath, onSave, onCancel }: ImageEditorProps) {
+export function ImageEditor({ imagePath, saveDir, onSave, onCancel }: ImageEditorProps) {
   const { t } = useI18n();
   // Use Zustand store with selectors for optimized re-renders
   const settings = useSettings();
@@ -56,6 +60,12 @@ export function ImageEditor({ imagePath, onSave, onCancel }: ImageEditorProps) {
   const [isCopying, setIsCopying] = useState(false);
   const [tempDir, setTempDir] = useState<string>("/private/tmp");
 
+  // Project state: the opened project replaces the capture being edited
+  const [openedProject, setOpenedProject] = useState<LoadedProject | null>(null);
+  const [projectPath, setProjectPath] = useState<string | null>(null);
+  const [isProjectBusy, setIsProjectBusy] = useState(false);
+  const sourcePath = openedProject?.capturePath ?? imagePath;
+
    // Annotation UI state (not part of undo/redo)
   const [selectedTool, setSelectedTool] = useState<ToolType>("select");
   const [selectedAnnotation, setSelectedAnnotation] = useState<Annotation | null>(null);
@@ -71,7 +81,7 @@ export function ImageEditor({ imagePath, onSave, onCancel }: ImageEditorProps) {
     paddingBottom: settings.paddingBottom,
     paddingLeft: settings.paddingLeft,
     paddingRight: settings.paddingRight,
-    imagePath,
+    imagePath: sourcePath,
   });
 
   // Combined error
@@ -86,6 +96,8 @@ export function ImageEditor({ imagePath, onSave, onCancel }: ImageEditorProps) {
   useEffect(() => {
     editorActions.reset();
     editorActions.initialize();
+    setOpenedProject(null);
+    setProjectPath(null);
   }, [imagePath]);
 
   // Restore window state on mount
@@ -116,7 +128,7 @@ export function ImageEditor({ imagePath, onSave, onCancel }: ImageEditorProps) {
     setImageLoaded(false);
     setScreenshotImage(null);
 
-    if (!imagePath) {
+    if (!sourcePath) {
       setLoadError("No image path provided");
       return;
     }
@@ -126,6 +138,15 @@ export function ImageEditor({ imagePath, onSave, onCancel }: ImageEditorProps) {
       setScreenshotImage(img);
       setImageLoaded(true);
 
+      if (openedProject) {
+        const saved = openedProject.editorState as Partial<EditorSettings> | null;
+        actions.restore(
+          { ...useEditorStore.getState().settings, ...saved },
+          openedProject.annotations,
+        );
+        return;
+      }
+
       // Calculate smart default padding: 10% of average dimension, capped at 400px
       const avgDimension = (img.width + img.height) / 2;
       const defaultPadding = Math.min(Math.round(avgDimension * 0.1), 400);
@@ -135,10 +156,10 @@ export function ImageEditor({ imagePath, onSave, onCancel }: ImageEditorProps) {
       actions.setPaddingRightTransient(defaultPadding);
     };
     img.onerror = () => {
-      setLoadError(`Failed to load image from: ${imagePath}`);
+      setLoadError(`Failed to load image from: ${sourcePath}`);
     };
 
-    const assetUrl = convertFileSrc(imagePath);
+    const assetUrl = convertFileSrc(sourcePath);
     img.crossOrigin = "anonymous";
     img.src = assetUrl;
 
@@ -146,7 +167,7 @@ export function ImageEditor({ imagePath, onSave, onCancel }: ImageEditorProps) {
       img.onload = null;
       img.onerror = null;
     };
-  }, [imagePath, actions]);
+  }, [sourcePath, openedProject, actions]);
 
   // Save handler
   const handleSave = useCallback(async () => {
@@ -154,7 +175,7 @@ export function ImageEditor({ imagePath, onSave, onCancel }: ImageEditorProps) {
     
     setIsSaving(true);
     try {
-      const highQualityCanvas = await renderHighQualityCanvas(annotations, imagePath);
+      const highQualityCanvas = await renderHighQualityCanvas(annotations, sourcePath);
       
       if (!highQualityCanvas) {
         setIsSaving(false);
@@ -164,28 +185,18 @@ export function ImageEditor({ imagePath, onSave, onCancel }: ImageEditorProps) {
       highQualityCanvas.toBlob(
         (blob) => {
           if (blob) {
-            const reader = new FileReader();
-            reader.onloadend = () => {
-              onSave(reader.result as string);
-              setIsSaving(false);
-            };
-            reader.onerror = () => {
-              setLoadError("Failed to read image data");
-              setIsSaving(false);
-            };
-            reader.readAsDataURL(blob);
-          } else {
-            setIsSaving(false);
+            onSave(blob);
           }
+          setIsSaving(false);
         },
         "image/png",
         1.0
       );
     } catch (err) {
-      setLoadError(`Failed to save: ${err instanceof Error ? err.message : String(err)}`);
+      setLoadError(`Failed to save: ${errorMessage(err)}`);
       setIsSaving(false);
     }
-  }, [screenshotImage, annotations, renderHighQualityCanvas, onSave, isSaving, isCopying, imagePath]);
+  }, [screenshotImage, annotations, renderHighQualityCanvas, onSave, isSaving, isCopying, sourcePath]);
 
   // Copy handler
   const handleCopy = useCallback(async () => {
@@ -193,17 +204,19 @@ export function ImageEditor({ imagePath, onSave, onCancel }: ImageEditorProps) {
     
     setIsCopying(true);
     try {
-      const highQualityCanvas = await renderHighQualityCanvas(annotations, imagePath);
+      const highQualityCanvas = await renderHighQualityCanvas(annotations, sourcePath);
       
       if (!highQualityCanvas) {
         setIsCopying(false);
         return;
       }
 
-      const dataUrl = highQualityCanvas.toDataURL("image/png");
-      
-      const saved = await invoke<SaveImageResponse>("save_edited_image", {
-        imageData: dataUrl,
+      const blob = await new Promise<Blob | null>((resolve) =>
+        highQualityCanvas.toBlob(resolve, "image/png", 1.0)
+      );
+      if (!blob) throw new Error("Failed to create blob from canvas");
+
+      const saved = await saveImage(blob, {
         saveDir: tempDir,
         copyToClip: true,
       });
@@ -218,16 +231,69 @@ export function ImageEditor({ imagePath, onSave, onCancel }: ImageEditorProps) {
         duration: 2000,
       });
     } catch (err) {
-      const errorMessage = err instanceof Error ? err.message : String(err);
-      setLoadError(`Failed to copy: ${errorMessage}`);
+      const message = errorMessage(err);
+      setLoadError(`Failed to copy: ${message}`);
       toast.error(t("editor.copyFailed"), {
-        description: errorMessage,
+        description: message,
         duration: 3000,
       });
     } finally {
       setIsCopying(false);
     }
-  }, [screenshotImage, annotations, renderHighQualityCanvas, isSaving, isCopying, tempDir, imagePath, t]);
+  }, [screenshotImage, annotations, renderHighQualityCanvas, isSaving, isCopying, tempDir, sourcePath, t]);
+
+  // Project handlers
+  const handleSaveProject = useCallback(async () => {
+    if (!imageLoaded || isProjectBusy) return;
+
+    setIsProjectBusy(true);
+    try {
+      const path = await saveProject(
+        sourcePath,
+        { annotations, settings: await resolveRustRenderSettings(settings), editorState: settings },
+        { projectPath, saveDir, jobId: createJobId("render") },
+      );
+      setProjectPath(path);
+      toast.success(t("editor.projectSaved"), {
+        description: path,
+        duration: 3000,
+      });
+    } catch (err) {
+      toast.error(t("editor.projectSaveFailed"), {
+        description: errorMessage(err),
+        duration: 5000,
+      });
+    } finally {
+      setIsProjectBusy(false);
+    }
+  }, [imageLoaded, isProjectBusy, sourcePath, annotations, settings, projectPath, saveDir, t]);
+
+  const handleOpenProject = useCallback(async () => {
+    if (isProjectBusy) return;
+
+    setIsProjectBusy(true);
+    try {
+      const selected = await open({
+        multiple: false,
+        directory: false,
+        defaultPath: saveDir || undefined,
+        filters: [{ name: t("editor.projectFiles"), extensions: [PROJECT_EXTENSION] }],
+      });
+      if (!selected || Array.isArray(selected)) return;
+
+      const project = await loadProject(selected, tempDir, createJobId("render"));
+      setSelectedAnnotation(null);
+      setOpenedProject(project);
+      setProjectPath(project.path);
+    } catch (err) {
+      toast.error(t("editor.projectOpenFailed"), {
+        description: errorMessage(err),
+        duration: 5000,
+      });
+    } finally {
+      setIsProjectBusy(false);
+    }
+  }, [isProjectBusy, saveDir, tempDir, t]);
 
   // Annotation handlers
   const handleAnnotationAdd = useCallback((annotation: Annotation) => {
@@ -395,6 +461,42 @@ export function ImageEditor({ imagePath, onSave, onCancel }: ImageEditorProps) {
             {t("common.cancel")}
           </Button>
           <TooltipProvider>
+            <Tooltip>
+              <TooltipTrigger asChild>
+                <Button
+                  variant="cta"
+                  onClick={handleOpenProject}
+                  disabled={isProjectBusy}
+                  className="disabled:opacity-50"
+                >
+                  <FolderOpen className="size-4" aria-hidden="true" />
+                  <span>{t("editor.openProject")}</span>
+                </Button>
+              </TooltipTrigger>
+              <TooltipContent>
+                <p>{t("editor.openProject")}</p>
+              </TooltipContent>
+            </Tooltip>
+            <Tooltip>
+              <TooltipTrigger asChild>
+                <Button
+                  variant="cta"
+                  onClick={handleSaveProject}
+                  disabled={!imageLoaded || isProjectBusy}
+                  className="disabled:opacity-50"
+                >
+                  {isProjectBusy ? (
+                    <Loader2 className="size-4 animate-spin" aria-hidden="true" />
+                  ) : (
+                    <Save className="size-4" aria-hidden="true" />
+                  )}
+                  <span>{t("editor.saveProject")}</span>
+                </Button>
+              </TooltipTrigger>
+              <TooltipContent>
+                <p>{t("editor.saveProject")}</p>
+              </TooltipContent>
+            </Tooltip>
             <Tooltip>
               <TooltipTrigger asChild>
                 <Button 
@@ -515,7 +617,7 @@ export function ImageEditor({ imagePath, onSave, onCancel }: ImageEditorProps) {
                       <strong className="block mb-1 text-red-300">Error:</strong>
                       {error}
                       <br />
-                      <small className="text-foreground0 break-all mt-2 block text-pretty">Path: {imagePath}</small>
+                      <small className="text-foreground0 break-all mt-2 block text-pretty">Path: {sourcePath}</small>
                     </div>
                   </CardContent>
                 </Card>
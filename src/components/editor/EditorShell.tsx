@@ -2,14 +2,15 @@ import { ImageEditor } from "@/components/ImageEditor";
 
 type EditorShellProps = {
   imagePath: string;
-  onSave: (editedImageData: string) => void;
+  saveDir: string;
+  onSave: (editedImage: Blob) => void;
   onCancel: () => void;
 };
 
-export function EditorShell({ imagePath, onSave, onCancel }: EditorShellProps) {
+export function EditorShell({ imagePath, saveDir, onSave, onCancel }: EditorShellProps) {
   return (
     <main className="min-h-dvh bg-background text-foreground">
-      <ImageEditor imagePath={imagePath} onSave={onSave} onCancel={onCancel} />
+      <ImageEditor imagePath={imagePath} saveDir={saveDir} onSave={onSave} onCancel={onCancel} />
     </main>
   );
 }
@@ -12,6 +12,12 @@ import type { KeyboardShortcut } from "./KeyboardShortcutManager";
 import { useI18n } from "@/i18n/useI18n";
 import type { AppLanguage } from "@/i18n/provider";
 import type { HotkeyHealthSnapshot } from "@/lib/hotkey-health";
+import { errorMessage } from "@/lib/capture-errors";
+import {
+  DEFAULT_SCROLL_MATCH_REGION,
+  type ScrollDirection,
+  type ScrollMatchRegion,
+} from "@/lib/scroll-session";
 
 interface PreferencesPageProps {
   onBack: () => void;
@@ -23,6 +29,17 @@ interface PreferencesPageProps {
 interface GeneralSettings {
   saveDir: string;
   language: AppLanguage;
+  scrollDirection: ScrollDirection;
+  scrollMatchRegion: ScrollMatchRegion;
+}
+
+const SELECT_CLASS =
+  "w-full px-3 py-2 bg-secondary border border-border rounded-lg text-card-foreground focus:outline-none focus:ring-2 focus:ring-blue-500/50 focus:border-blue-500 transition-all text-sm";
+
+/** Clamp a match region edge to whole percents in `min..=max`. */
+function clampPercent(value: number, min: number, max: number): number {
+  if (!Number.isFinite(value)) return min;
+  return Math.min(max, Math.max(min, Math.round(value)));
 }
 
 export function PreferencesPage({ onBack, onSettingsChange, shortcuts, hotkeyHealth }: PreferencesPageProps) {
@@ -30,6 +47,8 @@ export function PreferencesPage({ onBack, onSettingsChange, shortcuts, hotkeyHea
   const [settings, setSettings] = useState<GeneralSettings>({
     saveDir: "",
     language,
+    scrollDirection: "Vertical",
+    scrollMatchRegion: DEFAULT_SCROLL_MATCH_REGION,
   });
   const [isLoading, setIsLoading] = useState(true);
   const [saveDirError, setSaveDirError] = useState<string | null>(null);
@@ -40,10 +59,14 @@ export function PreferencesPage({ onBack, onSettingsChange, shortcuts, hotkeyHea
         const store = await Store.load("settings.json");
         const saveDir = await store.get<string>("saveDir");
         const storedLanguage = await store.get<AppLanguage>("language");
+        const scrollDirection = await store.get<ScrollDirection>("scrollDirection");
+        const scrollMatchRegion = await store.get<ScrollMatchRegion>("scrollMatchRegion");
 
         setSettings({
           saveDir: saveDir || "",
           language: storedLanguage === "zh-CN" || storedLanguage === "en" ? storedLanguage : language,
+          scrollDirection: scrollDirection === "Horizontal" ? "Horizontal" : "Vertical",
+          scrollMatchRegion: scrollMatchRegion ?? DEFAULT_SCROLL_MATCH_REGION,
         });
       } catch (err) {
         console.error("Failed to load settings:", err);
@@ -84,7 +107,7 @@ export function PreferencesPage({ onBack, onSettingsChange, shortcuts, hotkeyHea
       await invoke("validate_save_directory", { path: selected });
       await saveSetting("saveDir", selected);
     } catch (err) {
-      const message = err instanceof Error ? err.message : String(err);
+      const message = errorMessage(err);
       setSaveDirError(message || t("preferences.toast.dirInvalid"));
       toast.error(t("preferences.toast.dirInvalid"), {
         description: message,
@@ -97,6 +120,18 @@ export function PreferencesPage({ onBack, onSettingsChange, shortcuts, hotkeyHea
     await saveSetting("language", nextLanguage);
   }, [saveSetting, setLanguage]);
 
+  const handleMatchRegionChange = useCallback(
+    (edge: keyof ScrollMatchRegion, value: number) => {
+      const current = settings.scrollMatchRegion;
+      const next =
+        edge === "start_percent"
+          ? { ...current, start_percent: clampPercent(value, 0, current.end_percent - 1) }
+          : { ...current, end_percent: clampPercent(value, current.start_percent + 1, 100) };
+      void saveSetting("scrollMatchRegion", next);
+    },
+    [saveSetting, settings.scrollMatchRegion],
+  );
+
   const handleShortcutsChange = useCallback((_shortcuts: KeyboardShortcut[]) => {
     onSettingsChange?.();
   }, [onSettingsChange]);
@@ -180,7 +215,7 @@ export function PreferencesPage({ onBack, onSettingsChange, shortcuts, hotkeyHea
                   const next = event.target.value as AppLanguage;
                   void handleLanguageChange(next);
                 }}
-                className="w-full px-3 py-2 bg-secondary border border-border rounded-lg text-card-foreground focus:outline-none focus:ring-2 focus:ring-blue-500/50 focus:border-blue-500 transition-all text-sm"
+                className={SELECT_CLASS}
               >
                 <option value="en">{t("preferences.lang.en")}</option>
                 <option value="zh-CN">{t("preferences.lang.zh-CN")}</option>
@@ -189,6 +224,59 @@ export function PreferencesPage({ onBack, onSettingsChange, shortcuts, hotkeyHea
           </CardContent>
         </Card>
 
+        <Card className="bg-card border-border">
+          <CardHeader className="pb-4">
+            <CardTitle className="text-lg font-semibold text-card-foreground">{t("preferences.scrollCapture")}</CardTitle>
+          </CardHeader>
+          <CardContent className="space-y-5">
+            <div className="space-y-2">
+              <label htmlFor="scroll-direction" className="text-sm font-medium text-foreground">
+                {t("preferences.scrollDirection")}
+              </label>
+              <select
+                id="scroll-direction"
+                value={settings.scrollDirection}
+                onChange={(event) => {
+                  void saveSetting("scrollDirection", event.target.value as ScrollDirection);
+                }}
+                className={SELECT_CLASS}
+              >
+                <option value="Vertical">{t("preferences.scrollDirection.vertical")}</option>
+                <option value="Horizontal">{t("preferences.scrollDirection.horizontal")}</option>
+              </select>
+            </div>
+
+            <div className="space-y-2">
+              <span className="text-sm font-medium text-foreground">{t("preferences.scrollMatchRegion")}</span>
+              <div className="grid grid-cols-2 gap-3">
+                <label className="space-y-1 text-xs text-muted-foreground">
+                  {t("preferences.scrollMatchRegion.start")}
+                  <input
+                    type="number"
+                    min={0}
+                    max={settings.scrollMatchRegion.end_percent - 1}
+                    value={settings.scrollMatchRegion.start_percent}
+                    onChange={(event) => handleMatchRegionChange("start_percent", event.target.valueAsNumber)}
+                    className={SELECT_CLASS}
+                  />
+                </label>
+                <label className="space-y-1 text-xs text-muted-foreground">
+                  {t("preferences.scrollMatchRegion.end")}
+                  <input
+                    type="number"
+                    min={settings.scrollMatchRegion.start_percent + 1}
+                    max={100}
+                    value={settings.scrollMatchRegion.end_percent}
+                    onChange={(event) => handleMatchRegionChange("end_percent", event.target.valueAsNumber)}
+                    className={SELECT_CLASS}
+                  />
+                </label>
+              </div>
+              <p className="text-xs text-muted-foreground">{t("preferences.scrollMatchRegionHint")}</p>
+            </div>
+          </CardContent>
+        </Card>
+
         <Card className="bg-card border-border">
           <CardHeader className="pb-4">
             <CardTitle className="text-lg font-semibold text-card-foreground">{t("preferences.defaultBackground")}</CardTitle>
@@ -1,6 +1,7 @@
-import { convertFileSrc } from "@tauri-apps/api/core";
 import { useMemo } from "react";
 import { useI18n } from "@/i18n/useI18n";
+import { frameUrl } from "@/lib/frame-store";
+import type { JobProgress } from "@/lib/jobs";
 import { getScrollPreviewPlacement } from "@/ui-workflows/capture-shell/geometry";
 import type {
   ActiveMonitorContext,
@@ -11,9 +12,11 @@ import type {
 type ScrollPreviewPanelProps = {
   monitor: ActiveMonitorContext;
   rect: CaptureRect;
-  previewPath: string | null;
+  previewFrameId: string | null;
   frameCount: number;
   isScrolling: boolean;
+  /** Progress of the running stitch job, if any. */
+  stitchProgress?: JobProgress | null;
 };
 
 const PANEL_WIDTH = 340;
@@ -22,9 +25,10 @@ const PANEL_GAP = 12;
 export function ScrollPreviewPanel({
   monitor,
   rect,
-  previewPath,
+  previewFrameId,
   frameCount,
   isScrolling,
+  stitchProgress = null,
 }: ScrollPreviewPanelProps) {
   const { t } = useI18n();
 
@@ -53,14 +57,21 @@ export function ScrollPreviewPanel({
       <div className="flex items-center justify-between border-b border-border px-3 py-2 text-xs text-muted-foreground">
         <span>{t("app.scroll.previewTitle")}</span>
         <span className="tabular-nums">
-          {isScrolling ? t("app.scroll.scrolling") : t("app.scroll.frameCount", { count: String(frameCount) })}
+          {stitchProgress
+            ? t("app.scroll.stitchProgress", {
+                done: String(stitchProgress.done),
+                total: String(stitchProgress.total),
+              })
+            : isScrolling
+              ? t("app.scroll.scrolling")
+              : t("app.scroll.frameCount", { count: String(frameCount) })}
         </span>
       </div>
 
       <div className="max-h-[360px] overflow-auto bg-secondary/30 p-2">
-        {previewPath ? (
+        {previewFrameId ? (
           <img
-            src={convertFileSrc(previewPath)}
+            src={frameUrl(previewFrameId)}
             alt={t("app.scroll.previewTitle")}
             className="h-auto w-full rounded border border-border bg-background"
             draggable={false}
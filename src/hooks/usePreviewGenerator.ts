@@ -2,6 +2,12 @@ import { useRef, useEffect, useCallback, useState, useMemo } from "react";
 import { EditorSettings } from "@/stores/editorStore";
 import { createHighQualityCanvas } from "@/lib/canvas-utils";
 import { drawAnnotationOnCanvas } from "@/lib/annotation-utils";
+import { errorMessage } from "@/lib/capture-errors";
+import {
+  getBackgroundImageSrc,
+  renderImageWithEffects,
+  resolveRustRenderSettings,
+} from "@/lib/image-ipc";
 import { Annotation } from "@/types/annotations";
 
 // Image cache with LRU-like cleanup (max 20 images)
@@ -47,19 +53,6 @@ export async function loadImage(src: string): Promise<HTMLImageElement> {
   });
 }
 
-/**
- * Get the background image source based on settings
- */
-function getBackgroundImageSrc(settings: EditorSettings): string | null {
-  if (settings.backgroundType === "image" && settings.selectedImageSrc) {
-    return settings.selectedImageSrc;
-  }
-  if (settings.backgroundType === "gradient" && settings.gradientSrc) {
-    return settings.gradientSrc;
-  }
-  return null;
-}
-
 /**
  * Draw background on a canvas context
  */
@@ -397,7 +390,7 @@ export function usePreviewGenerator({
       }, "image/png");
     } catch (err) {
       if (currentRenderId === renderIdRef.current) {
-        const message = err instanceof Error ? err.message : String(err);
+        const message = errorMessage(err);
         setError(`Preview generation failed: ${message}`);
         setIsGenerating(false);
         console.error("Preview generation failed:", err);
@@ -465,35 +458,29 @@ export function usePreviewGenerator({
       if (!screenshotImage) return null;
 
       try {
-        if (settings.blurAmount > 0 && imagePath && (settings.backgroundType === "transparent" || settings.backgroundType === "white" || settings.backgroundType === "black" || settings.backgroundType === "gray" || settings.backgroundType === "custom")) {
+        if (settings.blurAmount > 0 && imagePath) {
           try {
-            const { invoke } = await import("@tauri-apps/api/core");
-            const rustRendered = await invoke<string>("render_image_with_effects_rust", {
-              imagePath,
-              settings: {
-                background_type: settings.backgroundType,
-                custom_color: settings.customColor,
-                blur_amount: settings.blurAmount,
-                noise_amount: settings.noiseAmount,
-                border_radius: settings.borderRadius,
-                padding_top: paddingTop,
-                padding_bottom: paddingBottom,
-                padding_left: paddingLeft,
-                padding_right: paddingRight,
-                shadow_blur: settings.shadow.blur,
-                shadow_offset_x: settings.shadow.offsetX,
-                shadow_offset_y: settings.shadow.offsetY,
-                shadow_opacity: settings.shadow.opacity,
-              },
+            const rendered = await renderImageWithEffects(imagePath, {
+              ...(await resolveRustRenderSettings(settings, annotations)),
+              padding_top: paddingTop,
+              padding_bottom: paddingBottom,
+              padding_left: paddingLeft,
+              padding_right: paddingRight,
             });
 
+            const renderedUrl = URL.createObjectURL(rendered);
             const img = new Image();
-            await new Promise<void>((resolve, reject) => {
-              img.onload = () => resolve();
-              img.onerror = () => reject(new Error("Failed to load Rust-rendered image"));
-              img.src = rustRendered;
-            });
+            try {
+              await new Promise<void>((resolve, reject) => {
+                img.onload = () => resolve();
+                img.onerror = () => reject(new Error("Failed to load Rust-rendered image"));
+                img.src = renderedUrl;
+              });
+            } finally {
+              URL.revokeObjectURL(renderedUrl);
+            }
 
+            // Annotations are drawn by the backend.
             const canvas = document.createElement("canvas");
             canvas.width = img.width;
             canvas.height = img.height;
@@ -502,12 +489,6 @@ export function usePreviewGenerator({
 
             ctx.drawImage(img, 0, 0);
 
-            if (annotations.length > 0) {
-              annotations.forEach((annotation) => {
-                drawAnnotationOnCanvas(ctx, annotation);
-              });
-            }
-
             return canvas;
           } catch (rustErr) {
             console.warn("Rust rendering failed, falling back to JS:", rustErr);
@@ -548,7 +529,7 @@ export function usePreviewGenerator({
 
         return canvas;
       } catch (err) {
-        const message = err instanceof Error ? err.message : String(err);
+        const message = errorMessage(err);
         setError(`Failed to render high-quality image: ${message}`);
         return null;
       }
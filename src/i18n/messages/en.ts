@@ -66,11 +66,21 @@ export const enMessages: Record<string, string> = {
   "app.toast.scrollCopied": "Scroll screenshot copied to clipboard",
   "app.toast.scrollCancelled": "Scroll capture cancelled",
   "app.toast.scrollTimeout": "Scroll session timed out. Capture hotkeys were restored.",
+  "app.toast.scrollStoppedMaxHeight": "Reached the maximum scroll capture height",
+  "app.toast.scrollStoppedMaxFrames": "Reached the maximum number of scroll frames",
+  "app.toast.scrollStoppedNoNewContent": "No new content found while scrolling",
+  "app.toast.scrollStoppedFailures": "Frames stopped lining up. Finishing with what was captured.",
+  "app.toast.scrollRecoverable": "Interrupted scroll capture found",
+  "app.toast.scrollRecoverableFrames": "{count} frames can be recovered",
+  "app.toast.scrollRecoverAction": "Recover",
+  "app.toast.scrollRecovering": "Recovering scroll capture: {done}/{total} frames",
+  "app.toast.scrollRecoverFailed": "Could not recover the scroll capture",
 
   "app.scroll.selectDesc": "Drag to select area · ESC to cancel",
   "app.scroll.frameCount": "{count} frames",
   "app.scroll.finish": "Finish",
   "app.scroll.stitching": "Stitching...",
+  "app.scroll.stitchProgress": "Stitching {done}/{total} frames",
   "app.scroll.scrolling": "Scrolling...",
   "app.scroll.confirmStart": "Start",
   "app.scroll.shortcutsHint": "Capture hotkeys paused during scroll session · Space finish · Esc cancel",
@@ -92,6 +102,14 @@ export const enMessages: Record<string, string> = {
   "preferences.language": "Language",
   "preferences.lang.en": "English",
   "preferences.lang.zh-CN": "简体中文",
+  "preferences.scrollCapture": "Scroll Capture",
+  "preferences.scrollDirection": "Scroll direction",
+  "preferences.scrollDirection.vertical": "Vertical",
+  "preferences.scrollDirection.horizontal": "Horizontal",
+  "preferences.scrollMatchRegion": "Match region",
+  "preferences.scrollMatchRegion.start": "Start (%)",
+  "preferences.scrollMatchRegion.end": "End (%)",
+  "preferences.scrollMatchRegionHint": "Part of each frame, across the scroll direction, used to line frames up. Narrow it to skip sidebars or scrollbars.",
   "preferences.defaultBackground": "Default Background",
   "preferences.shortcuts": "Keyboard Shortcuts",
   "preferences.shortcuts.add": "Add",
@@ -122,6 +140,12 @@ export const enMessages: Record<string, string> = {
   "editor.saveFailed": "Failed to save",
   "editor.loadFailed": "Could not load image",
   "editor.export": "Export",
+  "editor.saveProject": "Save Project",
+  "editor.openProject": "Open Project",
+  "editor.projectSaved": "Project saved",
+  "editor.projectSaveFailed": "Failed to save project",
+  "editor.projectOpenFailed": "Failed to open project",
+  "editor.projectFiles": "Projects",
 
   "onboarding.welcome.title": "Welcome to Better Shot",
   "onboarding.welcome.desc": "Your open-source alternative to CleanShot X. Let's get you started with a quick tour of the app.",
@@ -66,11 +66,21 @@ export const zhCNMessages: Record<string, string> = {
   "app.toast.scrollCopied": "\u6EDA\u52A8\u622A\u56FE\u5DF2\u590D\u5236\u5230\u526A\u8D34\u677F",
   "app.toast.scrollCancelled": "\u6EDA\u52A8\u622A\u56FE\u5DF2\u53D6\u6D88",
   "app.toast.scrollTimeout": "\u6EDA\u52A8\u4F1A\u8BDD\u8D85\u65F6\uFF0C\u5DF2\u6062\u590D\u5E38\u89C4\u622A\u56FE\u5FEB\u6377\u952E\u3002",
+  "app.toast.scrollStoppedMaxHeight": "\u5DF2\u8FBE\u5230\u6EDA\u52A8\u622A\u56FE\u7684\u6700\u5927\u9AD8\u5EA6",
+  "app.toast.scrollStoppedMaxFrames": "\u5DF2\u8FBE\u5230\u6EDA\u52A8\u622A\u56FE\u7684\u6700\u5927\u5E27\u6570",
+  "app.toast.scrollStoppedNoNewContent": "\u6EDA\u52A8\u65F6\u672A\u53D1\u73B0\u65B0\u5185\u5BB9",
+  "app.toast.scrollStoppedFailures": "\u753B\u9762\u65E0\u6CD5\u7EE7\u7EED\u5BF9\u9F50\uFF0C\u5C06\u4F7F\u7528\u5DF2\u91C7\u96C6\u7684\u5185\u5BB9\u5B8C\u6210\u62FC\u63A5\u3002",
+  "app.toast.scrollRecoverable": "\u53D1\u73B0\u672A\u5B8C\u6210\u7684\u6EDA\u52A8\u622A\u56FE",
+  "app.toast.scrollRecoverableFrames": "\u53EF\u6062\u590D {count} \u5E27",
+  "app.toast.scrollRecoverAction": "\u6062\u590D",
+  "app.toast.scrollRecovering": "\u6B63\u5728\u6062\u590D\u6EDA\u52A8\u622A\u56FE\uFF1A{done}/{total} \u5E27",
+  "app.toast.scrollRecoverFailed": "\u65E0\u6CD5\u6062\u590D\u6EDA\u52A8\u622A\u56FE",
 
   "app.scroll.selectDesc": "\u62D6\u52A8\u9009\u62E9\u533A\u57DF \u00B7 ESC \u53D6\u6D88",
   "app.scroll.frameCount": "{count} \u5E27",
   "app.scroll.finish": "\u5B8C\u6210",
   "app.scroll.stitching": "\u62FC\u63A5\u4E2D...",
+  "app.scroll.stitchProgress": "\u6B63\u5728\u62FC\u63A5 {done}/{total} \u5E27",
   "app.scroll.scrolling": "\u6EDA\u52A8\u4E2D...",
   "app.scroll.confirmStart": "\u5F00\u59CB",
   "app.scroll.shortcutsHint": "\u6EDA\u52A8\u4F1A\u8BDD\u671F\u95F4\u6682\u505C\u5E38\u89C4\u622A\u56FE\u5FEB\u6377\u952E \u00B7 Space \u5B8C\u6210 \u00B7 Esc \u53D6\u6D88",
@@ -92,6 +102,14 @@ export const zhCNMessages: Record<string, string> = {
   "preferences.language": "\u8BED\u8A00",
   "preferences.lang.en": "English",
   "preferences.lang.zh-CN": "\u7B80\u4F53\u4E2D\u6587",
+  "preferences.scrollCapture": "\u6EDA\u52A8\u622A\u56FE",
+  "preferences.scrollDirection": "\u6EDA\u52A8\u65B9\u5411",
+  "preferences.scrollDirection.vertical": "\u5782\u76F4",
+  "preferences.scrollDirection.horizontal": "\u6C34\u5E73",
+  "preferences.scrollMatchRegion": "\u5339\u914D\u533A\u57DF",
+  "preferences.scrollMatchRegion.start": "\u8D77\u59CB (%)",
+  "preferences.scrollMatchRegion.end": "\u7ED3\u675F (%)",
+  "preferences.scrollMatchRegionHint": "\u7528\u4E8E\u5BF9\u9F50\u5E27\u7684\u753B\u9762\u8303\u56F4\uFF08\u5782\u76F4\u4E8E\u6EDA\u52A8\u65B9\u5411\uFF09\u3002\u7F29\u5C0F\u8303\u56F4\u53EF\u907F\u5F00\u4FA7\u680F\u6216\u6EDA\u52A8\u6761\u3002",
   "preferences.defaultBackground": "\u9ED8\u8BA4\u80CC\u666F",
   "preferences.shortcuts": "\u5FEB\u6377\u952E",
   "preferences.shortcuts.add": "\u65B0\u589E",
@@ -122,6 +140,12 @@ export const zhCNMessages: Record<string, string> = {
   "editor.saveFailed": "\u4FDD\u5B58\u5931\u8D25",
   "editor.loadFailed": "\u56FE\u7247\u52A0\u8F7D\u5931\u8D25",
   "editor.export": "\u5BFC\u51FA",
+  "editor.saveProject": "\u4FDD\u5B58\u9879\u76EE",
+  "editor.openProject": "\u6253\u5F00\u9879\u76EE",
+  "editor.projectSaved": "\u9879\u76EE\u5DF2\u4FDD\u5B58",
+  "editor.projectSaveFailed": "\u4FDD\u5B58\u9879\u76EE\u5931\u8D25",
+  "editor.projectOpenFailed": "\u6253\u5F00\u9879\u76EE\u5931\u8D25",
+  "editor.projectFiles": "\u9879\u76EE",
 
   "onboarding.welcome.title": "\u6B22\u8FCE\u4F7F\u7528 Better Shot",
   "onboarding.welcome.desc": "\u5F00\u6E90\u7684 CleanShot X \u66FF\u4EE3\u65B9\u6848\uFF0C\u5148\u5FEB\u901F\u4E86\u89E3\u4E00\u4E0B\u6838\u5FC3\u529F\u80FD\u3002",
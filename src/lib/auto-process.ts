@@ -2,6 +2,7 @@ import { convertFileSrc } from "@tauri-apps/api/core";
 import { Store } from "@tauri-apps/plugin-store";
 import { createHighQualityCanvas } from "./canvas-utils";
 import { resolveBackgroundPath, getDefaultBackgroundPath } from "./asset-registry";
+import { blobToDataUrl } from "./image-ipc";
 
 type BackgroundType = "transparent" | "white" | "black" | "gray" | "custom" | "image" | "gradient";
 
@@ -36,7 +37,7 @@ function loadImage(src: string, context: string): Promise<HTMLImageElement> {
   });
 }
 
-function canvasToDataUrl(canvas: HTMLCanvasElement): Promise<string> {
+function canvasToBlob(canvas: HTMLCanvasElement): Promise<Blob> {
   return new Promise((resolve, reject) => {
     canvas.toBlob(
       (blob) => {
@@ -44,14 +45,7 @@ function canvasToDataUrl(canvas: HTMLCanvasElement): Promise<string> {
           reject(new Error("Failed to create blob from canvas"));
           return;
         }
-        const reader = new FileReader();
-        reader.onloadend = () => {
-          resolve(reader.result as string);
-        };
-        reader.onerror = () => {
-          reject(new Error("Failed to read processed image"));
-        };
-        reader.readAsDataURL(blob);
+        resolve(blob);
       },
       "image/png",
       1.0,
@@ -59,9 +53,17 @@ function canvasToDataUrl(canvas: HTMLCanvasElement): Promise<string> {
   });
 }
 
+/** Like `renderScreenshotWithDefaultBackground`, as a PNG data URL. */
 export async function processScreenshotWithDefaultBackground(
   imagePath: string,
 ): Promise<string> {
+  return blobToDataUrl(await renderScreenshotWithDefaultBackground(imagePath));
+}
+
+/** Apply the default background settings to a screenshot, as a PNG blob. */
+export async function renderScreenshotWithDefaultBackground(
+  imagePath: string,
+): Promise<Blob> {
   let backgroundType: BackgroundType = "image";
   let customColor = "#667eea";
   let defaultBgImage = getDefaultBackgroundPath();
@@ -137,7 +139,7 @@ export async function processScreenshotWithDefaultBackground(
   });
 
   return withTimeout(
-    canvasToDataUrl(canvas),
+    canvasToBlob(canvas),
     CANVAS_EXPORT_TIMEOUT_MS,
     "Timed out exporting processed screenshot",
   );
@@ -4,10 +4,67 @@ export type CaptureErrorKind =
   | "io"
   | "timeout"
   | "processing"
-  | "ocr_empty";
+  | "stitch"
+  | "validation"
+  | "ocr_empty"
+  | "busy"
+  | "unsupported";
 
-export function classifyCaptureError(message: string): CaptureErrorKind {
-  const lower = message.toLowerCase();
+/** Error kinds returned by Tauri commands (`capture_core::CaptureErrorKind`). */
+export type BackendErrorKind =
+  | "permission"
+  | "cancelled"
+  | "capture_failed"
+  | "stitch_failed"
+  | "command_failed"
+  | "validation_failed"
+  | "io"
+  | "timeout"
+  | "ocr_empty"
+  | "busy"
+  | "unsupported";
+
+/** Serialized `CaptureError`, the rejection value of every Tauri command. */
+export interface BackendError {
+  kind: BackendErrorKind;
+  message: string;
+  details?: Record<string, string>;
+}
+
+export function isBackendError(error: unknown): error is BackendError {
+  return (
+    typeof error === "object" &&
+    error !== null &&
+    typeof (error as BackendError).kind === "string" &&
+    typeof (error as BackendError).message === "string"
+  );
+}
+
+/** Human-readable message for anything thrown or rejected. */
+export function errorMessage(error: unknown): string {
+  if (error instanceof Error) return error.message;
+  if (isBackendError(error)) return error.message;
+  return String(error);
+}
+
+const BACKEND_KINDS: Partial<Record<BackendErrorKind, CaptureErrorKind>> = {
+  permission: "permission",
+  cancelled: "cancelled",
+  stitch_failed: "stitch",
+  validation_failed: "validation",
+  io: "io",
+  timeout: "timeout",
+  ocr_empty: "ocr_empty",
+  busy: "busy",
+  unsupported: "unsupported",
+};
+
+export function classifyCaptureError(error: unknown): CaptureErrorKind {
+  if (isBackendError(error)) {
+    return BACKEND_KINDS[error.kind] ?? "io";
+  }
+
+  const lower = errorMessage(error).toLowerCase();
   if (lower.includes("cancelled") || lower.includes("was cancelled")) return "cancelled";
   if (
     lower.includes("permission") ||
@@ -21,7 +78,7 @@ export function classifyCaptureError(message: string): CaptureErrorKind {
   if (
     lower.includes("failed to process screenshot") ||
     lower.includes("failed to load background image") ||
-    lower.includes("failed to read processed image") ||
+    lower.includes("failed to read image data") ||
     lower.includes("failed to create blob")
   ) return "processing";
   return "io";
@@ -0,0 +1,16 @@
+import { convertFileSrc } from "@tauri-apps/api/core";
+
+/** URI scheme served by the backend frame store (`frame_store::FRAME_SCHEME`). */
+export const FRAME_SCHEME = "frame";
+
+/** A frame kept in memory by the backend (`frame_store::FrameHandle`). */
+export type FrameHandle = {
+  id: string;
+  width: number;
+  height: number;
+};
+
+/** URL the webview loads a stored frame from. */
+export function frameUrl(id: string): string {
+  return convertFileSrc(id, FRAME_SCHEME);
+}
@@ -0,0 +1,218 @@
+import { invoke } from "@tauri-apps/api/core";
+import { isBackendError } from "@/lib/capture-errors";
+import type { EditorSettings } from "@/stores";
+import type { Annotation } from "@/types/annotations";
+
+export type SaveImageResponse = {
+  path: string;
+  copy_warning?: string | null;
+};
+
+export type RustGradientStop = { offset: number; color: string };
+
+/** Gradient spec for `background_type: "gradient"` (`image::GradientSpec`). */
+export type RustGradient =
+  /** Without an `angle` the gradient runs from the top left to the bottom right corner. */
+  | { kind: "linear"; angle?: number | null; stops: RustGradientStop[] }
+  | { kind: "radial"; center?: [number, number]; radius?: number; stops: RustGradientStop[] }
+  | { kind: "mesh"; points: { x: number; y: number; color: string }[] };
+
+export type RustBackgroundFit = "stretch" | "cover" | "contain" | "tile";
+
+/** Settings accepted by the Rust renderer (`image::RenderSettings`). */
+export type RustRenderSettings = {
+  background_type: string;
+  custom_color: string;
+  blur_amount: number;
+  noise_amount: number;
+  border_radius: number;
+  padding_top: number;
+  padding_bottom: number;
+  padding_left: number;
+  padding_right: number;
+  shadow_blur: number;
+  shadow_offset_x: number;
+  shadow_offset_y: number;
+  shadow_opacity: number;
+  gradient?: RustGradient | null;
+  /** Image file path or base64 data URL for `background_type: "image"`. */
+  background_image?: string | null;
+  background_fit?: RustBackgroundFit;
+  /** Grow the padding so the shadow is never cut off. Defaults to true. */
+  shadow_auto_padding?: boolean;
+  /** Per-corner radii, used instead of `border_radius` when set. */
+  corner_radii?: {
+    top_left: number;
+    top_right: number;
+    bottom_right: number;
+    bottom_left: number;
+  } | null;
+  /** Stroke drawn just inside the screenshot's rounded edge. */
+  border?: { width: number; color: string } | null;
+  /** Annotations drawn by the backend, placed as in the editor canvas. */
+  annotations?: Annotation[];
+};
+
+/** The wallpaper the editor draws behind the screenshot, if any. */
+export function getBackgroundImageSrc(settings: EditorSettings): string | null {
+  if (settings.backgroundType === "image" && settings.selectedImageSrc) {
+    return settings.selectedImageSrc;
+  }
+  if (settings.backgroundType === "gradient" && settings.gradientSrc) {
+    return settings.gradientSrc;
+  }
+  return null;
+}
+
+/**
+ * `src` as a data URL. Bundled wallpapers are only reachable through the
+ * webview, so they are fetched here and handed to the backend inline.
+ */
+export async function imageSrcToDataUrl(src: string): Promise<string> {
+  if (src.startsWith("data:")) return src;
+  const response = await fetch(src);
+  if (!response.ok) {
+    throw new Error(`Failed to load background image: ${src}`);
+  }
+  return blobToDataUrl(await response.blob());
+}
+
+/**
+ * The editor's settings as the Rust renderer takes them. `backgroundImage`
+ * is the wallpaper from `getBackgroundImageSrc` as a path or data URL, and
+ * is stretched over the canvas as the editor draws it; without one,
+ * gradients are drawn from their two colors, corner to corner.
+ */
+export function toRustRenderSettings(
+  settings: EditorSettings,
+  annotations: Annotation[] = [],
+  backgroundImage: string | null = null,
+): RustRenderSettings {
+  const [from, to] = settings.gradientColors;
+  return {
+    background_type: backgroundImage ? "image" : settings.backgroundType,
+    custom_color: settings.customColor,
+    blur_amount: settings.blurAmount,
+    noise_amount: settings.noiseAmount,
+    border_radius: settings.borderRadius,
+    padding_top: settings.paddingTop,
+    padding_bottom: settings.paddingBottom,
+    padding_left: settings.paddingLeft,
+    padding_right: settings.paddingRight,
+    shadow_blur: settings.shadow.blur,
+    shadow_offset_x: settings.shadow.offsetX,
+    shadow_offset_y: settings.shadow.offsetY,
+    shadow_opacity: settings.shadow.opacity,
+    gradient:
+      !backgroundImage && settings.backgroundType === "gradient"
+        ? {
+            kind: "linear",
+            stops: [
+              { offset: 0, color: from },
+              { offset: 1, color: to },
+            ],
+          }
+        : null,
+    background_image: backgroundImage,
+    background_fit: "stretch",
+    // Annotations are placed by the editor's padding.
+    shadow_auto_padding: false,
+    annotations,
+  };
+}
+
+/** `toRustRenderSettings` with the editor's wallpaper, if any, inlined. */
+export async function resolveRustRenderSettings(
+  settings: EditorSettings,
+  annotations: Annotation[] = [],
+): Promise<RustRenderSettings> {
+  const src = getBackgroundImageSrc(settings);
+  const backgroundImage = src ? await imageSrcToDataUrl(src) : null;
+  return toRustRenderSettings(settings, annotations, backgroundImage);
+}
+
+export type SaveImageOptions = {
+  saveDir: string;
+  copyToClip: boolean;
+};
+
+const PNG = "image/png";
+
+export function blobToDataUrl(blob: Blob): Promise<string> {
+  return new Promise((resolve, reject) => {
+    const reader = new FileReader();
+    reader.onloadend = () => resolve(reader.result as string);
+    reader.onerror = () => reject(new Error("Failed to read image data"));
+    reader.readAsDataURL(blob);
+  });
+}
+
+function dataUrlToBlob(dataUrl: string): Blob {
+  const [header, data] = dataUrl.split(",", 2);
+  const type = /^data:([^;]+)/.exec(header)?.[1] ?? PNG;
+  const binary = atob(data ?? "");
+  const bytes = new Uint8Array(binary.length);
+  for (let i = 0; i < binary.length; i++) {
+    bytes[i] = binary.charCodeAt(i);
+  }
+  return new Blob([bytes], { type });
+}
+
+/**
+ * Save an encoded image, sending its bytes as a binary IPC body. Falls back
+ * to the base64 command for PNGs when the binary channel is unavailable;
+ * typed backend errors are rethrown as they are.
+ */
+export async function saveImage(
+  image: Blob,
+  { saveDir, copyToClip }: SaveImageOptions,
+): Promise<SaveImageResponse> {
+  const type = image.type || PNG;
+  try {
+    return await invoke<SaveImageResponse>(
+      "save_edited_image_bytes",
+      new Uint8Array(await image.arrayBuffer()),
+      {
+        headers: {
+          "Content-Type": type,
+          "X-Save-Dir": encodeURIComponent(saveDir),
+          "X-Copy-To-Clip": copyToClip ? "1" : "0",
+        },
+      },
+    );
+  } catch (error) {
+    if (isBackendError(error) || type !== PNG) throw error;
+    console.warn("Binary save failed, falling back to base64:", error);
+    return invoke<SaveImageResponse>("save_edited_image", {
+      imageData: await blobToDataUrl(image),
+      saveDir,
+      copyToClip,
+    });
+  }
+}
+
+/**
+ * Render `imagePath` with effects in Rust, received as raw bytes in
+ * `format`. Formats the backend does not support are retried as PNG, and
+ * the base64 command is used when the binary channel is unavailable.
+ */
+export async function renderImageWithEffects(
+  imagePath: string,
+  settings: RustRenderSettings,
+  format = PNG,
+): Promise<Blob> {
+  try {
+    const bytes = await invoke<ArrayBuffer>("render_image_bytes", { imagePath, settings, format });
+    return new Blob([bytes], { type: format });
+  } catch (error) {
+    if (isBackendError(error)) {
+      if (error.kind === "unsupported" && format !== PNG) {
+        return renderImageWithEffects(imagePath, settings, PNG);
+      }
+      throw error;
+    }
+    console.warn("Binary render failed, falling back to base64:", error);
+    const dataUrl = await invoke<string>("render_image_with_effects_rust", { imagePath, settings });
+    return dataUrlToBlob(dataUrl);
+  }
+}
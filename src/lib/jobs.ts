@@ -0,0 +1,35 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+
+export type JobKind = "capture" | "stitch" | "render";
+
+/** Payload of `job-progress` events (`jobs::JobProgress`). */
+export type JobProgress = {
+  jobId: string;
+  kind: JobKind;
+  stage: string;
+  done: number;
+  total: number;
+};
+
+let nextJobId = 0;
+
+/** Id to pass as `jobId` to a command, so it can be cancelled while running. */
+export function createJobId(kind: JobKind): string {
+  nextJobId += 1;
+  return `${kind}-${Date.now()}-${nextJobId}`;
+}
+
+/** Cancel a running job. Resolves to whether it was still running. */
+export function cancelJob(jobId: string): Promise<boolean> {
+  return invoke<boolean>("cancel_job", { jobId }).catch(() => false);
+}
+
+export function onJobProgress(
+  jobId: string,
+  handler: (progress: JobProgress) => void,
+): Promise<UnlistenFn> {
+  return listen<JobProgress>("job-progress", (event) => {
+    if (event.payload.jobId === jobId) handler(event.payload);
+  });
+}
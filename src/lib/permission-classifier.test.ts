@@ -1,5 +1,5 @@
 import { describe, expect, it } from "vitest";
-import { classifyCaptureError } from "./capture-errors";
+import { classifyCaptureError, errorMessage } from "./capture-errors";
 
 describe("capture error classifier", () => {
   it("maps display-image creation failure to permission error", () => {
@@ -16,4 +16,30 @@ describe("capture error classifier", () => {
       "permission",
     );
   });
+
+  it("uses the kind of typed backend errors", () => {
+    expect(
+      classifyCaptureError({ kind: "permission", message: "Screen Recording permission required." }),
+    ).toBe("permission");
+    expect(classifyCaptureError({ kind: "ocr_empty", message: "No text recognized" })).toBe(
+      "ocr_empty",
+    );
+    expect(classifyCaptureError({ kind: "busy", message: "Capture in progress" })).toBe("busy");
+    expect(classifyCaptureError({ kind: "stitch_failed", message: "No overlap found" })).toBe(
+      "stitch",
+    );
+    expect(classifyCaptureError({ kind: "validation_failed", message: "Invalid region" })).toBe(
+      "validation",
+    );
+  });
+
+  it("does not re-parse typed messages", () => {
+    const error = {
+      kind: "capture_failed",
+      message: "Access to the display was lost",
+      details: { backend: "xcap" },
+    };
+    expect(classifyCaptureError(error)).toBe("io");
+    expect(errorMessage(error)).toBe("Access to the display was lost");
+  });
 });
@@ -0,0 +1,76 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { RustRenderSettings } from "@/lib/image-ipc";
+import type { Annotation } from "@/types/annotations";
+
+/** Extension of project files (`project::PROJECT_EXTENSION`). */
+export const PROJECT_EXTENSION = "ashot";
+
+/** Project metadata (`project::ProjectManifest`). Times are epoch milliseconds. */
+export type ProjectManifest = {
+  format: "ashot";
+  schemaVersion: number;
+  createdAt: number;
+  modifiedAt: number;
+  appVersion: string;
+  width: number;
+  height: number;
+  title?: string | null;
+};
+
+/** The editable part of a project (`commands::ProjectEdits`). */
+export type ProjectEdits = {
+  annotations: Annotation[];
+  settings: RustRenderSettings;
+  /** Editor state the backend stores as given, e.g. the selected background asset. */
+  editorState?: unknown;
+  title?: string | null;
+};
+
+/** A project opened for editing (`commands::LoadedProject`). */
+export type LoadedProject = {
+  path: string;
+  /** The original capture, extracted to the temp directory. */
+  capturePath: string;
+  annotations: Annotation[];
+  settings: RustRenderSettings;
+  editorState: unknown;
+  manifest: ProjectManifest;
+};
+
+export type SaveProjectOptions = {
+  /** Project to overwrite; a new one is created in `saveDir` otherwise. */
+  projectPath?: string | null;
+  saveDir: string;
+  jobId?: string;
+};
+
+/** Save the capture with its edits as a project. Resolves to the project path. */
+export function saveProject(
+  capturePath: string,
+  edits: ProjectEdits,
+  { projectPath = null, saveDir, jobId }: SaveProjectOptions,
+): Promise<string> {
+  return invoke<string>("save_project", { capturePath, edits, projectPath, saveDir, jobId });
+}
+
+/**
+ * Open a project. Projects saved by a newer version of the app are
+ * rejected with an `unsupported` backend error.
+ */
+export function loadProject(
+  projectPath: string,
+  tempDir: string,
+  jobId?: string,
+): Promise<LoadedProject> {
+  return invoke<LoadedProject>("load_project", { projectPath, tempDir, jobId });
+}
+
+/** Render a project to an image in `saveDir`. Resolves to the saved path. */
+export function flattenProject(
+  projectPath: string,
+  saveDir: string,
+  format = "image/png",
+  jobId?: string,
+): Promise<string> {
+  return invoke<string>("flatten_project", { projectPath, saveDir, format, jobId });
+}
@@ -1,5 +1,9 @@
 import { describe, expect, it } from "vitest";
-import { SCROLL_SESSION_TIMEOUT_MS, shouldAutoCancelScrollSession } from "./scroll-session";
+import {
+  SCROLL_SESSION_TIMEOUT_MS,
+  scrollStopMessageKey,
+  shouldAutoCancelScrollSession,
+} from "./scroll-session";
 
 describe("scroll session timeout", () => {
   it("returns false before timeout threshold", () => {
@@ -14,3 +18,15 @@ describe("scroll session timeout", () => {
     expect(shouldAutoCancelScrollSession(lastActive, now)).toBe(true);
   });
 });
+
+describe("scroll stop messages", () => {
+  it("maps backend limits to toast keys", () => {
+    expect(scrollStopMessageKey("ReachedMaxFrames")).toBe("app.toast.scrollStoppedMaxFrames");
+    expect(scrollStopMessageKey("NoNewContent")).toBe("app.toast.scrollStoppedNoNewContent");
+  });
+
+  it("leaves user and timeout stops to their own handlers", () => {
+    expect(scrollStopMessageKey("User")).toBeNull();
+    expect(scrollStopMessageKey("Timeout")).toBeNull();
+  });
+});
@@ -1,3 +1,5 @@
+import type { FrameHandle } from "@/lib/frame-store";
+
 export type ScrollSessionState =
   | "idle"
   | "selecting"
@@ -7,18 +9,82 @@ export type ScrollSessionState =
   | "previewing"
   | "failed";
 
+/** Mirrors `capture_core::StopReason`. */
+export type ScrollStopReason =
+  | "User"
+  | "Timeout"
+  | "ReachedMaxHeight"
+  | "ReachedMaxFrames"
+  | "NoNewContent"
+  | "ConsecutiveFailures";
+
+/** Mirrors `capture_core::SkipReason`. */
+export type ScrollSkipReason = "Duplicate" | "TooSmallDelta" | "MatchFailed" | "Throttled" | "Paused";
+
+/** Mirrors `capture_core::ScrollDirection`. */
+export type ScrollDirection = "Vertical" | "Horizontal";
+
+/**
+ * Mirrors `capture_core::MatchRegion`: the band used for matching frames, in
+ * percent of the frame width (vertical) or height (horizontal).
+ */
+export type ScrollMatchRegion = {
+  start_percent: number;
+  end_percent: number;
+};
+
+export const DEFAULT_SCROLL_MATCH_REGION: ScrollMatchRegion = { start_percent: 15, end_percent: 85 };
+
+/** Mirrors `capture_core::AppendResult` (serde externally tagged). */
+export type ScrollAppendResult =
+  | { Accepted: { dy: number; score: number } }
+  | { Skipped: ScrollSkipReason }
+  | { AutoStopped: ScrollStopReason };
+
+/** Mirrors `capture_core::ScrollProgress`. */
+export type ScrollProgress = {
+  frames: number;
+  captured_height_px: number;
+  state: "Ready" | "Capturing" | "Paused" | "Done" | "Error";
+};
+
+export type ScrollSessionInfo = {
+  sessionId: string;
+  sessionDir: string;
+  framesDir: string;
+  progress: ScrollProgress;
+};
+
+/** A session left on disk by an earlier run, offered for recovery on launch. */
+export type RecoverableScrollSession = {
+  sessionId: string;
+  sessionDir: string;
+  createdAt: number;
+  updatedAt: number;
+  frameCount: number;
+  capturedHeightPx: number;
+  state: ScrollProgress["state"];
+  stopReason: ScrollStopReason | null;
+};
+
 export type StitchResult = {
   path: string;
   totalFrames: number;
   usedFrames: number;
   skippedFrames: number;
   finalHeight: number;
+  progress: ScrollProgress;
+  stopReason: ScrollStopReason | null;
 };
 
 export type ScrollPollResult = {
-  state: "unchanged" | "scrolling" | "captured";
-  framePath: string | null;
+  state: "unchanged" | "scrolling" | "captured" | "skipped" | "stopped";
+  /** The captured frame, held by the backend frame store until cleanup. */
+  frame: FrameHandle | null;
   frameCount: number;
+  progress: ScrollProgress;
+  append: ScrollAppendResult | null;
+  stopReason: ScrollStopReason | null;
 };
 
 export const SCROLL_SESSION_TIMEOUT_MS = 120_000;
@@ -30,3 +96,24 @@ export function shouldAutoCancelScrollSession(
 ) {
   return now - lastActivityAt >= timeoutMs;
 }
+
+/**
+ * Toast message key for a backend stop that should finish the session with
+ * the frames captured so far. `null` for reasons handled elsewhere (user
+ * cancel, timeout).
+ */
+export function scrollStopMessageKey(reason: ScrollStopReason): string | null {
+  switch (reason) {
+    case "ReachedMaxHeight":
+      return "app.toast.scrollStoppedMaxHeight";
+    case "ReachedMaxFrames":
+      return "app.toast.scrollStoppedMaxFrames";
+    case "NoNewContent":
+      return "app.toast.scrollStoppedNoNewContent";
+    case "ConsecutiveFailures":
+      return "app.toast.scrollStoppedFailures";
+    case "User":
+    case "Timeout":
+      return null;
+  }
+}
@@ -187,6 +187,25 @@ describe("editorStore - padding feature", () => {
     });
   });
 
+  describe("restore", () => {
+    it("should replace settings and annotations and clear history", () => {
+      act(() => {
+        editorActions.setPaddingTop(50);
+      });
+      const settings = { ...useEditorStore.getState().settings, paddingTop: 30 };
+
+      act(() => {
+        editorActions.restore(settings, []);
+      });
+
+      const state = useEditorStore.getState();
+      expect(state.settings.paddingTop).toBe(30);
+      expect(state.annotations).toEqual([]);
+      expect(state.past).toHaveLength(0);
+      expect(state.future).toHaveLength(0);
+    });
+  });
+
   describe("padding with other settings", () => {
     it("should not affect other settings when changing padding", () => {
       const initialNoise = useEditorStore.getState().settings.noiseAmount;
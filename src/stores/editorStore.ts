@@ -111,6 +111,9 @@ interface EditorActions {
   deleteAnnotation: (id: string) => void;
   setAnnotations: (annotations: Annotation[]) => void;
 
+  // Replace settings and annotations, e.g. from an opened project
+  restore: (settings: EditorSettings, annotations: Annotation[]) => void;
+
   // History actions
   undo: () => void;
   redo: () => void;
@@ -513,6 +516,15 @@ export const useEditorStore = create<EditorStore>()(
         });
       },
 
+      restore: (settings, annotations) => {
+        set((state) => {
+          state.settings = settings;
+          state.annotations = annotations;
+          state.past = [];
+          state.future = [];
+        });
+      },
+
       // ========================================
       // History
       // ========================================
@@ -657,6 +669,7 @@ export const editorActions = {
   get updateAnnotationTransient() { return useEditorStore.getState().updateAnnotationTransient; },
   get deleteAnnotation() { return useEditorStore.getState().deleteAnnotation; },
   get setAnnotations() { return useEditorStore.getState().setAnnotations; },
+  get restore() { return useEditorStore.getState().restore; },
   get undo() { return useEditorStore.getState().undo; },
   get redo() { return useEditorStore.getState().redo; },
   get pushHistory() { return useEditorStore.getState().pushHistory; },
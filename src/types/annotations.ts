@@ -65,6 +65,10 @@ export interface TextAnnotation extends BaseAnnotation {
   type: "text";
   text: string;
   fontSize: number;
+  /**
+   * Canvas font family. The Rust renderer ignores it and draws all text in
+   * its bundled DejaVu Sans, a close match for the editor's Arial.
+   */
   fontFamily: string;
   width: number;
   height: number;
@@ -17,6 +17,35 @@ export type MonitorShot = {
   path: string;
 };
 
+type LayoutRect = {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+};
+
+/** Where a monitor landed in a virtual desktop composite (`capture_core::composite::DesktopLayout`). */
+export type DesktopLayout = {
+  outputScale: number;
+  bounds: LayoutRect;
+  width: number;
+  height: number;
+  monitors: {
+    id: number;
+    name: string;
+    isPrimary: boolean;
+    scaleFactor: number;
+    logical: LayoutRect;
+    pixels: LayoutRect;
+  }[];
+};
+
+export type VirtualDesktopShot = {
+  path: string;
+  layoutPath: string;
+  layout: DesktopLayout;
+};
+
 export type CaptureWindowInfo = {
   id: number;
   appName: string;